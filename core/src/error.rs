@@ -2,18 +2,24 @@
 use crate::v2::models::{DataType, ParameterIn};
 use thiserror::Error;
 
+use std::fmt;
+
 /// Errors related to spec validation.
 #[derive(Debug, Error)]
 pub enum ValidationError {
     /// Failed to resolve the schema because an invalid URI was provided for
     /// `$ref` field.
     ///
-    /// Currently, we only support `#/{definitions,parameters}/Name` in `$ref` field.
+    /// Currently, we only support `#/{definitions,parameters,responses}/Name` in `$ref`
+    /// field, and references to definitions in other documents (when resolving with a loader).
     #[error("Invalid $ref URI {:?}. Only relative URIs are supported.", _0)]
     InvalidRefURI(String),
     /// The specified reference is missing in the spec.
     #[error("Reference missing in spec: {}", _0)]
     MissingReference(String),
+    /// Failed to load the document (or the definition) referred by some `$ref` field.
+    #[error("Failed to load {:?}: {}", _0, _1)]
+    LoadingReference(String, String),
     /// If a parameter specifies body, then schema must be specified.
     #[error(
         "Parameter {:?} in path {:?} is a body but the schema is missing",
@@ -36,3 +42,87 @@ pub enum ValidationError {
     )]
     InvalidParameterType(String, String, Option<DataType>, ParameterIn),
 }
+
+/// Prefix for `$ref` values pointing to definitions (in v2 specs).
+pub const DEF_REF_PREFIX: &str = "#/definitions/";
+/// Prefix for `$ref` values pointing to global parameters (in v2 specs).
+pub const PARAM_REF_PREFIX: &str = "#/parameters/";
+/// Prefix for `$ref` values pointing to global responses (in v2 specs).
+pub const RESP_REF_PREFIX: &str = "#/responses/";
+
+/// Appends the given key to the given JSON pointer (escaping `~` and `/` in the key).
+pub fn pointer_child(pointer: &str, key: &str) -> String {
+    String::from(pointer) + "/" + &key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Error found at some location in the spec.
+#[derive(Debug)]
+pub struct Diagnostic<E> {
+    /// JSON pointer to the offending value in the spec (empty for the spec itself).
+    pub pointer: String,
+    /// The actual error.
+    pub error: E,
+}
+
+/// Report of all the errors found in a spec. This is returned when the errors
+/// are collected (instead of failing at the first one) in diagnostics mode.
+#[derive(Debug)]
+pub struct Report<E> {
+    /// Errors in the order they were found.
+    pub diagnostics: Vec<Diagnostic<E>>,
+}
+
+impl<E> Report<E> {
+    /// Adds an error found at the given location.
+    pub fn push(&mut self, pointer: impl Into<String>, error: E) {
+        self.diagnostics.push(Diagnostic {
+            pointer: pointer.into(),
+            error,
+        });
+    }
+
+    /// Whether this report doesn't have any errors.
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    /// Number of errors in this report.
+    pub fn len(&self) -> usize {
+        self.diagnostics.len()
+    }
+
+    /// Returns the given value if this report doesn't have
+    /// any errors, or the report itself otherwise.
+    pub fn into_result<T>(self, value: T) -> Result<T, Self> {
+        if self.is_empty() {
+            Ok(value)
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl<E> Default for Report<E> {
+    fn default() -> Self {
+        Report {
+            diagnostics: vec![],
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for Report<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Found {} error(s) in spec:", self.len())?;
+        for d in &self.diagnostics {
+            if d.pointer.is_empty() {
+                write!(f, "\n  {}", d.error)?;
+            } else {
+                write!(f, "\n  at {}: {}", d.pointer, d.error)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for Report<E> {}
@@ -1,14 +1,20 @@
 //! Interior mutability stuff.
 
 #[cfg(feature = "v2")]
-use crate::v2::models::Resolvable;
+use crate::error::DEF_REF_PREFIX;
 #[cfg(feature = "v2")]
-use serde::{Serialize, Serializer};
+use crate::v2::{models::Resolvable, schema::Schema};
+#[cfg(feature = "v2")]
+use serde::{ser::SerializeMap, Serialize, Serializer};
 
+/// Serializes the schema as it was in the spec. Resolved references are
+/// serialized as `$ref` (using the original reference, or the name of the
+/// definition if there isn't one), so that named definitions (including cyclic
+/// ones) aren't inlined. Anonymous schemas are inlined.
 #[cfg(feature = "v2")]
 impl<T> Serialize for Resolvable<T>
 where
-    T: Serialize,
+    T: Serialize + Schema,
 {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -16,7 +22,21 @@ where
     {
         match self {
             Resolvable::Raw(s) => s.serialize(serializer),
-            Resolvable::Resolved { new, .. } => new.serialize(serializer),
+            Resolvable::Resolved { new, old } => {
+                if old.read().reference().is_some() {
+                    return old.serialize(serializer);
+                }
+
+                let name = new.read().name().map(String::from);
+                match name {
+                    Some(name) => {
+                        let mut map = serializer.serialize_map(Some(1))?;
+                        map.serialize_entry("$ref", &format!("{}{}", DEF_REF_PREFIX, name))?;
+                        map.end()
+                    }
+                    None => new.serialize(serializer),
+                }
+            }
         }
     }
 }
@@ -8,5 +8,10 @@ mod error;
 pub mod im;
 #[cfg(feature = "v2")]
 pub mod v2;
+#[cfg(feature = "v3")]
+pub mod v3;
 
-pub use self::error::ValidationError;
+pub use self::error::{
+    pointer_child, Diagnostic, Report, ValidationError, DEF_REF_PREFIX, PARAM_REF_PREFIX,
+    RESP_REF_PREFIX,
+};
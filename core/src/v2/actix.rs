@@ -487,7 +487,7 @@ where
         let mut security_map = BTreeMap::new();
         let scopes = scheme.scopes.keys().map(String::clone).collect();
         security_map.insert(name.into(), scopes);
-        op.security.push(security_map);
+        op.security.get_or_insert_with(Vec::new).push(security_map);
     }
 }
 
@@ -0,0 +1,46 @@
+//! Loaders for documents referred by `$ref` values pointing outside the spec
+//! (like `common.yaml#/definitions/Error`).
+
+pub use url::Url;
+
+use std::error::Error;
+use std::fs;
+
+/// Error returned by loaders.
+pub type LoaderError = Box<dyn Error + Send + Sync + 'static>;
+
+/// Loads the (JSON or YAML) documents referred by external `$ref` values.
+///
+/// This is implemented for closures, so that any function taking a URI
+/// and returning the contents can be used as a loader.
+pub trait RefLoader {
+    /// Loads the contents of the document at the given absolute URI
+    /// (without the fragment).
+    fn load(&self, uri: &Url) -> Result<String, LoaderError>;
+}
+
+impl<F> RefLoader for F
+where
+    F: Fn(&Url) -> Result<String, LoaderError>,
+{
+    fn load(&self, uri: &Url) -> Result<String, LoaderError> {
+        self(uri)
+    }
+}
+
+/// Loader for documents in the local filesystem (i.e., `file://` URIs).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FileLoader;
+
+impl RefLoader for FileLoader {
+    fn load(&self, uri: &Url) -> Result<String, LoaderError> {
+        if uri.scheme() != "file" {
+            return Err(format!("unsupported scheme {:?}", uri.scheme()).into());
+        }
+
+        let path = uri
+            .to_file_path()
+            .map_err(|_| format!("{} is not a valid file path", uri))?;
+        Ok(fs::read_to_string(path)?)
+    }
+}
@@ -4,6 +4,8 @@
 #[cfg(feature = "actix")]
 mod actix;
 mod extensions;
+#[cfg(feature = "codegen")]
+pub mod loader;
 pub mod models;
 #[cfg(feature = "codegen")]
 mod resolver;
@@ -16,10 +18,14 @@ pub use self::models::{DefaultSchema, ResolvableApi};
 pub use self::schema::Schema;
 pub use paperclip_macros::*;
 
+#[cfg(feature = "codegen")]
+use self::loader::{RefLoader, Url};
 #[cfg(feature = "codegen")]
 use self::resolver::Resolver;
 #[cfg(feature = "codegen")]
-use crate::error::ValidationError;
+use crate::error::{Report, ValidationError};
+#[cfg(feature = "codegen")]
+use serde::de::DeserializeOwned;
 
 #[cfg(feature = "codegen")]
 impl<S: Schema + Default> ResolvableApi<S> {
@@ -30,13 +36,80 @@ impl<S: Schema + Default> ResolvableApi<S> {
     /// substitutes the referenced IDs with the pointer to schema objects
     /// and returns the resolved object or an error if it encountered one.
     pub fn resolve(self) -> Result<ResolvableApi<S>, ValidationError> {
+        self.resolve_using(|_| Ok(()), None)
+    }
+
+    /// Same as `resolve`, but this doesn't stop at the first error. Instead,
+    /// it collects all the errors (along with their locations in the spec)
+    /// and returns them in a report at the end.
+    pub fn resolve_with_report(self) -> Result<ResolvableApi<S>, Report<ValidationError>> {
+        self.resolve_reporting(|_| Ok(()))
+    }
+
+    /// Resolves the references (using a resolver prepared by the given function)
+    /// and collects all the errors into a report.
+    fn resolve_reporting<F>(self, prepare: F) -> Result<ResolvableApi<S>, Report<ValidationError>>
+    where
+        F: FnOnce(&mut Resolver<S>) -> Result<(), ValidationError>,
+    {
+        let mut report = Report::default();
+        match self.resolve_into_report(prepare, &mut report) {
+            Some(api) => report.into_result(api),
+            None => Err(report),
+        }
+    }
+
+    /// Resolves the references (using a resolver prepared by the given function)
+    /// and collects the errors into the given report. This returns the resolved
+    /// spec even if there were errors, unless the resolver couldn't go on.
+    fn resolve_into_report<F>(
+        self,
+        prepare: F,
+        report: &mut Report<ValidationError>,
+    ) -> Option<ResolvableApi<S>>
+    where
+        F: FnOnce(&mut Resolver<S>) -> Result<(), ValidationError>,
+    {
+        match self.resolve_using(prepare, Some(report)) {
+            Ok(api) => Some(api),
+            Err(e) => {
+                report.push("", e);
+                None
+            }
+        }
+    }
+
+    /// Resolves the references using a resolver which has been prepared
+    /// using the given function. If a report has been given, then the errors
+    /// are collected into that report.
+    fn resolve_using<F>(
+        self,
+        prepare: F,
+        report: Option<&mut Report<ValidationError>>,
+    ) -> Result<ResolvableApi<S>, ValidationError>
+    where
+        F: FnOnce(&mut Resolver<S>) -> Result<(), ValidationError>,
+    {
         let mut resolver = Resolver::from((
             self.definitions,
             self.paths,
             self.parameters,
             self.responses,
         ));
-        resolver.resolve()?;
+        if report.is_some() {
+            resolver.report = Some(Default::default());
+        }
+
+        if let Err(e) = prepare(&mut resolver) {
+            resolver.report("", e)?;
+        }
+
+        let res = resolver.resolve();
+        if let (Some(report), Some(r)) = (report, resolver.report.take()) {
+            report.diagnostics.extend(r.into_inner().diagnostics);
+        }
+
+        res?;
         Ok(ResolvableApi {
             swagger: self.swagger,
             info: self.info,
@@ -59,3 +132,82 @@ impl<S: Schema + Default> ResolvableApi<S> {
         })
     }
 }
+
+#[cfg(feature = "codegen")]
+impl<S: Schema + Default + DeserializeOwned> ResolvableApi<S> {
+    /// Same as `resolve`, but this also resolves the references to definitions
+    /// in other documents (like `common.yaml#/definitions/Error`).
+    ///
+    /// Relative references are resolved against the given URI (where this spec
+    /// lives) and the documents are loaded using the given loader. The loaded
+    /// definitions are added to this spec's definitions. If some definition
+    /// already has the same name, then the loaded one is prefixed with the
+    /// name of its document (for example, `CommonError`).
+    pub fn resolve_with_loader<L>(
+        self,
+        base: &Url,
+        loader: &L,
+    ) -> Result<ResolvableApi<S>, ValidationError>
+    where
+        L: RefLoader,
+    {
+        self.resolve_using(|r| r.resolve_external(base, loader), None)
+    }
+
+    /// Loads the definitions referred from other documents into this spec
+    /// (like `resolve_with_loader`) and replaces those references with local
+    /// ones, but doesn't resolve anything else. This is useful for bundling
+    /// the spec into a single document.
+    pub fn load_external_refs<L>(
+        self,
+        base: &Url,
+        loader: &L,
+    ) -> Result<ResolvableApi<S>, ValidationError>
+    where
+        L: RefLoader,
+    {
+        let mut resolver = Resolver::from((
+            self.definitions,
+            self.paths,
+            self.parameters,
+            self.responses,
+        ));
+        resolver.resolve_external(base, loader)?;
+        Ok(ResolvableApi {
+            definitions: resolver.defs,
+            paths: resolver.paths,
+            parameters: resolver.params,
+            responses: resolver.resp,
+            ..self
+        })
+    }
+
+    /// Same as `resolve_with_loader`, but this collects all the errors
+    /// into a report (like `resolve_with_report`).
+    pub fn resolve_with_loader_and_report<L>(
+        self,
+        base: &Url,
+        loader: &L,
+    ) -> Result<ResolvableApi<S>, Report<ValidationError>>
+    where
+        L: RefLoader,
+    {
+        self.resolve_reporting(|r| r.resolve_external(base, loader))
+    }
+
+    /// Same as `resolve_with_loader_and_report`, but this collects the errors
+    /// into the given report and returns the resolved spec even if there were
+    /// errors (so that it can be used for finding more errors, like in codegen).
+    /// This returns `None` only if the spec couldn't be resolved at all.
+    pub fn resolve_with_loader_into_report<L>(
+        self,
+        base: &Url,
+        loader: &L,
+        report: &mut Report<ValidationError>,
+    ) -> Option<ResolvableApi<S>>
+    where
+        L: RefLoader,
+    {
+        self.resolve_into_report(|r| r.resolve_external(base, loader), report)
+    }
+}
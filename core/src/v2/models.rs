@@ -18,7 +18,7 @@ use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{self, Display};
 use std::ops::{Deref, DerefMut};
-use std::sync::Arc;
+use std::sync::{Arc, Weak};
 
 lazy_static! {
     /// Regex that can be used for fetching templated path parameters.
@@ -29,6 +29,9 @@ lazy_static! {
 // Ensure that they're all lowercase for case insensitive check.
 const SPECIAL_HEADERS: &[&str] = &["content-type", "accept", "authorization"];
 
+/// Byte order mark which may precede UTF-8 documents.
+pub const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
 /// OpenAPI version.
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq)]
 pub enum Version {
@@ -184,6 +187,19 @@ impl SpecFormat {
             SpecFormat::Yaml => &*YAML_MIME,
         }
     }
+
+    /// Guesses the format of the given document from its first significant
+    /// character (skipping any UTF-8 BOM and leading whitespace).
+    ///
+    /// JSON documents must begin with an object, so anything else
+    /// is assumed to be YAML.
+    pub fn sniff(contents: &[u8]) -> Self {
+        let contents = contents.strip_prefix(UTF8_BOM).unwrap_or(contents);
+        match contents.iter().find(|b| !b.is_ascii_whitespace()) {
+            Some(b'{') => SpecFormat::Json,
+            _ => SpecFormat::Yaml,
+        }
+    }
 }
 
 impl<P, R, S> Api<P, R, S> {
@@ -195,6 +211,19 @@ impl<P, R, S> Api<P, R, S> {
     ) -> Cow<'_, str> {
         PATH_TEMPLATE_REGEX.replace_all(path, |c: &Captures| f(&c[1]))
     }
+
+    /// Returns the given path with its templated parameters replaced by `:`
+    /// (for checking whether paths are the same regardless of templating),
+    /// along with the names of those parameters.
+    pub fn path_template(path: &str) -> (String, Vec<String>) {
+        let mut params = vec![];
+        let path_fmt = Self::path_parameters_map(path, |p| {
+            params.push(p.to_owned());
+            ":".into()
+        });
+
+        (path_fmt.into_owned(), params)
+    }
 }
 
 use crate as paperclip; // hack for proc macro
@@ -447,6 +476,9 @@ pub struct Parameter<S> {
     pub multiple_of: Option<f32>,
     #[serde(default, rename = "enum", skip_serializing_if = "Vec::is_empty")]
     pub enum_: Vec<serde_json::Value>,
+    /// Extensions (`x-` fields) and other unknown fields in this parameter.
+    #[serde(flatten)]
+    pub extensions: BTreeMap<String, serde_json::Value>,
 }
 
 /// Items object.
@@ -489,10 +521,7 @@ pub struct Items {
     pub multiple_of: Option<f32>,
 }
 
-impl<S> Parameter<Resolvable<S>>
-where
-    S: Schema,
-{
+impl<S> Parameter<S> {
     /// Checks the validity of this parameter using the relative URL
     /// path it's associated with.
     pub fn check(&self, path: &str) -> Result<(), ValidationError> {
@@ -590,6 +619,22 @@ pub enum CollectionFormat {
     Multi,
 }
 
+impl CollectionFormat {
+    /// Checks whether the given collection formats of an array (starting from
+    /// the root array and followed by its nested arrays) are allowed for
+    /// parameters in the given location.
+    ///
+    /// Multiple instances (`multi`) are allowed only for the root array
+    /// in query and form data parameters.
+    pub fn is_valid_nesting(in_: ParameterIn, formats: &[CollectionFormat]) -> bool {
+        let is_url_encoded = in_ == ParameterIn::Query || in_ == ParameterIn::FormData;
+        formats
+            .iter()
+            .enumerate()
+            .all(|(i, &f)| f != CollectionFormat::Multi || (i == 0 && is_url_encoded))
+    }
+}
+
 /// Operation that can be traversed and resolved for codegen.
 pub type ResolvableOperation<S> = Operation<ResolvableParameter<S>, ResolvableResponse<S>>;
 
@@ -602,19 +647,21 @@ pub type DefaultOperationRaw = Operation<DefaultParameterRaw, DefaultResponseRaw
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Operation<P, R> {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub operation_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
-    // *NOTE:* `consumes` and `produces` are optional, because
-    // local media ranges can be used to override global media ranges
-    // (including setting it to empty), so we cannot go for an empty set.
+    // *NOTE:* `consumes`, `produces` and `security` are optional, because
+    // local values can be used to override global values (including
+    // setting them to empty), so we cannot go for an empty set.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub consumes: Option<BTreeSet<MediaRange>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub produces: Option<BTreeSet<MediaRange>>,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub security: Vec<BTreeMap<String, Vec<String>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub security: Option<Vec<BTreeMap<String, Vec<String>>>>,
     #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
     pub schemes: BTreeSet<OperationProtocol>,
     // FIXME: Validate using `http::status::StatusCode::from_u16`
@@ -813,6 +860,10 @@ pub enum Resolvable<S> {
     },
 }
 
+/// Weak reference to a schema. This is used for referring to other schemas
+/// without creating reference cycles (like the subtypes of some schema).
+pub type WeakResolvable<S> = Weak<RwLock<S>>;
+
 impl<S> Resolvable<S>
 where
     S: Schema,
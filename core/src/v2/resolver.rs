@@ -1,23 +1,26 @@
 use super::{
+    loader::{RefLoader, Url},
     models::{
         Either, HttpMethod, Reference, Resolvable, ResolvableParameter, ResolvablePathItem,
         ResolvableResponse,
     },
     Schema,
 };
-use crate::error::ValidationError;
+use crate::error::{
+    pointer_child, Report, ValidationError, DEF_REF_PREFIX, PARAM_REF_PREFIX, RESP_REF_PREFIX,
+};
 use heck::CamelCase;
+use parking_lot::RwLock;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
 
 use std::cell::RefCell;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::mem;
+use std::sync::Arc;
 
 // FIXME: The resolver is not in its best. It "just" works atm.
 
-const DEF_REF_PREFIX: &str = "#/definitions/";
-const PARAM_REF_PREFIX: &str = "#/parameters/";
-const RESP_REF_PREFIX: &str = "#/responses/";
-
 type DefinitionsMap<S> = BTreeMap<String, Resolvable<S>>;
 type OperationsMap<S> = BTreeMap<String, ResolvablePathItem<S>>;
 type ParametersMap<S> = BTreeMap<String, ResolvableParameter<S>>;
@@ -38,6 +41,14 @@ pub(crate) struct Resolver<S> {
     pub params: ParametersMap<S>,
     /// Globally defined responses;
     pub resp: ResponsesMap<S>,
+    /// Errors collected so far (if we're collecting them instead of failing
+    /// at the first one).
+    pub report: Option<RefCell<Report<ValidationError>>>,
+    /// Schemas whose references couldn't be resolved (so that we don't
+    /// report them again when we come across them from elsewhere).
+    failed: RefCell<HashSet<*const RwLock<S>>>,
+    /// Locations of anonymous definitions moved from operations to global definitions.
+    hoisted: HashMap<String, String>,
 }
 
 impl<S>
@@ -62,6 +73,9 @@ impl<S>
             paths,
             params,
             resp,
+            report: None,
+            failed: Default::default(),
+            hoisted: HashMap::new(),
         }
     }
 }
@@ -78,7 +92,8 @@ where
         let mut paths = mem::replace(&mut self.paths, BTreeMap::new());
         paths.iter_mut().try_for_each(|(path, map)| {
             log::trace!("Checking path: {}", path);
-            self.resolve_operations(path, map)
+            let pointer = pointer_child(&pointer_child("", "paths"), path);
+            self.resolve_operations(path, map, &pointer)
         })?;
         self.paths = paths;
 
@@ -89,7 +104,7 @@ where
 
         for (name, schema) in &self.defs {
             log::trace!("Entering: {}", name);
-            self.resolve_definitions_no_root_ref(schema)?;
+            self.resolve_definitions_no_root_ref(schema, &self.def_pointer(name))?;
 
             for def in self.cyclic_defs.borrow_mut().drain(..) {
                 log::debug!(
@@ -100,14 +115,55 @@ where
             }
         }
 
+        self.record_subtypes();
         Ok(())
     }
 
+    /// Reports the error found at the given location. If we're collecting errors,
+    /// then it's added to the report (so that we can move on), otherwise it's returned.
+    pub fn report(&self, pointer: &str, err: ValidationError) -> Result<(), ValidationError> {
+        match self.report.as_ref() {
+            Some(r) => {
+                log::debug!("Error at {:?}: {}", pointer, err);
+                r.borrow_mut().push(pointer, err);
+                Ok(())
+            }
+            None => Err(err),
+        }
+    }
+
+    /// Returns the location of the given definition in spec.
+    fn def_pointer(&self, name: &str) -> String {
+        self.hoisted
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| pointer_child(&pointer_child("", "definitions"), name))
+    }
+
+    /// Adds the definitions extending (through `allOf`) some polymorphic
+    /// definition (i.e., one with a discriminator) to its subtypes.
+    fn record_subtypes(&self) {
+        for (name, schema) in &self.defs {
+            let s = schema.read();
+            for base in s.all_of().into_iter().flatten() {
+                if let Resolvable::Resolved { new, .. } = base {
+                    if Arc::ptr_eq(new, schema) || new.read().discriminator().is_none() {
+                        continue;
+                    }
+
+                    log::trace!("Recording {:?} as subtype of {:?}", name, new.read().name());
+                    new.write().add_subtype(schema);
+                }
+            }
+        }
+    }
+
     /// We've passed some definition. Resolve it assuming that it doesn't
     /// contain any reference.
     fn resolve_definitions_no_root_ref(
         &self,
         schema: &Resolvable<S>,
+        pointer: &str,
     ) -> Result<(), ValidationError> {
         let mut schema = match schema.try_write() {
             Some(s) => s,
@@ -118,13 +174,16 @@ where
         };
 
         if let Some(inner) = schema.items_mut().take() {
-            return self.resolve_definitions(inner);
+            return self.resolve_definitions(inner, &pointer_child(pointer, "items"));
         }
 
         if let Some(props) = schema.properties_mut().take() {
             props.iter_mut().try_for_each(|(k, s)| {
                 log::trace!("Resolving property {:?}", k);
-                self.resolve_definitions(s)
+                self.resolve_definitions(
+                    s,
+                    &pointer_child(&pointer_child(pointer, "properties"), k),
+                )
             })?;
         }
 
@@ -133,7 +192,16 @@ where
             .take()
             .and_then(|s| s.right_mut())
         {
-            self.resolve_definitions(props)?;
+            self.resolve_definitions(props, &pointer_child(pointer, "additionalProperties"))?;
+        }
+
+        if let Some(schemas) = schema.all_of_mut() {
+            schemas.iter_mut().enumerate().try_for_each(|(i, s)| {
+                self.resolve_definitions(
+                    s,
+                    &pointer_child(&pointer_child(pointer, "allOf"), &i.to_string()),
+                )
+            })?;
         }
 
         Ok(())
@@ -141,7 +209,11 @@ where
 
     /// Resolve the given definition. If it contains a reference, find and assign it,
     /// otherwise traverse further.
-    fn resolve_definitions(&self, schema: &mut Resolvable<S>) -> Result<(), ValidationError> {
+    fn resolve_definitions(
+        &self,
+        schema: &mut Resolvable<S>,
+        pointer: &str,
+    ) -> Result<(), ValidationError> {
         let ref_def = {
             let s = match schema.try_read() {
                 Some(s) => s,
@@ -153,13 +225,25 @@ where
 
             if let Some(ref_name) = s.reference() {
                 log::trace!("Resolving definition {}", ref_name);
-                Some(self.resolve_definition_reference(ref_name)?)
+                match self.resolve_definition_reference(ref_name) {
+                    Ok(d) => Some((ref_name[DEF_REF_PREFIX.len()..].to_owned(), d)),
+                    Err(e) => {
+                        // Unresolved schemas can be reached again (in diagnostics mode).
+                        if self.failed.borrow_mut().insert(Arc::as_ptr(&**schema)) {
+                            self.report(pointer, e)?;
+                        }
+
+                        return Ok(());
+                    }
+                }
             } else {
                 None
             }
         };
 
-        if let Some(new) = ref_def {
+        let mut pointer = pointer.to_owned();
+        if let Some((name, new)) = ref_def {
+            pointer = self.def_pointer(&name);
             *schema = match schema {
                 Resolvable::Raw(old) => Resolvable::Resolved {
                     old: old.clone(),
@@ -169,7 +253,7 @@ where
             };
         }
 
-        self.resolve_definitions_no_root_ref(&*schema)
+        self.resolve_definitions_no_root_ref(&*schema, &pointer)
     }
 
     /// Resolve a given operation.
@@ -177,19 +261,23 @@ where
         &mut self,
         path: &str,
         map: &mut ResolvablePathItem<S>,
+        pointer: &str,
     ) -> Result<(), ValidationError> {
         for (&method, op) in &mut map.methods {
-            self.resolve_parameters(Some(method), path, &mut op.parameters)?;
-            for resp in op.responses.values_mut() {
-                let ref_resp = if let Some(r) = resp.left() {
+            let op_pointer = pointer_child(pointer, &method.to_string().to_lowercase());
+            let params_pointer = pointer_child(&op_pointer, "parameters");
+            self.resolve_parameters(Some(method), path, &mut op.parameters, &params_pointer)?;
+            for (code, resp) in &mut op.responses {
+                let pointer = pointer_child(&pointer_child(&op_pointer, "responses"), code);
+                if let Some(r) = resp.left() {
                     log::trace!("Resolving response {}", r.reference);
-                    Some(self.resolve_response_reference(&r.reference)?)
-                } else {
-                    None
-                };
-
-                if let Some(new) = ref_resp {
-                    *resp = Either::Right(new);
+                    match self.resolve_response_reference(&r.reference) {
+                        Ok(new) => *resp = Either::Right(new),
+                        Err(e) => {
+                            self.report(&pointer, e)?;
+                            continue;
+                        }
+                    }
                 }
 
                 let mut response = resp.write();
@@ -198,11 +286,13 @@ where
                     Some(method),
                     path,
                     "Response",
+                    &pointer_child(&pointer, "schema"),
                 )?;
             }
         }
 
-        self.resolve_parameters(None, path, &mut map.parameters)
+        let params_pointer = pointer_child(pointer, "parameters");
+        self.resolve_parameters(None, path, &mut map.parameters, &params_pointer)
     }
 
     /// Resolve the given bunch of parameters.
@@ -211,21 +301,24 @@ where
         method: Option<HttpMethod>,
         path: &str,
         params: &mut Vec<Either<Reference, ResolvableParameter<S>>>,
+        pointer: &str,
     ) -> Result<(), ValidationError> {
-        for p in params.iter_mut() {
-            let ref_param = if let Some(r) = p.left() {
+        for (i, p) in params.iter_mut().enumerate() {
+            let pointer = pointer_child(pointer, &i.to_string());
+            if let Some(r) = p.left() {
                 log::trace!("Resolving parameter {}", r.reference);
-                Some(self.resolve_parameter_reference(&r.reference)?)
-            } else {
-                None
-            };
-
-            if let Some(new) = ref_param {
-                *p = Either::Right(new);
+                match self.resolve_parameter_reference(&r.reference) {
+                    Ok(new) => *p = Either::Right(new),
+                    Err(e) => {
+                        self.report(&pointer, e)?;
+                        continue;
+                    }
+                }
             }
 
             let mut param = p.write();
-            self.resolve_operation_schema(&mut param.schema, method, path, "Body")?;
+            let pointer = pointer_child(&pointer, "schema");
+            self.resolve_operation_schema(&mut param.schema, method, path, "Body", &pointer)?;
         }
 
         Ok(())
@@ -238,6 +331,7 @@ where
         method: Option<HttpMethod>,
         path: &str,
         suffix: &str,
+        pointer: &str,
     ) -> Result<(), ValidationError> {
         let schema = match s.as_mut() {
             Some(s) => s,
@@ -253,12 +347,13 @@ where
                 let mut ref_schema = S::default();
                 ref_schema.set_reference(format!("{}{}", DEF_REF_PREFIX, def_name));
                 let old_schema = mem::replace(schema, ref_schema.into());
+                self.hoisted.insert(def_name.clone(), pointer.into());
                 self.defs.insert(def_name, old_schema);
             }
             _ => (),
         }
 
-        self.resolve_definitions(schema)?;
+        self.resolve_definitions(schema, pointer)?;
         Ok(())
     }
 
@@ -310,3 +405,207 @@ where
         Ok(resp.clone())
     }
 }
+
+impl<S> Resolver<S>
+where
+    S: Schema + Default + DeserializeOwned,
+{
+    /// Finds the `$ref` values pointing to definitions in other documents (relative
+    /// to the given base URI of this spec), loads those definitions (along with
+    /// the ones they refer to) using the given loader, adds them to the global
+    /// definitions and replaces the references with local ones.
+    ///
+    /// Definitions are added with their own names, unless some other definition
+    /// already has the name, in which case, it's prefixed with the document name.
+    pub fn resolve_external(
+        &mut self,
+        base: &Url,
+        loader: &dyn RefLoader,
+    ) -> Result<(), ValidationError> {
+        let mut external = ExternalRefs {
+            base,
+            loader,
+            docs: HashMap::new(),
+            names: HashMap::new(),
+            defs: vec![],
+            taken: self.defs.keys().cloned().collect(),
+        };
+
+        for schema in self.defs.values() {
+            external.visit(schema, None)?;
+        }
+
+        for param in self.params.values() {
+            if let Some(s) = param.read().schema.as_ref() {
+                external.visit(s, None)?;
+            }
+        }
+
+        for resp in self.resp.values() {
+            if let Some(s) = resp.read().schema.as_ref() {
+                external.visit(s, None)?;
+            }
+        }
+
+        for map in self.paths.values() {
+            let params = map
+                .methods
+                .values()
+                .flat_map(|op| op.parameters.iter())
+                .chain(map.parameters.iter())
+                .filter_map(|p| p.right());
+            for param in params {
+                if let Some(s) = param.read().schema.as_ref() {
+                    external.visit(s, None)?;
+                }
+            }
+
+            let responses = map
+                .methods
+                .values()
+                .flat_map(|op| op.responses.values())
+                .filter_map(|r| r.right());
+            for resp in responses {
+                if let Some(s) = resp.read().schema.as_ref() {
+                    external.visit(s, None)?;
+                }
+            }
+        }
+
+        self.defs.extend(external.defs);
+        Ok(())
+    }
+}
+
+/// Returns the URI of the document referred by the given URI.
+fn without_fragment(uri: &Url) -> Url {
+    let mut uri = uri.clone();
+    uri.set_fragment(None);
+    uri
+}
+
+/// State for loading definitions from external documents.
+struct ExternalRefs<'a, S> {
+    /// Base URI of the spec.
+    base: &'a Url,
+    loader: &'a dyn RefLoader,
+    /// Documents loaded so far (cached by their URIs).
+    docs: HashMap<Url, Value>,
+    /// Names assigned to the loaded definitions (by their URIs).
+    names: HashMap<Url, String>,
+    /// Definitions loaded so far.
+    defs: Vec<(String, Resolvable<S>)>,
+    /// Names of all definitions known so far.
+    taken: Vec<String>,
+}
+
+impl<'a, S> ExternalRefs<'a, S>
+where
+    S: Schema + DeserializeOwned,
+{
+    /// Visits the given schema (found in the document at the given URI, or the
+    /// spec itself if it's `None`) and replaces external references with local ones.
+    fn visit(&mut self, schema: &Resolvable<S>, doc: Option<&Url>) -> Result<(), ValidationError> {
+        let mut schema = schema.write();
+        if let Some(ref_) = schema.reference() {
+            let uri = match doc {
+                // Local reference in spec.
+                None if ref_.starts_with('#') => return Ok(()),
+                None => self.base.join(ref_),
+                Some(d) => d.join(ref_),
+            }
+            .map_err(|_| ValidationError::InvalidRefURI(ref_.into()))?;
+
+            // References back into the spec are local references.
+            if without_fragment(&uri) == without_fragment(self.base) {
+                schema.set_reference(format!("#{}", uri.fragment().unwrap_or_default()));
+                return Ok(());
+            }
+
+            let name = self.load(uri)?;
+            schema.set_reference(format!("{}{}", DEF_REF_PREFIX, name));
+            return Ok(());
+        }
+
+        if let Some(s) = schema.items_mut() {
+            self.visit(s, doc)?;
+        }
+
+        if let Some(props) = schema.properties_mut() {
+            props.values().try_for_each(|s| self.visit(s, doc))?;
+        }
+
+        if let Some(s) = schema
+            .additional_properties_mut()
+            .and_then(|s| s.right_mut())
+        {
+            self.visit(s, doc)?;
+        }
+
+        if let Some(schemas) = schema.all_of_mut() {
+            schemas.iter().try_for_each(|s| self.visit(s, doc))?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads the definition at the given URI (if it hasn't been loaded already)
+    /// and returns its name.
+    fn load(&mut self, uri: Url) -> Result<String, ValidationError> {
+        if let Some(name) = self.names.get(&uri) {
+            return Ok(name.clone());
+        }
+
+        let doc_uri = without_fragment(&uri);
+        if !self.docs.contains_key(&doc_uri) {
+            log::debug!("Loading {}", doc_uri);
+            let contents = self.loader.load(&doc_uri).map_err(|e| {
+                ValidationError::LoadingReference(doc_uri.to_string(), e.to_string())
+            })?;
+            let doc = serde_yaml::from_str(&contents).map_err(|e| {
+                ValidationError::LoadingReference(doc_uri.to_string(), e.to_string())
+            })?;
+            self.docs.insert(doc_uri.clone(), doc);
+        }
+
+        let pointer = uri.fragment().unwrap_or_default();
+        let value = self.docs[&doc_uri]
+            .pointer(pointer)
+            .ok_or_else(|| ValidationError::MissingReference(uri.to_string()))?;
+        let schema = serde_json::from_value::<S>(value.clone())
+            .map_err(|e| ValidationError::LoadingReference(uri.to_string(), e.to_string()))?;
+
+        let name = self.unique_name(&doc_uri, pointer);
+        log::trace!("Adding {} as {:?}", uri, name);
+        self.names.insert(uri, name.clone());
+
+        let schema = Resolvable::from(schema);
+        self.visit(&schema, Some(&doc_uri))?;
+        self.defs.push((name.clone(), schema));
+        Ok(name)
+    }
+
+    /// Returns a name (not used by other definitions) for the definition
+    /// at the given pointer in the given document.
+    fn unique_name(&mut self, doc_uri: &Url, pointer: &str) -> String {
+        let doc_name = doc_uri
+            .path_segments()
+            .and_then(|mut s| s.next_back())
+            .map(|s| s.split('.').next().unwrap_or(s))
+            .unwrap_or_default()
+            .to_camel_case();
+        let name = match pointer.rsplit('/').next() {
+            Some(n) if !n.is_empty() => n.replace("~1", "/").replace("~0", "~"),
+            _ => doc_name.clone(),
+        };
+
+        let mut candidates = vec![name.clone(), doc_name + &name]
+            .into_iter()
+            .chain((1..).map(|i| format!("{}{}", name, i)));
+        let name = candidates
+            .find(|n| !self.taken.contains(n))
+            .expect("infinite names?");
+        self.taken.push(name.clone());
+        name
+    }
+}
@@ -14,12 +14,19 @@ use std::collections::{BTreeMap, BTreeSet};
 /// **NOTE:** Don't implement this by yourself! Please use the `#[api_v2_schema]`
 /// proc macro attribute instead.
 pub trait Schema: Sized {
+    /// Title of this schema, if any (`title` field).
+    fn title(&self) -> Option<&str>;
+
     /// Description for this schema, if any (`description` field).
     fn description(&self) -> Option<&str>;
 
     /// Reference to some other schema, if any (`$ref` field).
     fn reference(&self) -> Option<&str>;
 
+    /// Property used for discriminating the subtypes of this
+    /// (polymorphic) schema, if any (`discriminator` field).
+    fn discriminator(&self) -> Option<&str>;
+
     /// Data type of this schema, if any (`type` field).
     fn data_type(&self) -> Option<DataType>;
 
@@ -47,6 +54,12 @@ pub trait Schema: Sized {
     /// Returns the required properties (if any) for this object.
     fn required_properties(&self) -> Option<&BTreeSet<String>>;
 
+    /// Schemas which should be combined to form this schema (`allOf` field).
+    fn all_of(&self) -> Option<&Vec<Resolvable<Self>>>;
+
+    /// Mutable access to `allOf` field.
+    fn all_of_mut(&mut self) -> Option<&mut Vec<Resolvable<Self>>>;
+
     /// Enum variants in this schema (if any). It's `serde_json::Value`
     /// because:
     ///
@@ -54,11 +67,72 @@ pub trait Schema: Sized {
     /// - `serde_json::Value` works for both JSON and YAML.
     fn enum_variants(&self) -> Option<&[serde_json::Value]>;
 
+    /// Data type of this schema, assuming that a schema composed using
+    /// `allOf` (without an explicit `type`) is an object.
+    fn effective_data_type(&self) -> Option<DataType> {
+        self.data_type()
+            .or_else(|| self.all_of().map(|_| DataType::Object))
+    }
+
     /// Returns whether this definition "is" or "has" `Any` type.
     fn contains_any(&self) -> bool {
         _schema_contains_any(self, vec![])
     }
 
+    /* MARK: Validation and metadata. */
+
+    /// Default value for this schema, if any (`default` field).
+    fn default_value(&self) -> Option<&serde_json::Value>;
+
+    /// Example value for this schema, if any (`example` field).
+    fn example(&self) -> Option<&serde_json::Value>;
+
+    /// Returns whether this schema is read-only (`readOnly` field).
+    fn is_read_only(&self) -> bool;
+
+    /// Maximum value for numbers, if any (`maximum` field).
+    fn maximum(&self) -> Option<f32>;
+
+    /// Returns whether the maximum value is exclusive (`exclusiveMaximum` field).
+    fn exclusive_maximum(&self) -> bool;
+
+    /// Minimum value for numbers, if any (`minimum` field).
+    fn minimum(&self) -> Option<f32>;
+
+    /// Returns whether the minimum value is exclusive (`exclusiveMinimum` field).
+    fn exclusive_minimum(&self) -> bool;
+
+    /// Numbers should be a multiple of this value, if any (`multipleOf` field).
+    fn multiple_of(&self) -> Option<f32>;
+
+    /// Maximum length for strings, if any (`maxLength` field).
+    fn max_length(&self) -> Option<u32>;
+
+    /// Minimum length for strings, if any (`minLength` field).
+    fn min_length(&self) -> Option<u32>;
+
+    /// Regex pattern for strings, if any (`pattern` field).
+    fn pattern(&self) -> Option<&str>;
+
+    /// Maximum number of items in arrays, if any (`maxItems` field).
+    fn max_items(&self) -> Option<u32>;
+
+    /// Minimum number of items in arrays, if any (`minItems` field).
+    fn min_items(&self) -> Option<u32>;
+
+    /// Returns whether the items in arrays should be unique (`uniqueItems` field).
+    fn unique_items(&self) -> bool;
+
+    /// Maximum number of properties in objects, if any (`maxProperties` field).
+    fn max_properties(&self) -> Option<u32>;
+
+    /// Minimum number of properties in objects, if any (`minProperties` field).
+    fn min_properties(&self) -> Option<u32>;
+
+    /// Existing Rust type to be used for this schema during codegen
+    /// (instead of generating one), if any (`x-rust-type` extension).
+    fn rust_type(&self) -> Option<&str>;
+
     /* MARK: Resolver-specific methods. */
 
     /// Set the reference to this schema.
@@ -73,6 +147,15 @@ pub trait Schema: Sized {
     /// set by the resolver using `set_cyclic` for codegen.
     fn is_cyclic(&self) -> bool;
 
+    /// Returns the schemas which extend this schema (through `allOf`).
+    ///
+    /// **NOTE:** This is not part of the schema object, but it's
+    /// set by the resolver using `add_subtype` for codegen.
+    fn subtypes(&self) -> Vec<Resolvable<Self>>;
+
+    /// Adds a schema extending this schema. This is done by the resolver.
+    fn add_subtype(&mut self, schema: &Resolvable<Self>);
+
     /// Name of this schema, if any.
     ///
     /// **NOTE:** This is not part of the schema object, but it's
@@ -84,7 +167,12 @@ pub trait Schema: Sized {
 }
 
 fn _schema_contains_any<'a, S: Schema>(schema: &'a S, mut nodes: Vec<&'a str>) -> bool {
-    if schema.data_type().is_none() {
+    // Existing Rust types don't need `Any`.
+    if schema.rust_type().is_some() {
+        return false;
+    }
+
+    if schema.effective_data_type().is_none() {
         return true;
     }
 
@@ -111,9 +199,20 @@ fn _schema_contains_any<'a, S: Schema>(schema: &'a S, mut nodes: Vec<&'a str>) -
             .additional_properties()
             .map(|e| match e {
                 Either::Left(extra_props_allowed) => *extra_props_allowed,
-                Either::Right(s) => _schema_contains_any(&*s.read(), nodes),
+                Either::Right(s) => _schema_contains_any(&*s.read(), nodes.clone()),
             })
             .unwrap_or(false)
+        || schema
+            .all_of()
+            .map(|v| {
+                v.iter()
+                    .any(|s| _schema_contains_any(&*s.read(), nodes.clone()))
+            })
+            .unwrap_or(false)
+        || schema
+            .subtypes()
+            .iter()
+            .any(|s| _schema_contains_any(&*s.read(), nodes.clone()))
 }
 
 /// Trait for returning OpenAPI data type and format for the implementor.
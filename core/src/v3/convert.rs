@@ -0,0 +1,654 @@
+//! Conversion from OpenAPI v2 to OpenAPI v3.
+
+use super::models::{
+    Api, Components, Either, Header, MediaRange, MediaType, OAuthFlow, OAuthFlows, Operation,
+    Parameter, ParameterIn, ParameterStyle, PathItem, Reference, RequestBody, Resolvable,
+    ResolvableParameter, Response, Schema, SecurityScheme, Server, Version,
+};
+use crate::error::{
+    DEF_REF_PREFIX as V2_DEF_REF_PREFIX, PARAM_REF_PREFIX as V2_PARAM_REF_PREFIX,
+    RESP_REF_PREFIX as V2_RESP_REF_PREFIX,
+};
+use crate::v2::models as v2;
+use parking_lot::RwLock;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+const SCHEMA_REF_PREFIX: &str = "#/components/schemas/";
+const PARAM_REF_PREFIX: &str = "#/components/parameters/";
+const RESP_REF_PREFIX: &str = "#/components/responses/";
+const BODY_REF_PREFIX: &str = "#/components/requestBodies/";
+
+/// Fields in v2 parameters, headers and items which belong to the schema in v3.
+const SCHEMA_FIELDS: &[&str] = &[
+    "type",
+    "format",
+    "items",
+    "default",
+    "maximum",
+    "exclusiveMaximum",
+    "minimum",
+    "exclusiveMinimum",
+    "maxLength",
+    "minLength",
+    "pattern",
+    "maxItems",
+    "minItems",
+    "uniqueItems",
+    "multipleOf",
+    "enum",
+];
+
+/// v2 spec where all schemas are kept as JSON values, so that we don't
+/// lose any (custom) fields while converting.
+type ValueApi = v2::Api<v2::Parameter<Value>, v2::Response<Value>, Value>;
+type ValueParameter = Either<v2::Reference, v2::Parameter<Value>>;
+
+impl Api {
+    /// Converts the given OpenAPI v2 spec into an OpenAPI v3 spec.
+    ///
+    /// - Body and form data parameters become request bodies.
+    /// - `consumes` and `produces` become the content maps of request
+    ///   bodies and responses.
+    /// - Security definitions become security schemes in components.
+    /// - `host`, `basePath` and `schemes` become servers.
+    /// - Extensions (`x-` fields) of non-body parameters are carried over
+    ///   (extensions of body and form data parameters are dropped, since
+    ///   they're merged into request bodies).
+    ///
+    /// **NOTE:** Resolved specs have their references to parameters and
+    /// responses inlined while serializing, so this works best with raw
+    /// (unresolved) specs.
+    pub fn from_v2<P, R, S>(api: &v2::Api<P, R, S>) -> Result<Self, serde_json::Error>
+    where
+        P: Serialize,
+        R: Serialize,
+        S: Serialize,
+    {
+        let api: ValueApi = serde_json::from_value(serde_json::to_value(api)?)?;
+        Converter { api: &api }.convert()
+    }
+}
+
+/// Converter for a v2 spec.
+struct Converter<'a> {
+    api: &'a ValueApi,
+}
+
+impl<'a> Converter<'a> {
+    /// Converts the whole spec.
+    fn convert(&self) -> Result<Api, serde_json::Error> {
+        let api = self.api;
+        let mut components = Components::default();
+        for (name, schema) in &api.definitions {
+            components
+                .schemas
+                .insert(name.clone(), convert_schema(schema.clone())?);
+        }
+
+        for (name, param) in &api.parameters {
+            match param.in_ {
+                v2::ParameterIn::Body => {
+                    let body = self.convert_body(param, &api.consumes)?;
+                    components
+                        .request_bodies
+                        .insert(name.clone(), Either::Right(Arc::new(RwLock::new(body))));
+                }
+                // Form parameters are inlined wherever they're referenced.
+                v2::ParameterIn::FormData => (),
+                _ => {
+                    let param = convert_parameter(param)?;
+                    components
+                        .parameters
+                        .insert(name.clone(), Either::Right(Arc::new(RwLock::new(param))));
+                }
+            }
+        }
+
+        for (name, resp) in &api.responses {
+            let resp = self.convert_response(resp, &api.produces)?;
+            components
+                .responses
+                .insert(name.clone(), Either::Right(Arc::new(RwLock::new(resp))));
+        }
+
+        for (name, scheme) in &api.security_definitions {
+            components
+                .security_schemes
+                .insert(name.clone(), Either::Right(convert_security_scheme(scheme)));
+        }
+
+        let mut paths = BTreeMap::new();
+        for (path, item) in &api.paths {
+            paths.insert(path.clone(), self.convert_path_item(item)?);
+        }
+
+        Ok(Api {
+            openapi: Version::V3_0_3,
+            info: api.info.clone(),
+            servers: servers(api.host.as_deref(), api.base_path.as_deref(), &api.schemes),
+            paths,
+            components,
+            security: api
+                .security
+                .iter()
+                .map(|s| {
+                    s.iter()
+                        .map(|(k, v)| (k.clone(), v.iter().cloned().collect()))
+                        .collect()
+                })
+                .collect(),
+            tags: api.tags.clone(),
+            external_docs: api.external_docs.clone(),
+            coders: api.coders.clone(),
+            support_crates: api.support_crates.clone(),
+            spec_format: api.spec_format,
+        })
+    }
+
+    /// Converts the path item and its operations. Body and form data
+    /// parameters shared by all operations are moved to the operations.
+    fn convert_path_item(
+        &self,
+        item: &v2::PathItem<v2::Parameter<Value>, v2::Response<Value>>,
+    ) -> Result<PathItem, serde_json::Error> {
+        let (mut shared, mut parameters) = (vec![], vec![]);
+        for param in &item.parameters {
+            if self.is_body_or_form(param) {
+                shared.push(param);
+            } else if let Some(p) = self.convert_parameter_or_ref(param)? {
+                parameters.push(p);
+            }
+        }
+
+        let mut methods = BTreeMap::new();
+        for (&method, op) in &item.methods {
+            methods.insert(method, self.convert_operation(op, &shared)?);
+        }
+
+        Ok(PathItem {
+            methods,
+            parameters,
+            ..Default::default()
+        })
+    }
+
+    /// Converts the given operation. Shared body/form data parameters
+    /// from the path item are also taken for building the request body.
+    fn convert_operation(
+        &self,
+        op: &v2::Operation<v2::Parameter<Value>, v2::Response<Value>>,
+        shared: &[&ValueParameter],
+    ) -> Result<Operation, serde_json::Error> {
+        let consumes = op.consumes.as_ref().unwrap_or(&self.api.consumes);
+        let produces = op.produces.as_ref().unwrap_or(&self.api.produces);
+
+        let (mut body, mut form, mut parameters) = (None, vec![], vec![]);
+        let params = shared
+            .iter()
+            .copied()
+            // Parameters in operations override the ones in path item.
+            .filter(|p| {
+                let p = self.get_parameter(p);
+                !op.parameters
+                    .iter()
+                    .any(|o| match (self.get_parameter(o), p) {
+                        (Some(o), Some(p)) => o.name == p.name && o.in_ == p.in_,
+                        _ => false,
+                    })
+            })
+            .chain(op.parameters.iter());
+
+        for param in params {
+            let is_ref = param.left().is_some();
+            let p = match self.get_parameter(param) {
+                Some(p) => p,
+                None => {
+                    parameters.extend(self.convert_parameter_or_ref(param)?);
+                    continue;
+                }
+            };
+
+            match p.in_ {
+                v2::ParameterIn::Body if is_ref => {
+                    body = Some(Either::Left(Reference {
+                        reference: param
+                            .left()
+                            .expect("expected reference")
+                            .reference
+                            .replacen(V2_PARAM_REF_PREFIX, BODY_REF_PREFIX, 1),
+                    }))
+                }
+                v2::ParameterIn::Body => {
+                    let b = self.convert_body(p, consumes)?;
+                    body = Some(Either::Right(Arc::new(RwLock::new(b))));
+                }
+                v2::ParameterIn::FormData => form.push(p),
+                _ => parameters.extend(self.convert_parameter_or_ref(param)?),
+            }
+        }
+
+        if !form.is_empty() {
+            let b = convert_form(&form, consumes)?;
+            body = Some(Either::Right(Arc::new(RwLock::new(b))));
+        }
+
+        let mut responses = BTreeMap::new();
+        for (code, resp) in &op.responses {
+            let resp = match resp {
+                Either::Left(r) => Either::Left(Reference {
+                    reference: r.reference.replacen(V2_RESP_REF_PREFIX, RESP_REF_PREFIX, 1),
+                }),
+                Either::Right(r) => {
+                    let r = self.convert_response(r, produces)?;
+                    Either::Right(Arc::new(RwLock::new(r)))
+                }
+            };
+
+            responses.insert(code.clone(), resp);
+        }
+
+        Ok(Operation {
+            tags: op.tags.clone(),
+            operation_id: op.operation_id.clone(),
+            description: op.description.clone(),
+            parameters,
+            request_body: body,
+            responses,
+            deprecated: op.deprecated,
+            security: op.security.clone(),
+            servers: if op.schemes.is_empty() {
+                vec![]
+            } else {
+                servers(
+                    self.api.host.as_deref(),
+                    self.api.base_path.as_deref(),
+                    &op.schemes,
+                )
+            },
+            ..Default::default()
+        })
+    }
+
+    /// Converts the given (non-body) parameter or its reference.
+    /// Returns `None` for form data references (which are inlined).
+    fn convert_parameter_or_ref(
+        &self,
+        param: &ValueParameter,
+    ) -> Result<Option<Either<Reference, ResolvableParameter>>, serde_json::Error> {
+        match param {
+            Either::Left(r) => {
+                if self.is_body_or_form(param) {
+                    return Ok(None);
+                }
+
+                Ok(Some(Either::Left(Reference {
+                    reference: r
+                        .reference
+                        .replacen(V2_PARAM_REF_PREFIX, PARAM_REF_PREFIX, 1),
+                })))
+            }
+            Either::Right(p) => Ok(Some(Either::Right(Arc::new(RwLock::new(
+                convert_parameter(p)?,
+            ))))),
+        }
+    }
+
+    /// Converts the given body parameter into a request body.
+    fn convert_body(
+        &self,
+        param: &v2::Parameter<Value>,
+        consumes: &BTreeSet<MediaRange>,
+    ) -> Result<RequestBody, serde_json::Error> {
+        let schema = match param.schema.as_ref() {
+            Some(s) => Some(convert_schema(s.clone())?),
+            None => None,
+        };
+
+        Ok(RequestBody {
+            description: param.description.clone(),
+            content: content(consumes, schema),
+            required: param.required,
+        })
+    }
+
+    /// Converts the given response.
+    fn convert_response(
+        &self,
+        resp: &v2::Response<Value>,
+        produces: &BTreeSet<MediaRange>,
+    ) -> Result<Response, serde_json::Error> {
+        let mut headers = BTreeMap::new();
+        for (name, header) in &resp.headers {
+            let header = Header {
+                description: header.description.clone(),
+                schema: Some(convert_schema(schema_from_fields(header)?)?),
+                ..Default::default()
+            };
+
+            headers.insert(name.clone(), Either::Right(Arc::new(RwLock::new(header))));
+        }
+
+        let content = match resp.schema.as_ref() {
+            Some(s) => content(produces, Some(convert_schema(s.clone())?)),
+            None => BTreeMap::new(),
+        };
+
+        Ok(Response {
+            description: resp.description.clone().unwrap_or_default(),
+            headers,
+            content,
+            ..Default::default()
+        })
+    }
+
+    /// Returns the actual parameter (resolving the reference, if needed).
+    fn get_parameter<'p>(&'p self, param: &'p ValueParameter) -> Option<&'p v2::Parameter<Value>> {
+        match param {
+            Either::Left(r) => {
+                if !r.reference.starts_with(V2_PARAM_REF_PREFIX) {
+                    return None;
+                }
+
+                self.api
+                    .parameters
+                    .get(&r.reference[V2_PARAM_REF_PREFIX.len()..])
+            }
+            Either::Right(p) => Some(p),
+        }
+    }
+
+    /// Checks whether the given parameter is a body or form data parameter.
+    fn is_body_or_form(&self, param: &ValueParameter) -> bool {
+        self.get_parameter(param)
+            .map(|p| p.in_ == v2::ParameterIn::Body || p.in_ == v2::ParameterIn::FormData)
+            .unwrap_or(false)
+    }
+}
+
+/// Converts the given non-body parameter.
+fn convert_parameter(param: &v2::Parameter<Value>) -> Result<Parameter, serde_json::Error> {
+    let in_ = match param.in_ {
+        v2::ParameterIn::Header => ParameterIn::Header,
+        v2::ParameterIn::Path => ParameterIn::Path,
+        _ => ParameterIn::Query,
+    };
+
+    let (style, explode) = match (param.data_type, param.collection_format) {
+        (Some(v2::DataType::Array), Some(fmt)) => collection_style(fmt, in_),
+        _ => (None, None),
+    };
+
+    Ok(Parameter {
+        name: param.name.clone(),
+        in_,
+        description: param.description.clone(),
+        required: param.required,
+        allow_empty_value: param.allow_empty_value,
+        style,
+        explode,
+        schema: Some(convert_schema(schema_from_fields(param)?)?),
+        extensions: param
+            .extensions
+            .iter()
+            .filter(|(k, _)| k.starts_with("x-"))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
+        ..Default::default()
+    })
+}
+
+/// Converts the given form data parameters into a request body.
+fn convert_form(
+    params: &[&v2::Parameter<Value>],
+    consumes: &BTreeSet<MediaRange>,
+) -> Result<RequestBody, serde_json::Error> {
+    let mut properties = Map::new();
+    let mut required = vec![];
+    for param in params {
+        let mut schema = schema_from_fields(param)?;
+        if let (Some(desc), Value::Object(map)) = (param.description.as_ref(), &mut schema) {
+            map.insert("description".into(), desc.clone().into());
+        }
+
+        properties.insert(param.name.clone(), schema);
+        if param.required {
+            required.push(Value::String(param.name.clone()));
+        }
+    }
+
+    let mut schema = Map::new();
+    schema.insert("type".into(), "object".into());
+    schema.insert("properties".into(), properties.into());
+    if !required.is_empty() {
+        schema.insert("required".into(), required.into());
+    }
+
+    let forms = consumes
+        .iter()
+        .filter(|m| {
+            m.0 == mime::MULTIPART_FORM_DATA || m.0 == mime::APPLICATION_WWW_FORM_URLENCODED
+        })
+        .cloned()
+        .collect::<BTreeSet<_>>();
+    let forms = if !forms.is_empty() {
+        forms
+    } else if params
+        .iter()
+        .any(|p| p.data_type == Some(v2::DataType::File))
+    {
+        // Files can only be uploaded through multipart forms.
+        Some(MediaRange(mime::MULTIPART_FORM_DATA))
+            .into_iter()
+            .collect()
+    } else {
+        Some(MediaRange(mime::APPLICATION_WWW_FORM_URLENCODED))
+            .into_iter()
+            .collect()
+    };
+
+    Ok(RequestBody {
+        description: None,
+        content: content(&forms, Some(convert_schema(schema.into())?)),
+        required: params.iter().any(|p| p.required),
+    })
+}
+
+/// Converts the given security definition into a security scheme.
+fn convert_security_scheme(scheme: &v2::SecurityScheme) -> SecurityScheme {
+    let mut new = SecurityScheme {
+        type_: scheme.type_.clone(),
+        description: scheme.description.clone(),
+        ..Default::default()
+    };
+
+    match scheme.type_.as_str() {
+        "basic" => {
+            new.type_ = "http".into();
+            new.scheme = Some("basic".into());
+        }
+        "oauth2" => {
+            let flow = OAuthFlow {
+                auth_url: scheme.auth_url.clone(),
+                token_url: scheme.token_url.clone(),
+                refresh_url: None,
+                scopes: scheme.scopes.clone(),
+            };
+
+            let mut flows = OAuthFlows::default();
+            match scheme.flow.as_deref() {
+                Some("implicit") => flows.implicit = Some(flow),
+                Some("password") => flows.password = Some(flow),
+                Some("application") => flows.client_credentials = Some(flow),
+                _ => flows.authorization_code = Some(flow),
+            }
+
+            new.flows = Some(flows);
+        }
+        _ => {
+            new.name = scheme.name.clone();
+            new.in_ = scheme.in_.clone();
+        }
+    }
+
+    new
+}
+
+/// Returns the style and explode values for the given collection format.
+fn collection_style(
+    fmt: v2::CollectionFormat,
+    in_: ParameterIn,
+) -> (Option<ParameterStyle>, Option<bool>) {
+    match (fmt, in_) {
+        (v2::CollectionFormat::Csv, ParameterIn::Query) => {
+            (Some(ParameterStyle::Form), Some(false))
+        }
+        (v2::CollectionFormat::Csv, _) => (Some(ParameterStyle::Simple), Some(false)),
+        (v2::CollectionFormat::Ssv, _) => (Some(ParameterStyle::SpaceDelimited), Some(false)),
+        (v2::CollectionFormat::Pipes, _) => (Some(ParameterStyle::PipeDelimited), Some(false)),
+        (v2::CollectionFormat::Multi, _) => (Some(ParameterStyle::Form), Some(true)),
+        // There's no equivalent for tab-separated values in v3.
+        (v2::CollectionFormat::Tsv, _) => (None, None),
+    }
+}
+
+/// Builds the content map for the given media types using the schema.
+fn content(
+    ranges: &BTreeSet<MediaRange>,
+    schema: Option<Resolvable<Schema>>,
+) -> BTreeMap<MediaRange, MediaType> {
+    let mut content = BTreeMap::new();
+    let default = Some(v2::JSON_MIME.clone()).into_iter().collect();
+    let ranges = if ranges.is_empty() { &default } else { ranges };
+    for range in ranges {
+        content.insert(
+            range.clone(),
+            MediaType {
+                schema: schema.clone(),
+                ..Default::default()
+            },
+        );
+    }
+
+    content
+}
+
+/// Builds the servers using the given host, base path and schemes.
+fn servers(
+    host: Option<&str>,
+    base_path: Option<&str>,
+    schemes: &BTreeSet<v2::OperationProtocol>,
+) -> Vec<Server> {
+    let base_path = base_path.unwrap_or_default();
+    let host = match host {
+        Some(h) => h,
+        None if base_path.is_empty() => return vec![],
+        None => {
+            return vec![Server {
+                url: base_path.into(),
+                ..Default::default()
+            }]
+        }
+    };
+
+    if schemes.is_empty() {
+        // Scheme is the same as the one used for accessing the spec.
+        return vec![Server {
+            url: format!("//{}{}", host, base_path),
+            ..Default::default()
+        }];
+    }
+
+    schemes
+        .iter()
+        .map(|s| {
+            let scheme = match s {
+                v2::OperationProtocol::Http => "http",
+                v2::OperationProtocol::Https => "https",
+                v2::OperationProtocol::Ws => "ws",
+                v2::OperationProtocol::Wss => "wss",
+            };
+
+            Server {
+                url: format!("{}://{}{}", scheme, host, base_path),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Collects the schema fields from the given v2 parameter/header/items
+/// object. In v3, these are moved to `schema`.
+fn schema_from_fields<T: Serialize>(object: &T) -> Result<Value, serde_json::Error> {
+    let mut value = serde_json::to_value(object)?;
+    if let Value::Object(map) = &mut value {
+        let mut new = map
+            .iter()
+            .filter(|(k, _)| SCHEMA_FIELDS.contains(&k.as_str()))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect::<Map<_, _>>();
+        if let Some(items) = map.get("items") {
+            new.insert("items".into(), schema_from_fields(items)?);
+        }
+
+        *map = new;
+    }
+
+    Ok(value)
+}
+
+/// Converts the given v2 schema into v3 schema.
+fn convert_schema(mut schema: Value) -> Result<Resolvable<Schema>, serde_json::Error> {
+    convert_schema_value(&mut schema);
+    serde_json::from_value::<Schema>(schema).map(Resolvable::from)
+}
+
+/// Rewrites the v2-specific fields in the given schema (recursively).
+fn convert_schema_value(schema: &mut Value) {
+    let map = match schema {
+        Value::Object(m) => m,
+        _ => return,
+    };
+
+    if let Some(Value::String(r)) = map.get_mut("$ref") {
+        if r.starts_with(V2_DEF_REF_PREFIX) {
+            *r = r.replacen(V2_DEF_REF_PREFIX, SCHEMA_REF_PREFIX, 1);
+        }
+    }
+
+    // v3 doesn't have "file" type.
+    if map.get("type").and_then(Value::as_str) == Some("file") {
+        map.insert("type".into(), "string".into());
+        map.insert("format".into(), "binary".into());
+    }
+
+    if let Some(v) = map.remove("x-nullable") {
+        map.insert("nullable".into(), v);
+    }
+
+    // Discriminator is an object in v3.
+    if let Some(Value::String(name)) = map.remove("discriminator") {
+        let mut d = Map::new();
+        d.insert("propertyName".into(), name.into());
+        map.insert("discriminator".into(), d.into());
+    }
+
+    if let Some(Value::Object(props)) = map.get_mut("properties") {
+        props.values_mut().for_each(convert_schema_value);
+    }
+
+    for key in &["items", "additionalProperties", "not"] {
+        if let Some(s) = map.get_mut(*key) {
+            convert_schema_value(s);
+        }
+    }
+
+    for key in &["allOf", "oneOf", "anyOf"] {
+        if let Some(Value::Array(schemas)) = map.get_mut(*key) {
+            schemas.iter_mut().for_each(convert_schema_value);
+        }
+    }
+}
@@ -0,0 +1,33 @@
+//! Core types and traits associated with the
+//! [OpenAPI v3 specification](https://github.com/OAI/OpenAPI-Specification/blob/master/versions/3.0.3.md).
+
+mod convert;
+pub mod models;
+#[cfg(feature = "codegen")]
+mod resolver;
+
+pub use self::models::{Api, Schema};
+
+#[cfg(feature = "codegen")]
+use self::resolver::Resolver;
+#[cfg(feature = "codegen")]
+use crate::error::ValidationError;
+
+#[cfg(feature = "codegen")]
+impl Api {
+    /// Consumes this API schema, resolves the references and returns
+    /// the resolved schema.
+    ///
+    /// This resolves the references to components and hoists anonymous
+    /// schemas in request bodies and responses into `components.schemas`,
+    /// so that they can be used for codegen.
+    pub fn resolve(self) -> Result<Api, ValidationError> {
+        let mut resolver = Resolver::from((self.paths, self.components));
+        resolver.resolve()?;
+        Ok(Api {
+            paths: resolver.paths,
+            components: resolver.components,
+            ..self
+        })
+    }
+}
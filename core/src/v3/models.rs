@@ -0,0 +1,453 @@
+//! Models used by OpenAPI v3.
+
+pub use crate::v2::models::{
+    Coders, Contact, DataType, DataTypeFormat, Either, ExternalDocs, HttpMethod, Info, License,
+    MediaRange, Reference, Resolvable, SpecFormat, Tag,
+};
+
+use paperclip_macros::api_v2_schema_struct;
+use parking_lot::RwLock;
+
+use std::collections::BTreeMap;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// OpenAPI version.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub enum Version {
+    #[serde(rename = "3.0.0")]
+    V3_0_0,
+    #[serde(rename = "3.0.1")]
+    V3_0_1,
+    #[serde(rename = "3.0.2")]
+    V3_0_2,
+    #[default]
+    #[serde(rename = "3.0.3")]
+    V3_0_3,
+}
+
+/// OpenAPI v3 spec.
+///
+/// https://github.com/OAI/OpenAPI-Specification/blob/master/versions/3.0.3.md#openapi-object
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Api {
+    pub openapi: Version,
+    pub info: Info,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub servers: Vec<Server>,
+    pub paths: BTreeMap<String, PathItem>,
+    #[serde(default, skip_serializing_if = "Components::is_empty")]
+    pub components: Components,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub security: Vec<BTreeMap<String, Vec<String>>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<Tag>,
+    #[serde(rename = "externalDocs", skip_serializing_if = "Option::is_none")]
+    pub external_docs: Option<ExternalDocs>,
+    /// Extension for custom coders to be used for decoding API objects.
+    /// This is the same as the `x-rust-coders` extension in v2.
+    #[serde(
+        default,
+        rename = "x-rust-coders",
+        skip_serializing_if = "<Coders as Deref>::Target::is_empty"
+    )]
+    pub coders: Coders,
+    /// Additional crates that need to be added to the manifest.
+    /// This is the same as the `x-rust-dependencies` extension in v2.
+    #[serde(
+        default,
+        rename = "x-rust-dependencies",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub support_crates: BTreeMap<String, String>,
+    /// This field is set manually, because we don't know the format in which
+    /// the spec was provided and we need to use this as the fallback encoding.
+    #[serde(skip)]
+    pub spec_format: SpecFormat,
+}
+
+/// Server object.
+///
+/// https://github.com/OAI/OpenAPI-Specification/blob/master/versions/3.0.3.md#server-object
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Server {
+    pub url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, ServerVariable>,
+}
+
+/// Server variable object.
+///
+/// https://github.com/OAI/OpenAPI-Specification/blob/master/versions/3.0.3.md#server-variable-object
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ServerVariable {
+    #[serde(default, rename = "enum", skip_serializing_if = "Vec::is_empty")]
+    pub enum_: Vec<String>,
+    pub default: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Components object.
+///
+/// https://github.com/OAI/OpenAPI-Specification/blob/master/versions/3.0.3.md#components-object
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Components {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub schemas: BTreeMap<String, Resolvable<Schema>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub responses: BTreeMap<String, Either<Reference, ResolvableResponse>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub parameters: BTreeMap<String, Either<Reference, ResolvableParameter>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub examples: BTreeMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub request_bodies: BTreeMap<String, Either<Reference, ResolvableRequestBody>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, Either<Reference, ResolvableHeader>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub security_schemes: BTreeMap<String, Either<Reference, SecurityScheme>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub links: BTreeMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub callbacks: BTreeMap<String, serde_json::Value>,
+}
+
+impl Components {
+    /// Checks whether this object doesn't have any components.
+    pub fn is_empty(&self) -> bool {
+        self.schemas.is_empty()
+            && self.responses.is_empty()
+            && self.parameters.is_empty()
+            && self.examples.is_empty()
+            && self.request_bodies.is_empty()
+            && self.headers.is_empty()
+            && self.security_schemes.is_empty()
+            && self.links.is_empty()
+            && self.callbacks.is_empty()
+    }
+}
+
+/// Path item object.
+///
+/// https://github.com/OAI/OpenAPI-Specification/blob/master/versions/3.0.3.md#path-item-object
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PathItem {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(flatten, default = "BTreeMap::default")]
+    pub methods: BTreeMap<HttpMethod, Operation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub servers: Vec<Server>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<Either<Reference, ResolvableParameter>>,
+}
+
+/// Operation object.
+///
+/// https://github.com/OAI/OpenAPI-Specification/blob/master/versions/3.0.3.md#operation-object
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Operation {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub external_docs: Option<ExternalDocs>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<Either<Reference, ResolvableParameter>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<Either<Reference, ResolvableRequestBody>>,
+    pub responses: BTreeMap<String, Either<Reference, ResolvableResponse>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub callbacks: BTreeMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub deprecated: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub security: Option<Vec<BTreeMap<String, Vec<String>>>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub servers: Vec<Server>,
+}
+
+/// Parameter which can be shared and resolved.
+pub type ResolvableParameter = Arc<RwLock<Parameter>>;
+
+/// Parameter object.
+///
+/// https://github.com/OAI/OpenAPI-Specification/blob/master/versions/3.0.3.md#parameter-object
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Parameter {
+    pub name: String,
+    #[serde(rename = "in")]
+    pub in_: ParameterIn,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub required: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub deprecated: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub allow_empty_value: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<ParameterStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explode: Option<bool>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub allow_reserved: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Resolvable<Schema>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub example: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub examples: BTreeMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub content: BTreeMap<MediaRange, MediaType>,
+    /// Extensions (`x-` fields) in this parameter.
+    #[serde(flatten)]
+    pub extensions: BTreeMap<String, serde_json::Value>,
+}
+
+/// The location of the parameter.
+///
+/// **NOTE:** The default is just a stub. This is usually set explicitly.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd)]
+#[serde(rename_all = "camelCase")]
+pub enum ParameterIn {
+    #[default]
+    Query,
+    Header,
+    Path,
+    Cookie,
+}
+
+/// Ways in which a parameter value can be serialized.
+///
+/// https://github.com/OAI/OpenAPI-Specification/blob/master/versions/3.0.3.md#style-values
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd)]
+#[serde(rename_all = "camelCase")]
+pub enum ParameterStyle {
+    Matrix,
+    Label,
+    Form,
+    Simple,
+    SpaceDelimited,
+    PipeDelimited,
+    DeepObject,
+}
+
+/// Request body which can be shared and resolved.
+pub type ResolvableRequestBody = Arc<RwLock<RequestBody>>;
+
+/// Request body object.
+///
+/// https://github.com/OAI/OpenAPI-Specification/blob/master/versions/3.0.3.md#request-body-object
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RequestBody {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub content: BTreeMap<MediaRange, MediaType>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub required: bool,
+}
+
+/// Media type object.
+///
+/// https://github.com/OAI/OpenAPI-Specification/blob/master/versions/3.0.3.md#media-type-object
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct MediaType {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Resolvable<Schema>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub example: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub examples: BTreeMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub encoding: BTreeMap<String, Encoding>,
+}
+
+/// Encoding object.
+///
+/// https://github.com/OAI/OpenAPI-Specification/blob/master/versions/3.0.3.md#encoding-object
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Encoding {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, Either<Reference, ResolvableHeader>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<ParameterStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explode: Option<bool>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub allow_reserved: bool,
+}
+
+/// Response which can be shared and resolved.
+pub type ResolvableResponse = Arc<RwLock<Response>>;
+
+/// Response object.
+///
+/// https://github.com/OAI/OpenAPI-Specification/blob/master/versions/3.0.3.md#response-object
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Response {
+    pub description: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, Either<Reference, ResolvableHeader>>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub content: BTreeMap<MediaRange, MediaType>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub links: BTreeMap<String, serde_json::Value>,
+}
+
+/// Header which can be shared and resolved.
+pub type ResolvableHeader = Arc<RwLock<Header>>;
+
+/// Header object. This is the same as the parameter object,
+/// but without `name` and `in` fields.
+///
+/// https://github.com/OAI/OpenAPI-Specification/blob/master/versions/3.0.3.md#header-object
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Header {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub required: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub deprecated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub style: Option<ParameterStyle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explode: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Resolvable<Schema>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub example: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub content: BTreeMap<MediaRange, MediaType>,
+}
+
+/// Security scheme object.
+///
+/// https://github.com/OAI/OpenAPI-Specification/blob/master/versions/3.0.3.md#security-scheme-object
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecurityScheme {
+    #[serde(rename = "type")]
+    pub type_: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "in", skip_serializing_if = "Option::is_none")]
+    pub in_: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheme: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bearer_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flows: Option<OAuthFlows>,
+    #[serde(rename = "openIdConnectUrl", skip_serializing_if = "Option::is_none")]
+    pub open_id_connect_url: Option<String>,
+}
+
+/// OAuth flows object.
+///
+/// https://github.com/OAI/OpenAPI-Specification/blob/master/versions/3.0.3.md#oauth-flows-object
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthFlows {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub implicit: Option<OAuthFlow>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<OAuthFlow>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_credentials: Option<OAuthFlow>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authorization_code: Option<OAuthFlow>,
+}
+
+/// OAuth flow object.
+///
+/// https://github.com/OAI/OpenAPI-Specification/blob/master/versions/3.0.3.md#oauth-flow-object
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthFlow {
+    #[serde(rename = "authorizationUrl", skip_serializing_if = "Option::is_none")]
+    pub auth_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_url: Option<String>,
+    #[serde(default)]
+    pub scopes: BTreeMap<String, String>,
+}
+
+use crate as paperclip; // hack for proc macro
+
+/// Schema object. In addition to the fields shared with v2 schema, this
+/// has the composition and other fields introduced in v3.
+///
+/// https://github.com/OAI/OpenAPI-Specification/blob/master/versions/3.0.3.md#schema-object
+#[api_v2_schema_struct]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Schema {
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub nullable: bool,
+    #[serde(default, rename = "oneOf", skip_serializing_if = "Vec::is_empty")]
+    pub one_of: Vec<Resolvable<Schema>>,
+    #[serde(default, rename = "anyOf", skip_serializing_if = "Vec::is_empty")]
+    pub any_of: Vec<Resolvable<Schema>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not: Option<Resolvable<Schema>>,
+    #[schema_override]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discriminator: Option<Discriminator>,
+    #[serde(default, rename = "writeOnly", skip_serializing_if = "is_false")]
+    pub write_only: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub deprecated: bool,
+    #[serde(rename = "externalDocs", skip_serializing_if = "Option::is_none")]
+    pub external_docs: Option<ExternalDocs>,
+    /// Extensions (`x-` fields) and other unknown fields in this schema.
+    #[serde(flatten)]
+    pub extensions: BTreeMap<String, serde_json::Value>,
+}
+
+/// Discriminator object.
+///
+/// https://github.com/OAI/OpenAPI-Specification/blob/master/versions/3.0.3.md#discriminator-object
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Discriminator {
+    pub property_name: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mapping: BTreeMap<String, String>,
+}
+
+/* Common trait impls */
+
+impl AsRef<str> for Discriminator {
+    fn as_ref(&self) -> &str {
+        &self.property_name
+    }
+}
+
+/* Serde helpers */
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_false(val: &bool) -> bool {
+    !*val
+}
@@ -0,0 +1,440 @@
+use super::models::{
+    Components, Either, Header, HttpMethod, MediaRange, MediaType, Operation, PathItem, Reference,
+    Resolvable, ResolvableHeader, ResolvableParameter, ResolvableResponse, Schema,
+};
+use crate::error::ValidationError;
+use crate::v2::Schema as _;
+use heck::CamelCase;
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::iter;
+use std::mem;
+use std::sync::Arc;
+
+const SCHEMA_REF_PREFIX: &str = "#/components/schemas/";
+const PARAM_REF_PREFIX: &str = "#/components/parameters/";
+const RESP_REF_PREFIX: &str = "#/components/responses/";
+const BODY_REF_PREFIX: &str = "#/components/requestBodies/";
+const HEADER_REF_PREFIX: &str = "#/components/headers/";
+
+type ComponentsMap<T> = BTreeMap<String, Either<Reference, T>>;
+
+/// API schema resolver for v3. This is similar to the v2 resolver, except
+/// that it resolves the references to components (schemas, parameters,
+/// request bodies, responses and headers) and it also walks through the
+/// composition fields (`allOf`, `oneOf`, `anyOf` and `not`) in schema.
+///
+/// Anonymous schemas in request bodies and responses are hoisted into
+/// `components.schemas` (like v2 body parameters and responses), so that
+/// all objects are named by the time we're done.
+pub(crate) struct Resolver {
+    /// List of definitions that must be marked as cyclic while resolving a definition.
+    cyclic_defs: RefCell<Vec<Resolvable<Schema>>>,
+    /// Paths and the corresponding operations.
+    pub paths: BTreeMap<String, PathItem>,
+    /// Reusable components.
+    pub components: Components,
+}
+
+impl From<(BTreeMap<String, PathItem>, Components)> for Resolver {
+    fn from((paths, components): (BTreeMap<String, PathItem>, Components)) -> Self {
+        Resolver {
+            cyclic_defs: vec![].into(),
+            paths,
+            components,
+        }
+    }
+}
+
+impl Resolver {
+    /// Visit components and operations and resolve them!
+    pub fn resolve(&mut self) -> Result<(), ValidationError> {
+        // Components can refer other components of the same kind.
+        resolve_component_refs(&mut self.components.parameters, PARAM_REF_PREFIX)?;
+        resolve_component_refs(&mut self.components.responses, RESP_REF_PREFIX)?;
+        resolve_component_refs(&mut self.components.request_bodies, BODY_REF_PREFIX)?;
+        resolve_component_refs(&mut self.components.headers, HEADER_REF_PREFIX)?;
+
+        // Resolve shared components first, so that the anonymous schemas
+        // in them are named after the components (and not after whichever
+        // operation happens to use them first).
+        let mut params = mem::take(&mut self.components.parameters);
+        self.resolve_parameters(params.values_mut())?;
+        self.components.parameters = params;
+
+        for (name, header) in &self.components.headers {
+            log::trace!("Checking header component: {}", name);
+            self.resolve_header(header)?;
+        }
+
+        let bodies = mem::take(&mut self.components.request_bodies);
+        for (name, body) in &bodies {
+            log::trace!("Checking request body component: {}", name);
+            self.resolve_content(&mut body.write().content, name, "Body")?;
+        }
+        self.components.request_bodies = bodies;
+
+        let responses = mem::take(&mut self.components.responses);
+        for (name, resp) in &responses {
+            log::trace!("Checking response component: {}", name);
+            self.resolve_response(resp, name)?;
+        }
+        self.components.responses = responses;
+
+        let mut paths = mem::take(&mut self.paths);
+        paths.iter_mut().try_for_each(|(path, map)| {
+            log::trace!("Checking path: {}", path);
+            self.resolve_operations(path, map)
+        })?;
+        self.paths = paths;
+
+        // Set the names of all schemas.
+        for (name, schema) in &self.components.schemas {
+            schema.write().set_name(name);
+        }
+
+        for (name, schema) in &self.components.schemas {
+            log::trace!("Entering: {}", name);
+            self.resolve_definitions_no_root_ref(schema)?;
+
+            for def in self.cyclic_defs.borrow_mut().drain(..) {
+                log::debug!(
+                    "Cyclic definition detected: {:?}",
+                    def.read().name().unwrap()
+                );
+                def.write().set_cyclic(true);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolve the operations in the given path item.
+    fn resolve_operations(
+        &mut self,
+        path: &str,
+        map: &mut PathItem,
+    ) -> Result<(), ValidationError> {
+        for (&method, op) in &mut map.methods {
+            self.resolve_operation(method, path, op)?;
+        }
+
+        self.resolve_parameters(map.parameters.iter_mut())
+    }
+
+    /// Resolve the parameters, request body and responses of an operation.
+    fn resolve_operation(
+        &mut self,
+        method: HttpMethod,
+        path: &str,
+        op: &mut Operation,
+    ) -> Result<(), ValidationError> {
+        let prefix = method.to_string() + path;
+        self.resolve_parameters(op.parameters.iter_mut())?;
+
+        if let Some(body) = op.request_body.as_mut() {
+            if let Some(r) = body.left() {
+                // Components have already been resolved.
+                log::trace!("Resolving request body {}", r.reference);
+                *body = Either::Right(self.resolve_component_reference(
+                    &self.components.request_bodies,
+                    BODY_REF_PREFIX,
+                    &r.reference,
+                )?);
+            } else {
+                self.resolve_content(&mut body.write().content, &prefix, "Body")?;
+            }
+        }
+
+        for (code, resp) in &mut op.responses {
+            if let Some(r) = resp.left() {
+                log::trace!("Resolving response {}", r.reference);
+                *resp = Either::Right(self.resolve_component_reference(
+                    &self.components.responses,
+                    RESP_REF_PREFIX,
+                    &r.reference,
+                )?);
+                continue;
+            }
+
+            // Responses for different status codes are named differently.
+            self.resolve_response(resp, &format!("{}_{}", prefix, code))?;
+        }
+
+        Ok(())
+    }
+
+    /// Resolve the given bunch of parameters.
+    fn resolve_parameters<'a>(
+        &self,
+        params: impl Iterator<Item = &'a mut Either<Reference, ResolvableParameter>>,
+    ) -> Result<(), ValidationError> {
+        for p in params {
+            if let Some(r) = p.left() {
+                log::trace!("Resolving parameter {}", r.reference);
+                *p = Either::Right(self.resolve_component_reference(
+                    &self.components.parameters,
+                    PARAM_REF_PREFIX,
+                    &r.reference,
+                )?);
+                continue;
+            }
+
+            let mut param = p.write();
+            self.resolve_inline_schema(&mut param.schema)?;
+            param
+                .content
+                .values_mut()
+                .try_for_each(|m| self.resolve_inline_schema(&mut m.schema))?;
+        }
+
+        Ok(())
+    }
+
+    /// Resolve the headers and content of a response.
+    fn resolve_response(
+        &mut self,
+        resp: &Either<Reference, ResolvableResponse>,
+        name: &str,
+    ) -> Result<(), ValidationError> {
+        let mut response = resp.write();
+        for header in response.headers.values_mut() {
+            if let Some(r) = header.left() {
+                log::trace!("Resolving header {}", r.reference);
+                *header = Either::Right(self.resolve_component_reference(
+                    &self.components.headers,
+                    HEADER_REF_PREFIX,
+                    &r.reference,
+                )?);
+            } else {
+                self.resolve_header(header)?;
+            }
+        }
+
+        self.resolve_content(&mut response.content, name, "Response")
+    }
+
+    /// Resolve the schema in the given header.
+    fn resolve_header(
+        &self,
+        header: &Either<Reference, ResolvableHeader>,
+    ) -> Result<(), ValidationError> {
+        let mut header = header.write();
+        let Header { schema, .. } = &mut *header;
+        self.resolve_inline_schema(schema)
+    }
+
+    /// Resolves the schema for each media type in the given content map,
+    /// by hoisting the anonymous schemas into the known definitions.
+    fn resolve_content(
+        &mut self,
+        content: &mut BTreeMap<MediaRange, MediaType>,
+        prefix: &str,
+        suffix: &str,
+    ) -> Result<(), ValidationError> {
+        for (range, media) in content.iter_mut() {
+            let schema = match media.schema.as_mut() {
+                Some(s) => s,
+                None => continue,
+            };
+
+            match schema {
+                Resolvable::Raw(ref s) if s.read().reference().is_none() => {
+                    // We've encountered an anonymous schema definition in some
+                    // request body/response. Give it a name (along with the media
+                    // subtype, since different media types may have different schemas)
+                    // and add it to the definitions.
+                    let mut subtype = range.0.subtype().as_str().to_owned();
+                    if let Some(s) = range.0.suffix() {
+                        subtype = subtype + "_" + s.as_str();
+                    }
+
+                    let name = (prefix.to_owned() + "_" + suffix).to_camel_case()
+                        + &subtype.to_camel_case();
+                    let mut candidates =
+                        iter::once(name.clone()).chain((1..).map(|i| format!("{}{}", name, i)));
+                    let def_name = candidates
+                        .find(|n| match self.components.schemas.get(n) {
+                            Some(existing) => Arc::ptr_eq(existing, s),
+                            None => true,
+                        })
+                        .expect("infinite names?");
+
+                    let mut ref_schema = Schema::default();
+                    ref_schema.set_reference(format!("{}{}", SCHEMA_REF_PREFIX, def_name));
+                    let old_schema = mem::replace(schema, ref_schema.into());
+                    self.components.schemas.insert(def_name, old_schema);
+                }
+                _ => (),
+            }
+
+            self.resolve_definitions(schema)?;
+        }
+
+        Ok(())
+    }
+
+    /// Resolves the (optional) schema in parameters and headers. These
+    /// are usually simple types, so we don't hoist them.
+    fn resolve_inline_schema(
+        &self,
+        schema: &mut Option<Resolvable<Schema>>,
+    ) -> Result<(), ValidationError> {
+        match schema.as_mut() {
+            Some(s) => self.resolve_definitions(s),
+            None => Ok(()),
+        }
+    }
+
+    /// We've passed some definition. Resolve it assuming that it doesn't
+    /// contain any reference.
+    fn resolve_definitions_no_root_ref(
+        &self,
+        schema: &Resolvable<Schema>,
+    ) -> Result<(), ValidationError> {
+        let mut schema = match schema.try_write() {
+            Some(s) => s,
+            None => {
+                self.cyclic_defs.borrow_mut().push(schema.clone());
+                return Ok(());
+            }
+        };
+
+        if let Some(inner) = schema.items.as_mut() {
+            self.resolve_definitions(inner)?;
+        }
+
+        schema.properties.iter_mut().try_for_each(|(k, s)| {
+            log::trace!("Resolving property {:?}", k);
+            self.resolve_definitions(s)
+        })?;
+
+        if let Some(props) = schema.extra_props.as_mut().and_then(|s| s.right_mut()) {
+            self.resolve_definitions(props)?;
+        }
+
+        let Schema {
+            all_of,
+            one_of,
+            any_of,
+            not,
+            ..
+        } = &mut *schema;
+        all_of
+            .iter_mut()
+            .chain(one_of.iter_mut())
+            .chain(any_of.iter_mut())
+            .chain(not.iter_mut())
+            .try_for_each(|s| self.resolve_definitions(s))
+    }
+
+    /// Resolve the given definition. If it contains a reference, find and assign it,
+    /// otherwise traverse further.
+    fn resolve_definitions(&self, schema: &mut Resolvable<Schema>) -> Result<(), ValidationError> {
+        let ref_def = {
+            let s = match schema.try_read() {
+                Some(s) => s,
+                None => {
+                    self.cyclic_defs.borrow_mut().push(schema.clone());
+                    return Ok(());
+                }
+            };
+
+            if let Some(ref_name) = s.reference() {
+                log::trace!("Resolving definition {}", ref_name);
+                Some(self.resolve_definition_reference(ref_name)?)
+            } else {
+                None
+            }
+        };
+
+        if let Some(new) = ref_def {
+            *schema = match schema {
+                // Definitions referring other definitions are resolved again.
+                Resolvable::Raw(old) | Resolvable::Resolved { old, .. } => Resolvable::Resolved {
+                    old: old.clone(),
+                    new: Arc::clone(&new),
+                },
+            };
+        }
+
+        self.resolve_definitions_no_root_ref(&*schema)
+    }
+
+    /// Given a name (from `$ref` field), get a reference to the schema.
+    fn resolve_definition_reference(
+        &self,
+        name: &str,
+    ) -> Result<Resolvable<Schema>, ValidationError> {
+        if !name.starts_with(SCHEMA_REF_PREFIX) {
+            return Err(ValidationError::InvalidRefURI(name.into()));
+        }
+
+        let name = &name[SCHEMA_REF_PREFIX.len()..];
+        let schema = self
+            .components
+            .schemas
+            .get(name)
+            .ok_or_else(|| ValidationError::MissingReference(name.into()))?;
+        Ok(schema.clone())
+    }
+
+    /// Given a name (from `$ref` field), get a reference to the component
+    /// in the given map.
+    fn resolve_component_reference<T: Clone>(
+        &self,
+        map: &ComponentsMap<T>,
+        prefix: &str,
+        name: &str,
+    ) -> Result<T, ValidationError> {
+        if !name.starts_with(prefix) {
+            return Err(ValidationError::InvalidRefURI(name.into()));
+        }
+
+        let name = &name[prefix.len()..];
+        map.get(name)
+            .and_then(|c| c.right())
+            .cloned()
+            .ok_or_else(|| ValidationError::MissingReference(name.into()))
+    }
+}
+
+/// Resolves references between components of the same kind
+/// (for example, a response referring to another response).
+fn resolve_component_refs<T: Clone>(
+    map: &mut ComponentsMap<T>,
+    prefix: &str,
+) -> Result<(), ValidationError> {
+    loop {
+        let mut unresolved = None;
+        let mut resolved = vec![];
+        for (name, component) in map.iter() {
+            let ref_ = match component.left() {
+                Some(r) => &r.reference,
+                None => continue,
+            };
+
+            if !ref_.starts_with(prefix) {
+                return Err(ValidationError::InvalidRefURI(ref_.clone()));
+            }
+
+            match map.get(&ref_[prefix.len()..]) {
+                Some(Either::Right(c)) => resolved.push((name.clone(), c.clone())),
+                Some(Either::Left(_)) => unresolved = Some(ref_.clone()),
+                None => return Err(ValidationError::MissingReference(ref_.clone())),
+            }
+        }
+
+        match (resolved.is_empty(), unresolved) {
+            // References are pointing to each other.
+            (true, Some(ref_)) => return Err(ValidationError::InvalidRefURI(ref_)),
+            (true, None) => return Ok(()),
+            _ => (),
+        }
+
+        for (name, component) in resolved {
+            map.insert(name, Either::Right(component));
+        }
+    }
+}
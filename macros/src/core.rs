@@ -5,6 +5,11 @@ use quote::quote;
 use syn::spanned::Spanned;
 use syn::{Data, DeriveInput, Fields, FieldsNamed, Ident};
 
+use std::collections::HashSet;
+
+/// Attribute for struct fields replacing the schema fields of the same name.
+const SCHEMA_OVERRIDE_ATTR: &str = "schema_override";
+
 /// Actual parser and emitter for `api_v2_schema_struct` macro.
 pub fn emit_v2_schema_struct(input: TokenStream) -> TokenStream {
     let mut item_ast = match crate::expect_struct_or_enum(input) {
@@ -64,6 +69,7 @@ pub fn emit_v2_schema_struct(input: TokenStream) -> TokenStream {
                 self.properties.values_mut().for_each(|s| s.remove_refs());
                 self.items.as_mut().map(|s| s.remove_refs());
                 self.extra_props.as_mut().and_then(|s| s.right_mut()).map(|s| s.remove_refs());
+                self.all_of.iter_mut().for_each(|s| s.remove_refs());
                 self.reference = None;
             }
 
@@ -78,6 +84,7 @@ pub fn emit_v2_schema_struct(input: TokenStream) -> TokenStream {
                     self.properties.values_mut().for_each(|s| s.retain_ref());
                     self.items.as_mut().map(|s| s.retain_ref());
                     self.extra_props.as_mut().and_then(|s| s.right_mut()).map(|s| s.retain_ref());
+                    self.all_of.iter_mut().for_each(|s| s.retain_ref());
                 }
             }
         }
@@ -93,6 +100,20 @@ pub fn emit_v2_schema_struct(input: TokenStream) -> TokenStream {
                 self.name = Some(name.into());
             }
 
+            #[inline]
+            fn subtypes(&self) -> Vec<paperclip::v2::models::Resolvable<Self>> {
+                self.subtypes
+                    .iter()
+                    .filter_map(|s| s.upgrade())
+                    .map(paperclip::v2::models::Resolvable::Raw)
+                    .collect()
+            }
+
+            #[inline]
+            fn add_subtype(&mut self, schema: &paperclip::v2::models::Resolvable<Self>) {
+                self.subtypes.push(std::sync::Arc::downgrade(&*schema));
+            }
+
             #[inline]
             fn set_cyclic(&mut self, cyclic: bool) {
                 self.cyclic = cyclic;
@@ -103,6 +124,11 @@ pub fn emit_v2_schema_struct(input: TokenStream) -> TokenStream {
                 self.cyclic
             }
 
+            #[inline]
+            fn title(&self) -> Option<&str> {
+                self.title.as_ref().map(String::as_str)
+            }
+
             #[inline]
             fn description(&self) -> Option<&str> {
                 self.description.as_ref().map(String::as_str)
@@ -118,6 +144,11 @@ pub fn emit_v2_schema_struct(input: TokenStream) -> TokenStream {
                 self.reference = Some(ref_);
             }
 
+            #[inline]
+            fn discriminator(&self) -> Option<&str> {
+                self.discriminator.as_ref().map(|d| AsRef::<str>::as_ref(d))
+            }
+
             #[inline]
             fn data_type(&self) -> Option<paperclip::v2::models::DataType> {
                 self.data_type
@@ -175,6 +206,109 @@ pub fn emit_v2_schema_struct(input: TokenStream) -> TokenStream {
                 }
             }
 
+            #[inline]
+            fn all_of(&self) -> Option<&Vec<paperclip::v2::models::Resolvable<Self>>> {
+                if self.all_of.is_empty() {
+                    None
+                } else {
+                    Some(&self.all_of)
+                }
+            }
+
+            #[inline]
+            fn all_of_mut(&mut self) -> Option<&mut Vec<paperclip::v2::models::Resolvable<Self>>> {
+                if self.all_of.is_empty() {
+                    None
+                } else {
+                    Some(&mut self.all_of)
+                }
+            }
+
+            #[inline]
+            fn default_value(&self) -> Option<&serde_json::Value> {
+                self.default.as_ref()
+            }
+
+            #[inline]
+            fn example(&self) -> Option<&serde_json::Value> {
+                self.example.as_ref()
+            }
+
+            #[inline]
+            fn is_read_only(&self) -> bool {
+                self.read_only.unwrap_or(false)
+            }
+
+            #[inline]
+            fn maximum(&self) -> Option<f32> {
+                self.maximum
+            }
+
+            #[inline]
+            fn exclusive_maximum(&self) -> bool {
+                self.exclusive_maximum.unwrap_or(false)
+            }
+
+            #[inline]
+            fn minimum(&self) -> Option<f32> {
+                self.minimum
+            }
+
+            #[inline]
+            fn exclusive_minimum(&self) -> bool {
+                self.exclusive_minimum.unwrap_or(false)
+            }
+
+            #[inline]
+            fn multiple_of(&self) -> Option<f32> {
+                self.multiple_of
+            }
+
+            #[inline]
+            fn max_length(&self) -> Option<u32> {
+                self.max_length
+            }
+
+            #[inline]
+            fn min_length(&self) -> Option<u32> {
+                self.min_length
+            }
+
+            #[inline]
+            fn pattern(&self) -> Option<&str> {
+                self.pattern.as_ref().map(String::as_str)
+            }
+
+            #[inline]
+            fn max_items(&self) -> Option<u32> {
+                self.max_items
+            }
+
+            #[inline]
+            fn min_items(&self) -> Option<u32> {
+                self.min_items
+            }
+
+            #[inline]
+            fn unique_items(&self) -> bool {
+                self.unique_items.unwrap_or(false)
+            }
+
+            #[inline]
+            fn max_properties(&self) -> Option<u32> {
+                self.max_properties
+            }
+
+            #[inline]
+            fn min_properties(&self) -> Option<u32> {
+                self.min_properties
+            }
+
+            #[inline]
+            fn rust_type(&self) -> Option<&str> {
+                self.rust_type.as_ref().map(String::as_str)
+            }
+
             #[inline]
             fn enum_variants(&self) -> Option<&[serde_json::Value]> {
                 if self.enum_.is_empty() {
@@ -200,7 +334,7 @@ fn raw_schema(item_ast: &mut DeriveInput) -> Result<proc_macro2::TokenStream, To
     let fields = named_fields(item_ast)?;
     let default_fields: FieldsNamed =
         syn::parse2(schema_fields(&ident, false)).expect("parsing schema fields?");
-    fields.named.extend(default_fields.named);
+    extend_fields(fields, default_fields)?;
 
     let mut defaults = quote!();
     for field in &fields.named {
@@ -218,7 +352,7 @@ fn actual_schema(item_ast: &mut DeriveInput) -> Result<proc_macro2::TokenStream,
 
     let default_fields: FieldsNamed =
         syn::parse2(schema_fields(&name, true)).expect("parsing schema fields?");
-    fields.named.extend(default_fields.named);
+    extend_fields(fields, default_fields)?;
 
     let mut defaults = quote!();
     for field in &fields.named {
@@ -229,6 +363,58 @@ fn actual_schema(item_ast: &mut DeriveInput) -> Result<proc_macro2::TokenStream,
     Ok(defaults)
 }
 
+/// Adds the given schema fields to the struct fields. A struct field can
+/// replace the schema field with the same name only if it's marked with
+/// `#[schema_override]` (which is removed from the field here).
+fn extend_fields(fields: &mut FieldsNamed, schema_fields: FieldsNamed) -> Result<(), TokenStream> {
+    let schema_names = schema_fields
+        .named
+        .iter()
+        .filter_map(|f| f.ident.clone())
+        .collect::<HashSet<_>>();
+
+    let mut overrides = HashSet::new();
+    for field in fields.named.iter_mut() {
+        let ident = field.ident.clone().expect("fields not named?");
+        let len = field.attrs.len();
+        field
+            .attrs
+            .retain(|a| !a.path.is_ident(SCHEMA_OVERRIDE_ATTR));
+        let is_override = field.attrs.len() != len;
+
+        match (schema_names.contains(&ident), is_override) {
+            (true, true) => {
+                overrides.insert(ident);
+            }
+            (true, false) => {
+                return Err(crate::span_error_with_msg(
+                    field,
+                    &format!(
+                    "field `{}` conflicts with a schema field (mark it with `#[{}]` to replace it)",
+                    ident, SCHEMA_OVERRIDE_ATTR
+                ),
+                ))
+            }
+            (false, true) => {
+                return Err(crate::span_error_with_msg(
+                    field,
+                    &format!("field `{}` doesn't override any schema field", ident),
+                ))
+            }
+            (false, false) => (),
+        }
+    }
+
+    fields.named.extend(
+        schema_fields
+            .named
+            .into_iter()
+            .filter(|f| !matches!(&f.ident, Some(i) if overrides.contains(i))),
+    );
+
+    Ok(())
+}
+
 /// Extracts named fields from the given struct.
 fn named_fields(item_ast: &mut DeriveInput) -> Result<&mut FieldsNamed, TokenStream> {
     let span = item_ast.span();
@@ -312,15 +498,66 @@ fn schema_fields(name: &Ident, is_ref: bool) -> proc_macro2::TokenStream {
     add_self(&mut gen);
     gen.extend(quote!(>>,));
 
+    gen.extend(quote!(
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub discriminator: Option<String>,
+    ));
+
+    gen.extend(quote!(
+        #[serde(default, rename = "allOf", skip_serializing_if = "Vec::is_empty")]
+        pub all_of: Vec<
+    ));
+    add_self(&mut gen);
+    gen.extend(quote!(>,));
+
     gen.extend(quote!(
         #[serde(default, skip_serializing_if = "std::collections::BTreeSet::is_empty")]
         pub required: std::collections::BTreeSet<String>,
     ));
 
+    gen.extend(quote!(
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub default: Option<serde_json::Value>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub example: Option<serde_json::Value>,
+        #[serde(rename = "readOnly", skip_serializing_if = "Option::is_none")]
+        pub read_only: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub maximum: Option<f32>,
+        #[serde(rename = "exclusiveMaximum", skip_serializing_if = "Option::is_none")]
+        pub exclusive_maximum: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub minimum: Option<f32>,
+        #[serde(rename = "exclusiveMinimum", skip_serializing_if = "Option::is_none")]
+        pub exclusive_minimum: Option<bool>,
+        #[serde(rename = "multipleOf", skip_serializing_if = "Option::is_none")]
+        pub multiple_of: Option<f32>,
+        #[serde(rename = "maxLength", skip_serializing_if = "Option::is_none")]
+        pub max_length: Option<u32>,
+        #[serde(rename = "minLength", skip_serializing_if = "Option::is_none")]
+        pub min_length: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub pattern: Option<String>,
+        #[serde(rename = "maxItems", skip_serializing_if = "Option::is_none")]
+        pub max_items: Option<u32>,
+        #[serde(rename = "minItems", skip_serializing_if = "Option::is_none")]
+        pub min_items: Option<u32>,
+        #[serde(rename = "uniqueItems", skip_serializing_if = "Option::is_none")]
+        pub unique_items: Option<bool>,
+        #[serde(rename = "maxProperties", skip_serializing_if = "Option::is_none")]
+        pub max_properties: Option<u32>,
+        #[serde(rename = "minProperties", skip_serializing_if = "Option::is_none")]
+        pub min_properties: Option<u32>,
+        #[serde(rename = "x-rust-type", skip_serializing_if = "Option::is_none")]
+        pub rust_type: Option<String>,
+    ));
+
     if is_ref {
         gen.extend(quote!(
             #[serde(skip)]
             cyclic: bool,
+            #[serde(skip)]
+            subtypes: Vec<paperclip::v2::models::WeakResolvable<#name>>,
         ));
     }
 
@@ -28,7 +28,12 @@ use syn::{
 /// [Schema](https://github.com/OAI/OpenAPI-Specification/blob/master/versions/2.0.md#schemaObject)
 /// object ([example](https://paperclip.waffles.space/paperclip/v2/)). This adds the necessary fields (in addition to your own fields) and implements the
 /// `Schema` trait for parsing and codegen.
+///
+/// Fields of your struct can't share names with the added fields, unless
+/// they're marked with `#[schema_override]` (in which case, they replace
+/// the added fields).
 #[cfg(feature = "v2")]
+#[proc_macro_error]
 #[proc_macro_attribute]
 pub fn api_v2_schema_struct(_attr: TokenStream, input: TokenStream) -> TokenStream {
     self::core::emit_v2_schema_struct(input)
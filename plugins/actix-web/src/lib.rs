@@ -1,5 +1,7 @@
+pub mod validation;
 pub mod web;
 
+pub use self::validation::{RequestValidator, ResponseValidator};
 pub use self::web::{Resource, Route, Scope};
 pub use paperclip_macros::{api_v2_errors, api_v2_operation, Apiv2Schema, Apiv2Security};
 
@@ -7,11 +9,12 @@ use self::web::{RouteWrapper, ServiceConfig};
 use actix_service::ServiceFactory;
 use actix_web::dev::{HttpServiceFactory, MessageBody, ServiceRequest, ServiceResponse, Transform};
 use actix_web::{web::HttpResponse, Error};
-use futures::future::{ok as fut_ok, Ready};
+use futures::future::{err as fut_err, ok as fut_ok, Ready};
 use paperclip_core::v2::models::{
     DefaultApiRaw, DefaultOperationRaw, DefaultPathItemRaw, DefaultSchemaRaw, HttpMethod,
     SecurityScheme,
 };
+use paperclip_core::v3;
 use parking_lot::RwLock;
 
 use std::collections::BTreeMap;
@@ -248,6 +251,49 @@ where
         self
     }
 
+    /// Mounts the specification for all operations and definitions
+    /// recorded by the wrapper and serves them in the given path
+    /// as an OpenAPI v3 JSON.
+    ///
+    /// The recorded (v2) spec is converted on each request, so like
+    /// `with_json_spec_at`, this also reflects the handlers added after
+    /// this call.
+    pub fn with_json_spec_v3_at(mut self, path: &str) -> Self {
+        self.inner = self.inner.take().map(|a| {
+            a.service(
+                actix_web::web::resource(path)
+                    .route(actix_web::web::get().to(SpecV3Handler(self.spec.clone()))),
+            )
+        });
+        self
+    }
+
+    /// Returns a middleware for validating requests against the spec recorded
+    /// by this wrapper. Since the spec is shared, this also validates the
+    /// requests to handlers added after this call.
+    ///
+    /// ```rust,ignore
+    /// let app = App::new().wrap_api();
+    /// let validator = app.request_validator();
+    /// app.wrap(validator).service(...)
+    /// ```
+    pub fn request_validator(&self) -> RequestValidator {
+        RequestValidator::new(self.spec.clone())
+    }
+
+    /// Returns a middleware for checking whether the responses conform to the
+    /// spec recorded by this wrapper (useful in tests for catching drifts
+    /// between handlers and the spec).
+    ///
+    /// ```rust,ignore
+    /// let app = App::new().wrap_api();
+    /// let validator = app.response_validator().fail_on_mismatch(true);
+    /// app.wrap(validator).service(...)
+    /// ```
+    pub fn response_validator(&self) -> ResponseValidator {
+        ResponseValidator::new(self.spec.clone())
+    }
+
     /// Calls the given function with `App` and JSON `Value` representing your API
     /// specification **built until now**.
     ///
@@ -295,3 +341,17 @@ impl actix_web::dev::Factory<(), Ready<Result<HttpResponse, Error>>, Result<Http
         fut_ok(HttpResponse::Ok().json(&*self.0.read()))
     }
 }
+
+#[derive(Clone)]
+struct SpecV3Handler(Arc<RwLock<DefaultApiRaw>>);
+
+impl actix_web::dev::Factory<(), Ready<Result<HttpResponse, Error>>, Result<HttpResponse, Error>>
+    for SpecV3Handler
+{
+    fn call(&self, _: ()) -> Ready<Result<HttpResponse, Error>> {
+        match v3::Api::from_v2(&*self.0.read()) {
+            Ok(spec) => fut_ok(HttpResponse::Ok().json(&spec)),
+            Err(e) => fut_err(actix_web::error::ErrorInternalServerError(e)),
+        }
+    }
+}
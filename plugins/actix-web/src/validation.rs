@@ -0,0 +1,934 @@
+//! Middleware for validating requests and responses against the recorded spec.
+
+use actix_service::{Service, Transform};
+use actix_web::dev::{Body, MessageBody, Payload, ResponseBody, ServiceRequest, ServiceResponse};
+use actix_web::error::JsonPayloadError;
+use actix_web::http::{
+    header::{HeaderMap, CONTENT_LENGTH, CONTENT_TYPE},
+    Method,
+};
+use actix_web::web::{Bytes, BytesMut};
+use actix_web::{Error, HttpMessage, HttpResponse, ResponseError};
+use futures::future::{ok as fut_ok, poll_fn, LocalBoxFuture, Ready};
+use futures::stream::{self, StreamExt};
+use paperclip_core::v2::models::{
+    CollectionFormat, DataType, DefaultApiRaw, DefaultOperationRaw, DefaultParameterRaw,
+    DefaultPathItemRaw, DefaultSchemaRaw, Either, HttpMethod, Items, ParameterIn,
+};
+use paperclip_core::{pointer_child, DEF_REF_PREFIX, PARAM_REF_PREFIX, RESP_REF_PREFIX};
+use parking_lot::{Mutex, RwLock};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{self, Display};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// Default limit for the size of buffered request bodies (same as actix's `JsonConfig`).
+const DEFAULT_BODY_LIMIT: usize = 32_768;
+
+lazy_static::lazy_static! {
+    static ref PATTERNS: Mutex<HashMap<String, Option<Regex>>> = Mutex::new(HashMap::new());
+}
+
+/// Middleware which validates the path, query and header parameters
+/// (and JSON bodies) of incoming requests against the matching operation
+/// in the spec.
+///
+/// Requests which violate the spec are rejected with `400 Bad Request`
+/// and a JSON body listing the violations, where each violation has a
+/// [JSON pointer](https://tools.ietf.org/html/rfc6901) to the offending
+/// value (prefixed by its location - `/path`, `/query`, `/header` or `/body`).
+/// Requests which don't match any operation are passed through.
+///
+/// JSON bodies are buffered for validation (up to [`limit`](#method.limit)
+/// bytes) and larger bodies are rejected with `413 Payload Too Large`.
+/// Other bodies are passed through untouched.
+///
+/// **NOTE:** Form data parameters are not validated.
+#[derive(Clone)]
+pub struct RequestValidator {
+    spec: Arc<RwLock<DefaultApiRaw>>,
+    limit: usize,
+}
+
+impl RequestValidator {
+    /// Creates a validator which validates the requests using the spec
+    /// behind the given lock. Changes made to the spec (for example, by
+    /// adding handlers to the app) are reflected in the validation.
+    pub fn new(spec: Arc<RwLock<DefaultApiRaw>>) -> Self {
+        RequestValidator {
+            spec,
+            limit: DEFAULT_BODY_LIMIT,
+        }
+    }
+
+    /// Sets the maximum size (in bytes) of the bodies buffered for
+    /// validation (32 KiB by default, like actix's `JsonConfig`).
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+}
+
+impl From<DefaultApiRaw> for RequestValidator {
+    fn from(api: DefaultApiRaw) -> Self {
+        RequestValidator::new(Arc::new(RwLock::new(api)))
+    }
+}
+
+impl<S, B> Transform<S> for RequestValidator
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestValidation<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        fut_ok(RequestValidation {
+            spec: self.spec.clone(),
+            limit: self.limit,
+            service: Rc::new(RefCell::new(service)),
+        })
+    }
+}
+
+/// Service created by the [`RequestValidator`](./struct.RequestValidator.html) middleware.
+pub struct RequestValidation<S> {
+    spec: Arc<RwLock<DefaultApiRaw>>,
+    limit: usize,
+    service: Rc<RefCell<S>>,
+}
+
+impl<S, B> Service for RequestValidation<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.borrow_mut().poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let spec = self.spec.clone();
+        let limit = self.limit;
+
+        Box::pin(async move {
+            let mut errors = vec![];
+            let body = {
+                let api = spec.read();
+                match find_operation(&api, req.method(), req.path()) {
+                    Some(op) => op.validate_params(&api, &req, &mut errors),
+                    None => None,
+                }
+            };
+
+            // Only JSON bodies are validated (and hence buffered).
+            if let Some((schema, required)) = body.filter(|_| is_json(req.headers())) {
+                let length = req
+                    .headers()
+                    .get(CONTENT_LENGTH)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<usize>().ok());
+                if length.unwrap_or_default() > limit {
+                    return Ok(req.error_response(JsonPayloadError::Overflow));
+                }
+
+                let mut payload = req.take_payload();
+                let mut bytes = BytesMut::new();
+                while let Some(chunk) = payload.next().await {
+                    let chunk = chunk?;
+                    if bytes.len() + chunk.len() > limit {
+                        return Ok(req.error_response(JsonPayloadError::Overflow));
+                    }
+
+                    bytes.extend_from_slice(&chunk);
+                }
+
+                let bytes = bytes.freeze();
+                validate_body(&spec.read(), &schema, required, &bytes, &mut errors);
+                let stream = stream::once(fut_ok::<_, actix_web::error::PayloadError>(bytes));
+                req.set_payload(Payload::Stream(Box::pin(stream)));
+            }
+
+            if !errors.is_empty() {
+                return Ok(req.error_response(RequestValidationError { errors }));
+            }
+
+            let fut = service.borrow_mut().call(req);
+            fut.await
+        })
+    }
+}
+
+/// Middleware which checks whether the responses conform to the matching
+/// operation in the spec. This is meant for catching drifts between the
+/// handlers and the spec (in tests, for example).
+///
+/// The response body is buffered, and then its status code is checked
+/// against the responses documented for the operation, and its JSON body
+/// (if any) is validated against the schema of the matching response.
+/// By default, mismatches are logged. Use
+/// [`fail_on_mismatch`](#method.fail_on_mismatch) to replace such responses
+/// with `500 Internal Server Error` listing the mismatches instead.
+#[derive(Clone)]
+pub struct ResponseValidator {
+    spec: Arc<RwLock<DefaultApiRaw>>,
+    fail: bool,
+}
+
+impl ResponseValidator {
+    /// Creates a validator which validates the responses using the spec
+    /// behind the given lock. Changes made to the spec (for example, by
+    /// adding handlers to the app) are reflected in the validation.
+    pub fn new(spec: Arc<RwLock<DefaultApiRaw>>) -> Self {
+        ResponseValidator { spec, fail: false }
+    }
+
+    /// Sets whether responses which don't conform to the spec should be
+    /// replaced with an error response (instead of only logging them).
+    pub fn fail_on_mismatch(mut self, fail: bool) -> Self {
+        self.fail = fail;
+        self
+    }
+}
+
+impl From<DefaultApiRaw> for ResponseValidator {
+    fn from(api: DefaultApiRaw) -> Self {
+        ResponseValidator::new(Arc::new(RwLock::new(api)))
+    }
+}
+
+impl<S, B> Transform<S> for ResponseValidator
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<Body>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ResponseValidation<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        fut_ok(ResponseValidation {
+            spec: self.spec.clone(),
+            fail: self.fail,
+            service,
+        })
+    }
+}
+
+/// Service created by the [`ResponseValidator`](./struct.ResponseValidator.html) middleware.
+pub struct ResponseValidation<S> {
+    spec: Arc<RwLock<DefaultApiRaw>>,
+    fail: bool,
+    service: S,
+}
+
+impl<S, B> Service for ResponseValidation<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<Body>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        let spec = self.spec.clone();
+        let fail = self.fail;
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+            let mut body = res.take_body();
+            let mut bytes = BytesMut::new();
+            while let Some(chunk) = poll_fn(|cx| body.poll_next(cx)).await {
+                bytes.extend_from_slice(&chunk?);
+            }
+
+            let bytes = bytes.freeze();
+            let mut errors = vec![];
+            {
+                let api = spec.read();
+                let req = res.request();
+                if let Some(op) = find_operation(&api, req.method(), req.path()) {
+                    validate_response(&api, op.op, &res, &bytes, &mut errors);
+                }
+            }
+
+            if errors.is_empty() {
+                return Ok(res.map_body(|_, _| ResponseBody::Other(Body::from(bytes))));
+            }
+
+            let err = ResponseValidationError {
+                method: res.request().method().to_string(),
+                path: res.request().path().into(),
+                status: res.status().as_u16(),
+                errors,
+            };
+
+            if fail {
+                let resp = HttpResponse::InternalServerError().json(&err);
+                Ok(res.into_response(resp))
+            } else {
+                log::warn!("{}", err);
+                Ok(res.map_body(|_, _| ResponseBody::Other(Body::from(bytes))))
+            }
+        })
+    }
+}
+
+/// Validates the status code and body of the given response against the operation.
+fn validate_response<B>(
+    api: &DefaultApiRaw,
+    op: &DefaultOperationRaw,
+    res: &ServiceResponse<B>,
+    bytes: &Bytes,
+    errors: &mut Vec<FieldError>,
+) {
+    let status = res.status().as_u16().to_string();
+    let response = match op
+        .responses
+        .get(&status)
+        .or_else(|| op.responses.get("default"))
+    {
+        Some(Either::Right(r)) => r,
+        Some(Either::Left(r)) => match r
+            .reference
+            .strip_prefix(RESP_REF_PREFIX)
+            .and_then(|n| api.responses.get(n))
+        {
+            Some(r) => r,
+            None => return,
+        },
+        None => {
+            add_error(errors, "/status", "status code is not documented");
+            return;
+        }
+    };
+
+    let schema = match response.schema.as_ref() {
+        Some(s) => s,
+        None => return,
+    };
+
+    let pointer = "/body";
+    if bytes.is_empty() {
+        add_error(errors, pointer, "missing response body");
+    } else if is_json(res.headers()) {
+        match serde_json::from_slice::<Value>(bytes) {
+            Ok(value) => validate_value(api, schema, &value, pointer, errors),
+            Err(e) => add_error(errors, pointer, &format!("invalid JSON: {}", e)),
+        }
+    }
+}
+
+/// Error for responses which don't conform to the spec.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResponseValidationError {
+    /// HTTP method of the request.
+    pub method: String,
+    /// Path of the request.
+    pub path: String,
+    /// Status code of the response.
+    pub status: u16,
+    /// Mismatches found in the response.
+    pub errors: Vec<FieldError>,
+}
+
+impl Display for ResponseValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "response ({}) for {} {} doesn't conform to the spec",
+            self.status, self.method, self.path
+        )?;
+        for (i, e) in self.errors.iter().enumerate() {
+            f.write_str(if i == 0 { ": " } else { "; " })?;
+            write!(f, "{}: {}", e.pointer, e.message)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Error returned when a request doesn't conform to the spec.
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestValidationError {
+    /// Violations found in the request.
+    pub errors: Vec<FieldError>,
+}
+
+/// Violation found in some value.
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    /// JSON pointer to the offending value.
+    pub pointer: String,
+    /// Description of the violation.
+    pub message: String,
+}
+
+impl Display for RequestValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("request validation failed")?;
+        for (i, e) in self.errors.iter().enumerate() {
+            f.write_str(if i == 0 { ": " } else { "; " })?;
+            write!(f, "{}: {}", e.pointer, e.message)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ResponseError for RequestValidationError {
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::BadRequest().json(self)
+    }
+}
+
+/// Operation matching some request, along with the path parameters
+/// captured from the request path.
+pub(crate) struct MatchedOperation<'a> {
+    pub(crate) path_item: &'a DefaultPathItemRaw,
+    pub(crate) op: &'a DefaultOperationRaw,
+    pub(crate) path_params: BTreeMap<&'a str, String>,
+}
+
+/// Finds the operation matching the given method and request path (if any). Templates
+/// with more literal segments are preferred over the ones with more parameters.
+pub(crate) fn find_operation<'a>(
+    api: &'a DefaultApiRaw,
+    method: &Method,
+    mut path: &str,
+) -> Option<MatchedOperation<'a>> {
+    if let Some(base) = api.base_path.as_ref().map(|p| p.trim_end_matches('/')) {
+        path = path.strip_prefix(base).unwrap_or(path);
+    }
+
+    let segments = split_path(path).collect::<Vec<_>>();
+    let method = HttpMethod::from(method);
+    let mut best: Option<(usize, MatchedOperation<'a>)> = None;
+
+    for (template, path_item) in &api.paths {
+        let op = match path_item.methods.get(&method) {
+            Some(op) => op,
+            None => continue,
+        };
+
+        let parts = split_path(template).collect::<Vec<_>>();
+        if parts.len() != segments.len() {
+            continue;
+        }
+
+        let mut literals = 0;
+        let mut path_params = BTreeMap::new();
+        let matches = parts.iter().zip(&segments).all(|(part, segment)| {
+            if part.starts_with('{') && part.ends_with('}') {
+                let name = part[1..part.len() - 1].split(':').next().unwrap_or("");
+                let value = percent_encoding::percent_decode_str(segment).decode_utf8_lossy();
+                path_params.insert(name, value.into_owned());
+                !segment.is_empty()
+            } else {
+                literals += 1;
+                part == segment
+            }
+        });
+
+        if matches && best.as_ref().filter(|(l, _)| *l >= literals).is_none() {
+            best = Some((
+                literals,
+                MatchedOperation {
+                    path_item,
+                    op,
+                    path_params,
+                },
+            ));
+        }
+    }
+
+    best.map(|(_, op)| op)
+}
+
+impl<'a> MatchedOperation<'a> {
+    /// Validates the non-body parameters of this operation in the given request and
+    /// returns the body schema (if any) along with whether it's required.
+    fn validate_params(
+        &self,
+        api: &DefaultApiRaw,
+        req: &ServiceRequest,
+        errors: &mut Vec<FieldError>,
+    ) -> Option<(DefaultSchemaRaw, bool)> {
+        let query = serde_urlencoded::from_str::<Vec<(String, String)>>(req.query_string())
+            .unwrap_or_default();
+        let mut body = None;
+
+        // Operation parameters override the path-level parameters with the same name.
+        let mut params = BTreeMap::new();
+        for param in self.path_item.parameters.iter().chain(&self.op.parameters) {
+            let param = match param {
+                Either::Right(p) => p,
+                Either::Left(r) => match r
+                    .reference
+                    .strip_prefix(PARAM_REF_PREFIX)
+                    .and_then(|n| api.parameters.get(n))
+                {
+                    Some(p) => p,
+                    None => continue,
+                },
+            };
+
+            params.insert((param.in_, param.name.as_str()), param);
+        }
+
+        for param in params.values() {
+            let pointer = pointer_child(location(param.in_), &param.name);
+            let values = match param.in_ {
+                ParameterIn::Body => {
+                    body = param.schema.clone().map(|s| (s, param.required));
+                    continue;
+                }
+                ParameterIn::FormData => continue,
+                ParameterIn::Path => self
+                    .path_params
+                    .get(param.name.as_str())
+                    .cloned()
+                    .into_iter()
+                    .collect(),
+                ParameterIn::Query => query
+                    .iter()
+                    .filter(|(k, _)| *k == param.name)
+                    .map(|(_, v)| v.clone())
+                    .collect(),
+                ParameterIn::Header => {
+                    let mut values = vec![];
+                    for value in req.headers().get_all(param.name.as_str()) {
+                        match value.to_str() {
+                            Ok(v) => values.push(v.to_owned()),
+                            Err(_) => add_error(errors, &pointer, "header value is not valid"),
+                        }
+                    }
+
+                    values
+                }
+            };
+
+            if values.is_empty() {
+                if param.required {
+                    add_error(errors, &pointer, "missing required parameter");
+                }
+
+                continue;
+            }
+
+            let schema = parameter_schema(param);
+            let value = if param.data_type == Some(DataType::Array)
+                && param.collection_format == Some(CollectionFormat::Multi)
+            {
+                let items = param.items.as_ref();
+                let values = values
+                    .iter()
+                    .map(|v| {
+                        parse_param_value(
+                            v,
+                            items.and_then(|i| i.data_type),
+                            items.and_then(|i| i.collection_format),
+                            items.and_then(|i| i.items.as_deref()),
+                        )
+                    })
+                    .collect();
+                Value::Array(values)
+            } else {
+                parse_param_value(
+                    &values[0],
+                    param.data_type,
+                    param.collection_format,
+                    param.items.as_ref(),
+                )
+            };
+
+            validate_value(api, &schema, &value, &pointer, errors);
+        }
+
+        body
+    }
+}
+
+/// Validates the given (JSON) request body against the body schema.
+fn validate_body(
+    api: &DefaultApiRaw,
+    schema: &DefaultSchemaRaw,
+    required: bool,
+    bytes: &Bytes,
+    errors: &mut Vec<FieldError>,
+) {
+    let pointer = "/body";
+    if bytes.is_empty() {
+        if required {
+            add_error(errors, pointer, "missing request body");
+        }
+
+        return;
+    }
+
+    match serde_json::from_slice::<Value>(bytes) {
+        Ok(value) => validate_value(api, schema, &value, pointer, errors),
+        Err(e) => add_error(errors, pointer, &format!("invalid JSON: {}", e)),
+    }
+}
+
+/// Checks whether the given headers have a JSON content type (or none at all).
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.contains("json"))
+        .is_none()
+}
+
+/// Validates the given value against the given schema and adds the violations
+/// (if any) to the list of errors.
+pub(crate) fn validate_value(
+    api: &DefaultApiRaw,
+    schema: &DefaultSchemaRaw,
+    value: &Value,
+    pointer: &str,
+    errors: &mut Vec<FieldError>,
+) {
+    if let Some(r) = schema.reference.as_ref() {
+        if let Some(def) = r
+            .strip_prefix(DEF_REF_PREFIX)
+            .and_then(|n| api.definitions.get(n))
+        {
+            validate_value(api, def, value, pointer, errors);
+        }
+
+        return;
+    }
+
+    for s in &schema.all_of {
+        validate_value(api, s, value, pointer, errors);
+    }
+
+    if !schema.enum_.is_empty() && !schema.enum_.contains(value) {
+        add_error(errors, pointer, "value is not one of the allowed values");
+        return;
+    }
+
+    let data_type = schema.data_type.or_else(|| {
+        if schema.properties.is_empty() && schema.extra_props.is_none() {
+            None
+        } else {
+            Some(DataType::Object)
+        }
+    });
+
+    match (data_type, value) {
+        (None, _) | (Some(DataType::File), _) => (),
+        (Some(DataType::Boolean), Value::Bool(_)) => (),
+        (Some(DataType::String), Value::String(s)) => {
+            check_bounds(
+                errors,
+                pointer,
+                "length",
+                s.chars().count(),
+                schema.min_length,
+                schema.max_length,
+            );
+
+            if let Some(p) = schema.pattern.as_ref() {
+                let mut patterns = PATTERNS.lock();
+                let regex = patterns
+                    .entry(p.clone())
+                    .or_insert_with(|| Regex::new(p).ok());
+                if let Some(false) = regex.as_ref().map(|r| r.is_match(s)) {
+                    add_error(
+                        errors,
+                        pointer,
+                        &format!("value does not match the pattern {:?}", p),
+                    );
+                }
+            }
+        }
+        (Some(DataType::Integer), Value::Number(n)) if n.is_i64() || n.is_u64() => {
+            check_number(schema, n.as_f64().unwrap_or_default(), pointer, errors)
+        }
+        (Some(DataType::Number), Value::Number(n)) => {
+            check_number(schema, n.as_f64().unwrap_or_default(), pointer, errors)
+        }
+        (Some(DataType::Array), Value::Array(values)) => {
+            check_bounds(
+                errors,
+                pointer,
+                "number of items",
+                values.len(),
+                schema.min_items,
+                schema.max_items,
+            );
+
+            if schema.unique_items == Some(true)
+                && values
+                    .iter()
+                    .enumerate()
+                    .any(|(i, v)| values[..i].contains(v))
+            {
+                add_error(errors, pointer, "items are not unique");
+            }
+
+            if let Some(s) = schema.items.as_ref() {
+                for (i, v) in values.iter().enumerate() {
+                    validate_value(api, s, v, &pointer_child(pointer, &i.to_string()), errors);
+                }
+            }
+        }
+        (Some(DataType::Object), Value::Object(map)) => {
+            check_bounds(
+                errors,
+                pointer,
+                "number of properties",
+                map.len(),
+                schema.min_properties,
+                schema.max_properties,
+            );
+
+            for name in &schema.required {
+                if !map.contains_key(name) {
+                    add_error(
+                        errors,
+                        &pointer_child(pointer, name),
+                        "missing required property",
+                    );
+                }
+            }
+
+            for (name, v) in map {
+                // Optional fields are usually `Option`, which accepts `null`.
+                if v.is_null() && !schema.required.contains(name) {
+                    continue;
+                }
+
+                let pointer = pointer_child(pointer, name);
+                match (schema.properties.get(name), schema.extra_props.as_ref()) {
+                    (Some(s), _) | (None, Some(Either::Right(s))) => {
+                        validate_value(api, s, v, &pointer, errors)
+                    }
+                    (None, Some(Either::Left(false))) => {
+                        add_error(errors, &pointer, "unknown property")
+                    }
+                    _ => (),
+                }
+            }
+        }
+        (Some(ty), _) => add_error(errors, pointer, &format!("expected {}", type_name(ty))),
+    }
+}
+
+/// Checks the range constraints of a number.
+fn check_number(
+    schema: &DefaultSchemaRaw,
+    value: f64,
+    pointer: &str,
+    errors: &mut Vec<FieldError>,
+) {
+    if let Some(min) = schema.minimum.map(f64::from) {
+        if value < min || (schema.exclusive_minimum == Some(true) && value == min) {
+            add_error(
+                errors,
+                pointer,
+                &format!("{} is less than the minimum {}", value, min),
+            );
+        }
+    }
+
+    if let Some(max) = schema.maximum.map(f64::from) {
+        if value > max || (schema.exclusive_maximum == Some(true) && value == max) {
+            add_error(
+                errors,
+                pointer,
+                &format!("{} is greater than the maximum {}", value, max),
+            );
+        }
+    }
+
+    if let Some(m) = schema.multiple_of.map(f64::from).filter(|&m| m != 0.0) {
+        if (value / m).fract() != 0.0 {
+            add_error(
+                errors,
+                pointer,
+                &format!("{} is not a multiple of {}", value, m),
+            );
+        }
+    }
+}
+
+/// Checks whether the given size is within the given (inclusive) bounds.
+fn check_bounds(
+    errors: &mut Vec<FieldError>,
+    pointer: &str,
+    what: &str,
+    size: usize,
+    min: Option<u32>,
+    max: Option<u32>,
+) {
+    if let Some(min) = min.filter(|&m| size < m as usize) {
+        add_error(
+            errors,
+            pointer,
+            &format!("{} {} is less than the minimum {}", what, size, min),
+        );
+    }
+
+    if let Some(max) = max.filter(|&m| size > m as usize) {
+        add_error(
+            errors,
+            pointer,
+            &format!("{} {} is greater than the maximum {}", what, size, max),
+        );
+    }
+}
+
+/// Parses the given parameter value into a JSON value based on its type. Values
+/// which can't be parsed are left as strings (so that validation rejects them).
+fn parse_param_value(
+    value: &str,
+    data_type: Option<DataType>,
+    format: Option<CollectionFormat>,
+    items: Option<&Items>,
+) -> Value {
+    match data_type {
+        Some(DataType::Integer) => value.parse::<i64>().map(Value::from).ok(),
+        Some(DataType::Number) => value.parse::<f64>().ok().map(Value::from),
+        Some(DataType::Boolean) => value.parse::<bool>().map(Value::Bool).ok(),
+        Some(DataType::Array) => {
+            let delimiter = match format.unwrap_or(CollectionFormat::Csv) {
+                CollectionFormat::Ssv => ' ',
+                CollectionFormat::Tsv => '\t',
+                CollectionFormat::Pipes => '|',
+                _ => ',',
+            };
+
+            Some(Value::Array(
+                value
+                    .split(delimiter)
+                    .map(|v| {
+                        parse_param_value(
+                            v,
+                            items.and_then(|i| i.data_type),
+                            items.and_then(|i| i.collection_format),
+                            items.and_then(|i| i.items.as_deref()),
+                        )
+                    })
+                    .collect(),
+            ))
+        }
+        _ => None,
+    }
+    .unwrap_or_else(|| Value::String(value.into()))
+}
+
+/// Builds a schema out of the constraints in the given parameter.
+fn parameter_schema(param: &DefaultParameterRaw) -> DefaultSchemaRaw {
+    DefaultSchemaRaw {
+        data_type: param.data_type,
+        format: param.format.clone(),
+        enum_: param.enum_.clone(),
+        maximum: param.maximum,
+        exclusive_maximum: param.exclusive_maximum,
+        minimum: param.minimum,
+        exclusive_minimum: param.exclusive_minimum,
+        max_length: param.max_length,
+        min_length: param.min_length,
+        pattern: param.pattern.clone(),
+        max_items: param.max_items,
+        min_items: param.min_items,
+        unique_items: Some(param.unique_items),
+        multiple_of: param.multiple_of,
+        items: param.items.as_ref().map(|i| Box::new(items_schema(i))),
+        ..Default::default()
+    }
+}
+
+/// Builds a schema out of the constraints in the given items object.
+fn items_schema(items: &Items) -> DefaultSchemaRaw {
+    DefaultSchemaRaw {
+        data_type: items.data_type,
+        format: items.format.clone(),
+        enum_: items.enum_.clone(),
+        maximum: items.maximum,
+        exclusive_maximum: items.exclusive_maximum,
+        minimum: items.minimum,
+        exclusive_minimum: items.exclusive_minimum,
+        max_length: items.max_length,
+        min_length: items.min_length,
+        pattern: items.pattern.clone(),
+        max_items: items.max_items,
+        min_items: items.min_items,
+        unique_items: items.unique_items,
+        multiple_of: items.multiple_of,
+        items: items.items.as_ref().map(|i| Box::new(items_schema(i))),
+        ..Default::default()
+    }
+}
+
+/// Returns the pointer prefix for the given parameter location.
+fn location(in_: ParameterIn) -> &'static str {
+    match in_ {
+        ParameterIn::Path => "/path",
+        ParameterIn::Query => "/query",
+        ParameterIn::Header => "/header",
+        ParameterIn::FormData => "/formData",
+        ParameterIn::Body => "/body",
+    }
+}
+
+/// Returns a readable name for the given type.
+fn type_name(ty: DataType) -> &'static str {
+    match ty {
+        DataType::Integer => "integer",
+        DataType::Number => "number",
+        DataType::String => "string",
+        DataType::Boolean => "boolean",
+        DataType::Array => "array",
+        DataType::Object => "object",
+        DataType::File => "file",
+    }
+}
+
+/// Splits the given path into its (non-empty) segments.
+fn split_path(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|s| !s.is_empty())
+}
+
+/// Adds an error for the value at the given pointer.
+pub(crate) fn add_error(errors: &mut Vec<FieldError>, pointer: &str, message: &str) {
+    errors.push(FieldError {
+        pointer: pointer.into(),
+        message: message.into(),
+    });
+}
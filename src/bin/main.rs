@@ -1,14 +1,27 @@
 use anyhow::Error;
 use paperclip::v2::{
-    self,
-    codegen::{CrateMeta, DefaultEmitter, EmitMode, Emitter, EmitterState},
-    models::{DefaultSchema, ResolvableApi},
+    self, bundle,
+    codegen::{
+        CrateMeta, DefaultEmitter, EmitMode, Emitter, EmitterState, FormatTypes, HttpBackends,
+    },
+    diff,
+    filter::Filter,
+    lint::{self, LintConfig, Severity},
+    loader::{FileLoader, LoaderError, RefLoader, Url},
+    models::{DefaultSchema, ResolvableApi, SpecFormat},
+};
+use paperclip::{v3, PaperClipError, Report};
+use reqwest::header::CONTENT_TYPE;
+use serde::Serialize;
+use structopt::{
+    clap::{self, ErrorKind},
+    StructOpt,
 };
-use paperclip::PaperClipError;
-use structopt::StructOpt;
 
 use std::fs::{self, File};
+use std::io::{self, Write};
 use std::path::PathBuf;
+use std::time::Duration;
 
 fn parse_version(s: &str) -> Result<OApiVersion, Error> {
     match s {
@@ -18,12 +31,117 @@ fn parse_version(s: &str) -> Result<OApiVersion, Error> {
     }
 }
 
-fn parse_spec(s: &str) -> Result<ResolvableApi<DefaultSchema>, Error> {
-    let fd = File::open(s)?;
-    Ok(v2::from_reader(fd)?)
+/// Timeout for fetching documents over HTTP.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+/// Maximum number of redirects to follow when fetching documents over HTTP.
+const MAX_REDIRECTS: usize = 10;
+/// Exit status for `diff` when there are breaking changes.
+const BREAKING_CHANGES_EXIT_STATUS: i32 = 2;
+
+/// Loads specs (and the documents referred by them) from the local
+/// filesystem or over HTTP.
+struct SpecLoader {
+    client: reqwest::blocking::Client,
+}
+
+impl SpecLoader {
+    fn new() -> Result<Self, Error> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(FETCH_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::limited(MAX_REDIRECTS))
+            .build()?;
+        Ok(SpecLoader { client })
+    }
+
+    /// Returns the URI for the given path or URL.
+    fn uri(s: &str) -> Result<Url, Error> {
+        if s.starts_with("http://") || s.starts_with("https://") {
+            return Ok(Url::parse(s)?);
+        }
+
+        Url::from_file_path(fs::canonicalize(s)?)
+            .map_err(|_| anyhow::anyhow!("Invalid path to spec: {:?}", s))
+    }
+
+    /// Fetches the document at the given URI and returns its contents,
+    /// its actual URI (after redirects) and its format.
+    fn fetch(&self, uri: &Url) -> Result<(Vec<u8>, Url, SpecFormat), LoaderError> {
+        if uri.scheme() != "http" && uri.scheme() != "https" {
+            let contents = FileLoader.load(uri)?.into_bytes();
+            let fmt = SpecFormat::sniff(&contents);
+            return Ok((contents, uri.clone(), fmt));
+        }
+
+        log::info!("Fetching {}", uri);
+        let resp = self.client.get(uri.clone()).send()?.error_for_status()?;
+        let uri = resp.url().clone();
+        let content_type = resp
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_lowercase);
+        let contents = resp.bytes()?.to_vec();
+        let fmt = match content_type {
+            Some(ref c) if c.contains("json") => SpecFormat::Json,
+            Some(ref c) if c.contains("yaml") || c.contains("yml") => SpecFormat::Yaml,
+            _ => SpecFormat::sniff(&contents),
+        };
+
+        Ok((contents, uri, fmt))
+    }
+}
+
+impl RefLoader for SpecLoader {
+    fn load(&self, uri: &Url) -> Result<String, LoaderError> {
+        let (contents, _, _) = self.fetch(uri)?;
+        Ok(String::from_utf8(contents)?)
+    }
+}
+
+/// Loads the spec from the given path or URL, and returns it along
+/// with its URI (for resolving references to other documents).
+fn parse_spec(
+    s: &str,
+    api: &OApiVersion,
+    loader: &SpecLoader,
+) -> Result<(ResolvableApi<DefaultSchema>, Url), Error> {
+    let (contents, uri, fmt) = loader
+        .fetch(&SpecLoader::uri(s)?)
+        .map_err(|e| anyhow::anyhow!("Failed to load spec {:?}: {}", s, e))?;
+    match api {
+        OApiVersion::V2 => {
+            let spec = v2::from_reader_with_format(&*contents, fmt)?;
+            Ok((spec, uri))
+        }
+        OApiVersion::V3 => {
+            // We can only parse (and validate) v3 specs for now.
+            v3::from_reader_with_format(&*contents, fmt)?;
+            Err(PaperClipError::UnsupportedCodegenVersion.into())
+        }
+    }
+}
+
+/// Writes the given spec to the output file (or stdout) in the given format.
+fn write_spec<T: Serialize>(
+    spec: &T,
+    fmt: SpecFormat,
+    output: Option<PathBuf>,
+) -> Result<(), Error> {
+    let mut writer: Box<dyn Write> = match output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+
+    match fmt {
+        SpecFormat::Json => serde_json::to_writer_pretty(&mut writer, spec)?,
+        SpecFormat::Yaml => serde_yaml::to_writer(&mut writer, spec)?,
+    }
+
+    writeln!(writer)?;
+    Ok(())
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 enum OApiVersion {
     V2,
     V3,
@@ -31,18 +149,75 @@ enum OApiVersion {
 
 #[derive(Debug, StructOpt)]
 struct Opt {
+    #[structopt(subcommand)]
+    cmd: Option<Command>,
     /// Path to OpenAPI spec in JSON/YAML format (also supports publicly accessible URLs).
-    #[structopt(parse(try_from_str = "parse_spec"))]
-    spec: ResolvableApi<DefaultSchema>,
+    spec: Option<String>,
     /// OpenAPI version (e.g., v2).
     #[structopt(long = "api", parse(try_from_str = "parse_version"))]
-    api: OApiVersion,
+    api: Option<OApiVersion>,
     /// Output directory to write code (default: current working directory).
     #[structopt(short = "o", long = "out", parse(from_os_str))]
     output: Option<PathBuf>,
     /// Emit CLI target instead.
     #[structopt(long = "cli")]
     cli: bool,
+    /// Emit a synchronous client (backed by `reqwest::blocking`) instead
+    /// of the async one.
+    #[structopt(long = "blocking")]
+    blocking: bool,
+    /// Report all the errors in the spec (along with their locations)
+    /// instead of stopping at the first one.
+    #[structopt(long = "diagnostics")]
+    diagnostics: bool,
+    /// Generate the operations with this tag (can be repeated).
+    #[structopt(long = "include-tag", number_of_values = 1)]
+    include_tags: Vec<String>,
+    /// Skip the operations with this tag (can be repeated).
+    #[structopt(long = "exclude-tag", number_of_values = 1)]
+    exclude_tags: Vec<String>,
+    /// Generate the operations with paths matching this glob (can be repeated).
+    /// `*` matches within a path segment and `**` matches across segments.
+    #[structopt(long = "include-path", number_of_values = 1)]
+    include_paths: Vec<String>,
+    /// Skip the operations with paths matching this glob (can be repeated).
+    #[structopt(long = "exclude-path", number_of_values = 1)]
+    exclude_paths: Vec<String>,
+    /// Generate the operation with this ID (can be repeated).
+    #[structopt(long = "include-op", number_of_values = 1)]
+    include_operations: Vec<String>,
+    /// Skip the operation with this ID (can be repeated).
+    #[structopt(long = "exclude-op", number_of_values = 1)]
+    exclude_operations: Vec<String>,
+    /// Group operations into modules for their tags, each with a facade
+    /// for calling them through the API client.
+    #[structopt(long = "group-by-tags")]
+    group_by_tags: bool,
+    /// Use an existing Rust type for a definition instead of generating one
+    /// (e.g., `Money=common::Money`, can be repeated).
+    #[structopt(
+        long = "rust-type",
+        number_of_values = 1,
+        parse(try_from_str = "parse_rust_type")
+    )]
+    rust_types: Vec<(String, String)>,
+    /// Map values of this string format onto a dedicated Rust type instead of `String`
+    /// (one of `date`, `date-time`, `uuid`, `byte`, `binary` or `all`, can be repeated).
+    #[structopt(
+        long = "format-type",
+        number_of_values = 1,
+        parse(try_from_str = "parse_format_type")
+    )]
+    format_types: Vec<FormatTypes>,
+    /// Also implement the API client for this HTTP backend (one of `hyper`
+    /// or `awc`, can be repeated). Each backend is gated behind a feature
+    /// of the same name in the generated crate.
+    #[structopt(
+        long = "backend",
+        number_of_values = 1,
+        parse(try_from_str = "parse_backend")
+    )]
+    backends: Vec<HttpBackends>,
     /// Name of the crate. If this is not specified, then the name of the
     /// working directory is assumed to be crate name.
     #[structopt(long = "name")]
@@ -52,14 +227,288 @@ struct Opt {
     pub version: Option<String>,
 }
 
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Convert the given OpenAPI v2 spec to another version (output is
+    /// in the same format as the input).
+    #[structopt(name = "convert")]
+    Convert {
+        /// Path to OpenAPI v2 spec in JSON/YAML format (also supports publicly accessible URLs).
+        spec: String,
+        /// OpenAPI version to convert to (only v3 is supported).
+        #[structopt(long = "to", parse(try_from_str = "parse_version"))]
+        to: OApiVersion,
+        /// Output file (default: stdout).
+        #[structopt(short = "o", long = "out", parse(from_os_str))]
+        output: Option<PathBuf>,
+    },
+    /// Check the given OpenAPI v2 spec for problems.
+    #[structopt(name = "lint")]
+    Lint {
+        /// Path to OpenAPI v2 spec in JSON/YAML format (also supports publicly accessible URLs).
+        spec: String,
+        /// Path to linter config in JSON/YAML format (for enabling/disabling rules).
+        #[structopt(long = "config", parse(from_os_str))]
+        config: Option<PathBuf>,
+        /// Output format (text or json).
+        #[structopt(
+            long = "format",
+            default_value = "text",
+            parse(try_from_str = "parse_output_format")
+        )]
+        format: OutputFormat,
+    },
+    /// Compare two versions of an OpenAPI v2 spec and report the changes
+    /// (exits with status 2 if there are breaking changes).
+    #[structopt(name = "diff")]
+    Diff {
+        /// Path to the old spec in JSON/YAML format (also supports publicly accessible URLs).
+        old: String,
+        /// Path to the new spec in JSON/YAML format (also supports publicly accessible URLs).
+        new: String,
+        /// Output format (text or json).
+        #[structopt(
+            long = "format",
+            default_value = "text",
+            parse(try_from_str = "parse_output_format")
+        )]
+        format: OutputFormat,
+    },
+    /// Bundle the given OpenAPI v2 spec (along with the documents it refers to)
+    /// into a single document, with anonymous objects moved to definitions and
+    /// the keys sorted.
+    #[structopt(name = "bundle")]
+    Bundle {
+        /// Path to OpenAPI v2 spec in JSON/YAML format (also supports publicly accessible URLs).
+        spec: String,
+        /// Output file (default: stdout).
+        #[structopt(short = "o", long = "out", parse(from_os_str))]
+        output: Option<PathBuf>,
+        /// Output format (json or yaml, defaults to the format of the spec).
+        #[structopt(long = "format", parse(try_from_str = "parse_spec_format"))]
+        format: Option<SpecFormat>,
+    },
+}
+
+/// Format for reports.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+fn parse_output_format(s: &str) -> Result<OutputFormat, Error> {
+    match s {
+        "text" => Ok(OutputFormat::Text),
+        "json" => Ok(OutputFormat::Json),
+        _ => Err(anyhow::anyhow!("Unknown output format {:?}", s)),
+    }
+}
+
+fn parse_rust_type(s: &str) -> Result<(String, String), Error> {
+    match s.split_once('=') {
+        Some((name, ty)) if !name.is_empty() && !ty.is_empty() => Ok((name.into(), ty.into())),
+        _ => Err(anyhow::anyhow!(
+            "Expected definition name and Rust type (NAME=TYPE), found {:?}",
+            s
+        )),
+    }
+}
+
+fn parse_format_type(s: &str) -> Result<FormatTypes, Error> {
+    let mut types = FormatTypes::default();
+    match s {
+        "date" => types.date = true,
+        "date-time" => types.date_time = true,
+        "uuid" => types.uuid = true,
+        "byte" => types.byte = true,
+        "binary" => types.binary = true,
+        "all" => return Ok(FormatTypes::all()),
+        _ => return Err(anyhow::anyhow!("Unsupported format {:?}", s)),
+    }
+
+    Ok(types)
+}
+
+fn parse_backend(s: &str) -> Result<HttpBackends, Error> {
+    let mut backends = HttpBackends::default();
+    match s {
+        "hyper" => backends.hyper = true,
+        "awc" => backends.awc = true,
+        _ => return Err(anyhow::anyhow!("Unsupported HTTP backend {:?}", s)),
+    }
+
+    Ok(backends)
+}
+
+fn parse_spec_format(s: &str) -> Result<SpecFormat, Error> {
+    match s {
+        "json" => Ok(SpecFormat::Json),
+        "yaml" => Ok(SpecFormat::Yaml),
+        _ => Err(anyhow::anyhow!("Unknown spec format {:?}", s)),
+    }
+}
+
+/// Lints the given v2 spec and prints the findings.
+fn lint_spec(spec: &str, config: Option<PathBuf>, format: OutputFormat) -> Result<(), Error> {
+    let config = match config {
+        Some(path) => serde_yaml::from_reader(File::open(path)?)?,
+        None => LintConfig::default(),
+    };
+
+    let (api, _) = parse_spec(spec, &OApiVersion::V2, &SpecLoader::new()?)?;
+    let findings = lint::lint(&api, &config);
+    match format {
+        OutputFormat::Text => {
+            for finding in &findings {
+                println!("{}", finding);
+            }
+        }
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(io::stdout(), &findings)?;
+            println!();
+        }
+    }
+
+    let errors = findings
+        .iter()
+        .filter(|f| f.severity == Severity::Error)
+        .count();
+    if errors > 0 {
+        return Err(anyhow::anyhow!("Found {} error(s) in spec.", errors));
+    }
+
+    Ok(())
+}
+
+/// Error for breaking changes found by `diff`.
+#[derive(Debug, thiserror::Error)]
+#[error("Found {} breaking change(s).", _0)]
+struct BreakingChanges(usize);
+
+/// Compares the given v2 specs and prints the changes.
+fn diff_specs(old: &str, new: &str, format: OutputFormat) -> Result<(), Error> {
+    let loader = SpecLoader::new()?;
+    // Definitions in other documents are loaded, so that the differ can follow them.
+    let (old, old_base) = parse_spec(old, &OApiVersion::V2, &loader)?;
+    let old = old.load_external_refs(&old_base, &loader)?;
+    let (new, new_base) = parse_spec(new, &OApiVersion::V2, &loader)?;
+    let new = new.load_external_refs(&new_base, &loader)?;
+    let changes = diff::diff(&old, &new);
+    match format {
+        OutputFormat::Text => {
+            for change in &changes {
+                println!("{}", change);
+            }
+        }
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(io::stdout(), &changes)?;
+            println!();
+        }
+    }
+
+    let breaking = changes.iter().filter(|c| c.is_breaking()).count();
+    if breaking > 0 {
+        return Err(BreakingChanges(breaking).into());
+    }
+
+    Ok(())
+}
+
+/// Bundles the given v2 spec into a single document.
+fn bundle_spec(
+    spec: &str,
+    output: Option<PathBuf>,
+    format: Option<SpecFormat>,
+) -> Result<(), Error> {
+    let loader = SpecLoader::new()?;
+    let (api, base) = parse_spec(spec, &OApiVersion::V2, &loader)?;
+    let format = format.unwrap_or(api.spec_format);
+    let bundled = bundle::bundle(api, &base, &loader)?;
+    write_spec(&bundle::canonical_value(&bundled)?, format, output)
+}
+
+/// Converts the given v2 spec to the given version.
+fn convert(spec: &str, to: OApiVersion, output: Option<PathBuf>) -> Result<(), Error> {
+    if to != OApiVersion::V3 {
+        return Err(PaperClipError::UnsupportedOpenAPIVersion.into());
+    }
+
+    let (api, _) = parse_spec(spec, &OApiVersion::V2, &SpecLoader::new()?)?;
+    let converted = v3::Api::from_v2(&api)?;
+    write_spec(&converted, api.spec_format, output)
+}
+
 fn parse_args_and_run() -> Result<(), Error> {
     let opt = Opt::from_args();
-    if let OApiVersion::V3 = opt.api {
-        return Err(PaperClipError::UnsupportedOpenAPIVersion.into());
+    match opt.cmd {
+        Some(Command::Convert { spec, to, output }) => return convert(&spec, to, output),
+        Some(Command::Lint {
+            spec,
+            config,
+            format,
+        }) => return lint_spec(&spec, config, format),
+        Some(Command::Diff { old, new, format }) => return diff_specs(&old, &new, format),
+        Some(Command::Bundle {
+            spec,
+            output,
+            format,
+        }) => return bundle_spec(&spec, output, format),
+        None => (),
     }
 
-    let spec = opt.spec.resolve()?;
+    // Spec and version are required for codegen.
+    let (spec, api) = match (opt.spec, opt.api) {
+        (Some(s), Some(a)) => (s, a),
+        _ => clap::Error::with_description(
+            "Path to the spec and the OpenAPI version (--api) are required for codegen.",
+            ErrorKind::MissingRequiredArgument,
+        )
+        .exit(),
+    };
+
+    // References to other documents are relative to the spec.
+    let loader = SpecLoader::new()?;
+    let (spec, base) = parse_spec(&spec, &api, &loader)?;
     let mut state = EmitterState::default();
+    state.diagnostics = opt.diagnostics;
+    let spec = if opt.diagnostics {
+        // Errors from resolving are reported along with the ones from codegen.
+        let mut report = Report::default();
+        let spec = spec.resolve_with_loader_into_report(&base, &loader, &mut report);
+        match spec {
+            Some(s) => {
+                state.set_resolver_report(report);
+                s
+            }
+            None => return Err(report.into()),
+        }
+    } else {
+        spec.resolve_with_loader(&base, &loader)?
+    };
+
+    state.filter = Filter {
+        include_tags: opt.include_tags,
+        exclude_tags: opt.exclude_tags,
+        include_paths: opt.include_paths,
+        exclude_paths: opt.exclude_paths,
+        include_operations: opt.include_operations,
+        exclude_operations: opt.exclude_operations,
+    };
+    state.group_by_tags = opt.group_by_tags;
+    state.rust_types = opt.rust_types.into_iter().collect();
+    for t in opt.format_types {
+        let types = &mut state.format_types;
+        types.date |= t.date;
+        types.date_time |= t.date_time;
+        types.uuid |= t.uuid;
+        types.byte |= t.byte;
+        types.binary |= t.binary;
+    }
+    for b in opt.backends {
+        state.http_backends.hyper |= b.hyper;
+        state.http_backends.awc |= b.awc;
+    }
 
     if let Some(o) = opt.output {
         fs::create_dir_all(&o)?;
@@ -72,6 +521,7 @@ fn parse_args_and_run() -> Result<(), Error> {
     } else {
         meta.mode = EmitMode::Crate;
     }
+    meta.blocking = opt.blocking;
     if opt.name.is_some() {
         meta.name = opt.name;
     }
@@ -88,5 +538,11 @@ fn main() {
     env_logger::init();
     if let Err(e) = parse_args_and_run() {
         eprintln!("{}", e);
+        // Breaking changes (from `diff`) aren't failures, so they have their own status.
+        if e.downcast_ref::<BreakingChanges>().is_some() {
+            std::process::exit(BREAKING_CHANGES_EXIT_STATUS);
+        }
+
+        std::process::exit(1);
     }
 }
@@ -1,4 +1,11 @@
+#[cfg(feature = "v2")]
+use paperclip_core::pointer_child;
+#[cfg(feature = "v2")]
+use paperclip_core::v2::models::SpecFormat;
+
 use std::collections::HashSet;
+#[cfg(feature = "v2")]
+use std::fmt;
 use std::path::PathBuf;
 
 macro_rules! impl_err_from {
@@ -26,6 +33,9 @@ pub enum PaperClipError {
     /// Currently, we only support OpenAPI v2, and eventually v3.
     #[error("This version of OpenAPI is unsupported.")]
     UnsupportedOpenAPIVersion,
+    /// Code generation is only supported for OpenAPI v2 (for now).
+    #[error("Code generation is not supported for this version of OpenAPI.")]
+    UnsupportedCodegenVersion,
     /// Paths listed in the spec must be unique.
     #[error("Path similar to {:?} already exists.", _0)]
     RelativePathNotUnique(String),
@@ -46,6 +56,12 @@ pub enum PaperClipError {
     /// A valid path cannot be obtained for the given definition.
     #[error("Invalid path for definition: {:?}", _0)]
     InvalidDefinitionPath(PathBuf),
+    /// The module for grouping operations by tags conflicts with a definition module.
+    #[error(
+        "Module {:?} (for grouping operations by tags) conflicts with a definition.",
+        _0
+    )]
+    TagModuleConflict(String),
     /// I/O errors.
     #[error("I/O error: {}", _0)]
     Io(std::io::Error),
@@ -55,11 +71,19 @@ pub enum PaperClipError {
     /// YAML coding errors.
     #[error("YAML error: {}", _0)]
     Yaml(serde_yaml::Error),
+    #[cfg(feature = "v2")]
+    /// The spec couldn't be deserialized.
+    #[error("{}", _0)]
+    InvalidSpec(SpecError),
     #[cfg(feature = "codegen-fmt")]
     /// Errors from rustfmt.
     #[error("Rustfmt formatting error: {}", _0)]
     RustFmt(rustfmt_nightly::ErrorKind),
     #[cfg(feature = "codegen")]
+    /// Errors collected during codegen in diagnostics mode.
+    #[error("{}", _0)]
+    Diagnostics(paperclip_core::Report<anyhow::Error>),
+    #[cfg(feature = "codegen")]
     /// Errors in templating.
     #[error("Templating error: {}", _0)]
     Templating(tinytemplate::error::Error),
@@ -69,7 +93,106 @@ impl_err_from!(PaperClipError::std::io::Error > Io);
 impl_err_from!(PaperClipError::serde_json::Error > Json);
 impl_err_from!(PaperClipError::serde_yaml::Error > Yaml);
 impl_err_from!(PaperClipError::paperclip_core::ValidationError > Validation);
+#[cfg(feature = "v2")]
+impl_err_from!(PaperClipError::SpecError > InvalidSpec);
 #[cfg(feature = "codegen-fmt")]
 impl_err_from!(PaperClipError::rustfmt_nightly::ErrorKind > RustFmt);
 #[cfg(feature = "codegen")]
 impl_err_from!(PaperClipError::tinytemplate::error::Error > Templating);
+
+/// Error encountered while deserializing a spec, along with
+/// the location where it occurred.
+#[cfg(feature = "v2")]
+#[derive(Debug)]
+pub struct SpecError {
+    /// Format of the spec.
+    pub format: SpecFormat,
+    /// Line (1-based) where the error occurred (if known).
+    pub line: Option<usize>,
+    /// Column (1-based) where the error occurred (if known).
+    pub column: Option<usize>,
+    /// JSON pointer to the value which couldn't be deserialized
+    /// (empty for the root of the document).
+    pub pointer: String,
+    /// Error message from the deserializer (as it is).
+    pub message: String,
+}
+
+#[cfg(feature = "v2")]
+impl SpecError {
+    pub(crate) fn from_json(err: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        let pointer = json_pointer(err.path());
+        Self::json(err.into_inner(), pointer)
+    }
+
+    /// Error found after deserializing the JSON document (i.e., trailing characters).
+    pub(crate) fn from_json_end(err: serde_json::Error) -> Self {
+        Self::json(err, String::new())
+    }
+
+    fn json(err: serde_json::Error, pointer: String) -> Self {
+        let (line, column) = (err.line(), err.column());
+        SpecError {
+            format: SpecFormat::Json,
+            line: Some(line).filter(|&l| l > 0),
+            column: Some(column).filter(|_| line > 0),
+            pointer,
+            message: err.to_string(),
+        }
+    }
+
+    pub(crate) fn from_yaml(err: serde_path_to_error::Error<serde_yaml::Error>) -> Self {
+        let pointer = json_pointer(err.path());
+        let err = err.into_inner();
+        let location = err.location();
+        SpecError {
+            format: SpecFormat::Yaml,
+            line: location.as_ref().map(|l| l.line()),
+            column: location.as_ref().map(|l| l.column()),
+            pointer,
+            message: err.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "v2")]
+impl fmt::Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let format = match self.format {
+            SpecFormat::Json => "JSON",
+            SpecFormat::Yaml => "YAML",
+        };
+
+        write!(f, "Cannot parse {} spec", format)?;
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, " (line {}, column {})", line, column)?;
+        }
+
+        if !self.pointer.is_empty() {
+            write!(f, " at {:?}", self.pointer)?;
+        }
+
+        write!(f, ": {}", self.message)
+    }
+}
+
+#[cfg(feature = "v2")]
+impl std::error::Error for SpecError {}
+
+/// Converts the path tracked during deserialization into a JSON pointer.
+#[cfg(feature = "v2")]
+fn json_pointer(path: &serde_path_to_error::Path) -> String {
+    use serde_path_to_error::Segment;
+
+    let mut pointer = String::new();
+    for segment in path.iter() {
+        pointer = match segment {
+            Segment::Seq { index } => pointer_child(&pointer, &index.to_string()),
+            Segment::Map { key } => pointer_child(&pointer, key),
+            Segment::Enum { variant } => pointer_child(&pointer, variant),
+            Segment::Unknown => break,
+        }
+    }
+
+    pointer
+}
@@ -11,8 +11,13 @@ extern crate log;
 mod error;
 #[cfg(feature = "v2")]
 pub mod v2;
+#[cfg(feature = "v3")]
+pub mod v3;
 
+#[cfg(feature = "v2")]
+pub use error::SpecError;
 pub use error::{PaperClipError, PaperClipResult};
+pub use paperclip_core::{Diagnostic, Report};
 #[cfg(feature = "v2")]
 pub use paperclip_macros::api_v2_schema_struct as api_v2_schema;
 
@@ -21,6 +26,8 @@ pub mod actix {
     //! Plugin types, traits and macros for actix-web framework.
 
     pub use paperclip_actix::{api_v2_errors, api_v2_operation, Apiv2Schema, Apiv2Security};
-    pub use paperclip_actix::{web, App, Mountable, OpenApiExt};
+    pub use paperclip_actix::{
+        validation, web, App, Mountable, OpenApiExt, RequestValidator, ResponseValidator,
+    };
     pub use paperclip_core::v2::{OperationModifier, ResponderWrapper};
 }
@@ -0,0 +1,199 @@
+//! Bundling OpenAPI v2 specs into a single document.
+//!
+//! Bundling loads the definitions referred from other documents into the spec,
+//! moves the anonymous object schemas (in definitions, body parameters and
+//! responses) to definitions (named the same way as the anonymous objects
+//! in codegen) and replaces them with references.
+//!
+//! ```rust,no_run
+//! use paperclip::v2::{self, bundle, loader::{FileLoader, Url}, DefaultSchema, ResolvableApi};
+//!
+//! use std::fs::File;
+//!
+//! let mut fd = File::open("/path/to/my_spec.yaml").unwrap(); // yaml or json
+//! let api: ResolvableApi<DefaultSchema> = v2::from_reader(&mut fd).unwrap();
+//! let base = Url::from_file_path("/path/to/my_spec.yaml").unwrap();
+//! let bundled = bundle::bundle(api, &base, &FileLoader).unwrap();
+//! let value = bundle::canonical_value(&bundled).unwrap();
+//! println!("{}", serde_json::to_string_pretty(&value).unwrap());
+//! ```
+
+use super::{
+    codegen::anon_name,
+    loader::{RefLoader, Url},
+    models::{Either, Resolvable, ResolvableApi, ResolvableParameter, ResolvableResponse},
+    raw_schema, Schema, DEF_REF_PREFIX,
+};
+use paperclip_core::ValidationError;
+use parking_lot::RwLock;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{Map, Value};
+
+use std::collections::{BTreeMap, HashSet};
+use std::mem;
+use std::sync::Arc;
+
+/// Bundles the given (unresolved) spec into a single document. References
+/// to other documents are resolved relative to the given base URI using the
+/// given loader.
+pub fn bundle<S, L>(
+    api: ResolvableApi<S>,
+    base: &Url,
+    loader: &L,
+) -> Result<ResolvableApi<S>, ValidationError>
+where
+    S: Schema + Default + DeserializeOwned,
+    L: RefLoader,
+{
+    let mut api = api.load_external_refs(base, loader)?;
+    let mut hoister = Hoister {
+        taken: api.definitions.keys().cloned().collect(),
+        defs: BTreeMap::new(),
+    };
+
+    for (name, schema) in &api.definitions {
+        hoister.visit(raw_schema(schema), &[name]);
+    }
+
+    for (name, param) in &api.parameters {
+        hoister.parameter(param, &[name, "Body"]);
+    }
+
+    for (name, resp) in &api.responses {
+        hoister.response(resp, &[name, "Response"]);
+    }
+
+    for (path, map) in &api.paths {
+        for param in map.parameters.iter().filter_map(Either::right) {
+            hoister.parameter(param, &[path, "Body"]);
+        }
+
+        for (&method, op) in &map.methods {
+            let method = method.to_string();
+            for param in op.parameters.iter().filter_map(Either::right) {
+                hoister.parameter(param, &[&method, path, "Body"]);
+            }
+
+            for resp in op.responses.values().filter_map(Either::right) {
+                hoister.response(resp, &[&method, path, "Response"]);
+            }
+        }
+    }
+
+    api.definitions.extend(hoister.defs);
+    Ok(api)
+}
+
+/// Returns the JSON value for the given spec with all the object keys sorted,
+/// so that it's the same regardless of how the spec was built. This can be
+/// serialized to JSON or YAML.
+pub fn canonical_value<T: Serialize>(spec: &T) -> serde_json::Result<Value> {
+    serde_json::to_value(spec).map(sort_keys)
+}
+
+/// Sorts the object keys in the given value (recursively).
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries = map.into_iter().collect::<Vec<_>>();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k, sort_keys(v)))
+                    .collect::<Map<_, _>>(),
+            )
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(sort_keys).collect()),
+        v => v,
+    }
+}
+
+/// Moves anonymous object schemas to definitions.
+struct Hoister<S> {
+    /// Names of definitions (including the hoisted ones).
+    taken: HashSet<String>,
+    /// Hoisted definitions.
+    defs: BTreeMap<String, Resolvable<S>>,
+}
+
+impl<S> Hoister<S>
+where
+    S: Schema + Default,
+{
+    /// Hoists the anonymous schema (if any) in the given parameter.
+    fn parameter(&mut self, param: &ResolvableParameter<S>, parents: &[&str]) {
+        if let Some(s) = param.write().schema.as_mut() {
+            self.hoist(s, parents);
+        }
+    }
+
+    /// Hoists the anonymous schema (if any) in the given response.
+    fn response(&mut self, resp: &ResolvableResponse<S>, parents: &[&str]) {
+        if let Some(s) = resp.write().schema.as_mut() {
+            self.hoist(s, parents);
+        }
+    }
+
+    /// Replaces the given schema with a reference to a new definition
+    /// if it's an anonymous object, and hoists the schemas inside it.
+    fn hoist(&mut self, schema: &mut Resolvable<S>, parents: &[&str]) {
+        let is_anonymous = {
+            let s = raw_schema(schema).read();
+            s.reference().is_none() && (s.properties().is_some() || s.all_of().is_some())
+        };
+
+        let name = match anon_name(parents) {
+            Some(n) if is_anonymous => self.unique_name(n),
+            _ => return self.visit(raw_schema(schema), parents),
+        };
+
+        let mut ref_schema = S::default();
+        ref_schema.set_reference(String::from(DEF_REF_PREFIX) + &name);
+        let hoisted = mem::replace(schema, Resolvable::Raw(Arc::new(RwLock::new(ref_schema))));
+        self.visit(raw_schema(&hoisted), &[&name]);
+        self.defs.insert(name, hoisted);
+    }
+
+    /// Hoists the anonymous objects in the given schema.
+    fn visit(&mut self, schema: &Arc<RwLock<S>>, parents: &[&str]) {
+        let mut schema = schema.write();
+        if let Some(props) = schema.properties_mut() {
+            for (name, prop) in props.iter_mut() {
+                let mut parents = parents.to_vec();
+                parents.push(name);
+                self.hoist(prop, &parents);
+            }
+        }
+
+        if let Some(s) = schema.items_mut() {
+            let mut parents = parents.to_vec();
+            parents.push("item");
+            self.hoist(s, &parents);
+        }
+
+        if let Some(Either::Right(s)) = schema.additional_properties_mut() {
+            self.hoist(s, parents);
+        }
+
+        // Properties in `allOf` schemas belong to this schema.
+        if let Some(schemas) = schema.all_of_mut() {
+            for s in schemas {
+                self.visit(raw_schema(s), parents);
+            }
+        }
+    }
+
+    /// Returns an unused name for a definition based on the given name.
+    fn unique_name(&mut self, name: String) -> String {
+        let mut unique = name.clone();
+        let mut i = 1;
+        while self.taken.contains(&unique) {
+            unique = format!("{}{}", name, i);
+            i += 1;
+        }
+
+        self.taken.insert(unique.clone());
+        unique
+    }
+}
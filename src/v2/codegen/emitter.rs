@@ -1,22 +1,25 @@
 use super::object::{
-    ApiObject, ObjectContainer, ObjectField, ObjectVariant, OpRequirement, Parameter, Response,
+    ApiObject, FieldConstraints, ObjectContainer, ObjectField, ObjectVariant, OpRequirement,
+    Parameter, Response, TaggedVariant,
 };
 use super::state::{ChildModule, EmitterState};
-use super::CrateMeta;
+use super::{CrateMeta, FormatTypes};
 use crate::error::PaperClipError;
 use crate::v2::{
     models::{
         Coder, CollectionFormat, DataType, DataTypeFormat, Either, HttpMethod, Items, MediaRange,
-        ParameterIn, Reference, ResolvableApi, ResolvableOperation, ResolvableParameter,
-        ResolvablePathItem, ResolvableResponse, JSON_CODER, JSON_MIME, YAML_CODER, YAML_MIME,
+        ParameterIn, Reference, Resolvable, ResolvableApi, ResolvableOperation,
+        ResolvableParameter, ResolvablePathItem, ResolvableResponse, JSON_CODER, JSON_MIME,
+        YAML_CODER, YAML_MIME,
     },
-    Schema,
+    pointer_child, Schema,
 };
 use anyhow::Error;
 use heck::{CamelCase, SnekCase};
 use http::{header::HeaderName, HeaderMap};
 use itertools::Itertools;
 use parking_lot::RwLock;
+use std::borrow::Cow;
 use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fmt::Debug;
 use std::fs;
@@ -141,18 +144,12 @@ pub trait Emitter: Sized {
     /// name for some definition based on its parent names. This is called whenever
     /// a definition doesn't have a name (i.e., through `$ref`) and we have to generate it.
     fn def_anon_name(&self, def: &Self::Definition, parents: &[&str]) -> Option<String> {
-        let mut name = String::new();
-        parents.iter().for_each(|s| {
-            name.push_str(s);
-            name.push_str("_");
-        });
-
-        if name.is_empty() {
+        let name = anon_name(parents);
+        if name.is_none() {
             trace!("Unable to get name for anonymous schema: {:?}", def);
-            None
-        } else {
-            Some(name.to_camel_case())
         }
+
+        name
     }
 
     /// Returns the enum variant of a possible value in the given definition.
@@ -243,6 +240,15 @@ pub trait Emitter: Sized {
         let state = self.state();
         state.reset_internal_fields();
 
+        // Leave out the operations (and the definitions) we don't need.
+        let filtered;
+        let api = if state.filter.is_empty() {
+            api
+        } else {
+            filtered = state.filter.apply(api);
+            &filtered
+        };
+
         let m = state.get_meta();
         if m.borrow().is_none() {
             let mut meta = CrateMeta::default();
@@ -267,6 +273,7 @@ pub trait Emitter: Sized {
         }
 
         state.set_media_info(api.spec_format, &coders);
+        state.set_security_schemes(&api.security_definitions);
 
         // Set host and base path.
         if let Some(h) = api.host.as_ref() {
@@ -295,12 +302,15 @@ pub trait Emitter: Sized {
         for (name, schema) in &api.definitions {
             debug!("Creating definition {}", name);
             let schema = schema.read();
-            gen.generate_from_definition(&schema)?;
+            if let Err(e) = gen.generate_from_definition(&schema) {
+                state.report(&pointer_child("/definitions", name), e)?;
+            }
         }
 
         for (path, map) in &api.paths {
             RequirementCollector {
                 path,
+                pointer: pointer_child("/paths", path),
                 emitter: self,
                 api,
                 map,
@@ -309,6 +319,9 @@ pub trait Emitter: Sized {
             .collect()?;
         }
 
+        // In diagnostics mode, we bail out only after we've gone through everything.
+        state.take_report()?;
+        state.add_tag_modules(&api.tags)?;
         state.declare_modules()?;
         state.write_definitions()?;
         state.add_builders()?;
@@ -327,20 +340,39 @@ pub trait Emitter: Sized {
         def: &Self::Definition,
         ctx: DefinitionContext<'a>,
     ) -> Result<EmittedUnit, Error> {
+        // Existing Rust types are neither defined nor emitted - we simply refer to them.
+        if let Some(ty) = self.state().rust_type(def) {
+            trace!("Using existing Rust type: {}", ty);
+            if ctx.define {
+                return Ok(EmittedUnit::None);
+            }
+
+            return Ok(EmittedUnit::Known(ty));
+        }
+
         if let Some(u) = CodegenEmitter(self).try_emit_enum(def, ctx.clone())? {
             return Ok(u);
         }
 
-        if let Some(ty) = matching_unit_type(def.format(), def.data_type()) {
+        let state = self.state();
+        let mut formats = state.format_types;
+        // Binary values can only be streamed as a whole (i.e., in responses).
+        formats.binary &= ctx.parents.is_empty();
+        if let Some(ty) = matching_unit_type(
+            def.format(),
+            def.data_type(),
+            formats,
+            &state.normalized_mod_prefix(),
+        ) {
             trace!("Matches unit type: {}", ty);
             if ctx.define {
                 return Ok(EmittedUnit::None);
             }
 
-            return Ok(EmittedUnit::Known(ty.to_owned()));
+            return Ok(EmittedUnit::Known(ty.into_owned()));
         }
 
-        match def.data_type() {
+        match def.effective_data_type() {
             Some(DataType::Array) => CodegenEmitter(self).emit_array(def, ctx),
             Some(DataType::Object) => CodegenEmitter(self).emit_object(def, ctx),
             Some(DataType::File) => Ok(EmittedUnit::Known(FILE_MARKER.into())),
@@ -488,7 +520,15 @@ where
         ctx: DefinitionContext<'_>,
     ) -> Result<Option<EmittedUnit>, Error> {
         // FIXME: Research on how we can support complex enums.
-        if def.data_type().is_some() && matching_unit_type(def.format(), def.data_type()).is_none()
+        let state = self.state();
+        if def.data_type().is_some()
+            && matching_unit_type(
+                def.format(),
+                def.data_type(),
+                state.format_types,
+                &state.normalized_mod_prefix(),
+            )
+            .is_none()
         {
             return Ok(None);
         }
@@ -545,6 +585,10 @@ where
             return self.emit_known_object_path(def, ctx);
         }
 
+        if def.discriminator().is_some() && !def.subtypes().is_empty() {
+            return self.emit_tagged_enum(def, ctx);
+        }
+
         self.emit_struct(def, ctx)
     }
 
@@ -624,6 +668,17 @@ where
             // anonymous object
             self.def_anon_name(def, &ctx.parents).ok_or_else(|| e)
         })?;
+        self.emit_struct_named(def, ctx, name)
+    }
+
+    /// Helper for `emit_struct` - This returns the Rust struct (along with the
+    /// anonymous objects it needs) for the given definition using the given name.
+    fn emit_struct_named(
+        &self,
+        def: &E::Definition,
+        ctx: DefinitionContext<'_>,
+        name: String,
+    ) -> Result<EmittedUnit, Error> {
         let mut obj = ApiObject::with_name(&name);
         obj.description = def.description().map(String::from);
 
@@ -638,8 +693,13 @@ where
         // Anonymous objects that we've collected along the way.
         let mut objects = vec![];
 
-        if let Some(props) = def.properties() {
-            props
+        // Properties from `allOf` schemas are flattened into this struct.
+        let mut composed = ComposedProperties::default();
+        composed.collect(def, &mut vec![]);
+
+        if !composed.props.is_empty() {
+            composed
+                .props
                 .iter()
                 .try_for_each(|(name, prop)| -> Result<(), Error> {
                     let schema = prop.read();
@@ -651,13 +711,12 @@ where
                         name: name.clone(),
                         description: prop.get_description(),
                         ty_path,
-                        is_required: def
-                            .required_properties()
-                            .map(|s| s.contains(name))
-                            .unwrap_or(false),
-                        needs_any: schema.contains_any(),
+                        is_required: composed.required.contains(name),
+                        needs_any: !self.state().refers_rust_type(&*schema)
+                            && schema.contains_any(),
                         boxed: schema.is_cyclic(),
                         child_req_fields: self.children_requirements(&schema),
+                        constraints: field_constraints(&*schema),
                     });
 
                     if let EmittedUnit::KnownButAnonymous(_, mut o) = ty {
@@ -669,7 +728,7 @@ where
 
             // If additional properties are enabled, then collect them into
             // a separate field for flattening.
-            if let Some(Either::Left(true)) = def.additional_properties() {
+            if composed.extra_props {
                 obj.fields_mut().push(ObjectField {
                     name: EXTRA_PROPS_FIELD.into(),
                     ty_path: "std::collections::BTreeMap<String, Any>".into(),
@@ -678,6 +737,7 @@ where
                     needs_any: true,
                     boxed: false,
                     child_req_fields: vec![],
+                    constraints: Default::default(),
                 });
             }
         }
@@ -686,17 +746,114 @@ where
         Ok(EmittedUnit::Objects(objects))
     }
 
+    /// Helper for `emit_object` - This returns the Rust enum definition for the
+    /// given polymorphic schema definition, with variants wrapping its subtypes.
+    ///
+    /// If the base definition can be instantiated by itself (i.e., the discriminator
+    /// doesn't exclude its name through `enum`), then the first variant wraps a
+    /// `{Name}Base` struct containing its own properties (which is also used for
+    /// the `Default` impl). Otherwise, the first subtype is used for `Default`.
+    fn emit_tagged_enum(
+        &self,
+        def: &E::Definition,
+        ctx: DefinitionContext<'_>,
+    ) -> Result<EmittedUnit, Error> {
+        let name = self.def_name(def).or_else(|e| {
+            // anonymous object
+            self.def_anon_name(def, &ctx.parents).ok_or_else(|| e)
+        })?;
+        let mut obj = ApiObject::with_name(&name);
+        obj.description = def.description().map(String::from);
+        let tag = def.discriminator().unwrap_or_default();
+
+        let mut objects = vec![];
+        let mut variants = vec![];
+        if let Some(base) = def
+            .name()
+            .filter(|n| self.is_instantiable_base(def, tag, n))
+        {
+            let base_name = format!("{}Base", name);
+            let ctx = if ctx.parents.is_empty() {
+                ctx.add_parent(&name)
+            } else {
+                ctx
+            };
+
+            let ty_path = format!(
+                "{}Base",
+                self.emit_known_object_path(def, ctx.clone().define(false))?
+                    .known_type()
+            );
+            if let EmittedUnit::Objects(mut o) =
+                self.emit_struct_named(def, ctx, base_name.clone())?
+            {
+                // The tag is set by serde while (de)serializing the enum.
+                o[0].fields_mut().retain(|f| f.name != tag);
+                objects.append(&mut o);
+            }
+
+            variants.push(TaggedVariant {
+                name: name.clone(),
+                tag_value: base.into(),
+                ty_path,
+                needs_any: !self.state().refers_rust_type(def) && def.contains_any(),
+                boxed: false,
+            });
+        }
+
+        for sub in def.subtypes() {
+            let schema = sub.read();
+            let ty = self.build_def(&schema, DefinitionContext::default())?;
+            variants.push(TaggedVariant {
+                name: self.def_name(&schema)?,
+                // Subtypes are always (named) definitions.
+                tag_value: schema.name().unwrap_or_default().into(),
+                ty_path: ty.known_type(),
+                needs_any: !self.state().refers_rust_type(&*schema) && schema.contains_any(),
+                boxed: schema.is_cyclic(),
+            });
+        }
+
+        obj.inner = ObjectContainer::TaggedEnum {
+            tag: tag.into(),
+            variants,
+        };
+
+        objects.insert(0, obj);
+        Ok(EmittedUnit::Objects(objects))
+    }
+
+    /// Checks whether a payload tagged with the name of the given polymorphic
+    /// definition is allowed by its discriminator property.
+    fn is_instantiable_base(&self, def: &E::Definition, tag: &str, name: &str) -> bool {
+        let prop = match def.properties().and_then(|p| p.get(tag)) {
+            Some(p) => p.read(),
+            None => return true,
+        };
+
+        prop.enum_variants()
+            .map(|vals| vals.iter().any(|v| v.as_str() == Some(name)))
+            .unwrap_or(true)
+    }
+
     /// Returns the requirements of the "deepest" child type in the given definition.
     ///
     /// See `ObjectField.children_req` field for what it means.
     fn children_requirements(&self, schema: &E::Definition) -> Vec<String> {
-        match schema.data_type() {
+        // We don't have builders for existing Rust types.
+        if self.state().rust_type(schema).is_some() {
+            return vec![];
+        }
+
+        match schema.effective_data_type() {
             Some(DataType::Object) => {
                 if let Some(Either::Right(s)) = schema.additional_properties() {
                     return self.children_requirements(&s.read());
-                } else if let Some(s) = schema.required_properties() {
-                    return s.iter().cloned().collect();
                 }
+
+                let mut composed = ComposedProperties::default();
+                composed.collect(schema, &mut vec![]);
+                return composed.required.into_iter().collect();
             }
             Some(DataType::Array) => {
                 if let Some(s) = schema.items() {
@@ -710,9 +867,67 @@ where
     }
 }
 
+/// Properties of an object schema, along with those of the schemas
+/// it's composed of (using `allOf`).
+struct ComposedProperties<S> {
+    props: BTreeMap<String, Resolvable<S>>,
+    required: BTreeSet<String>,
+    extra_props: bool,
+}
+
+impl<S> Default for ComposedProperties<S> {
+    fn default() -> Self {
+        ComposedProperties {
+            props: BTreeMap::new(),
+            required: BTreeSet::new(),
+            extra_props: false,
+        }
+    }
+}
+
+impl<S: Schema> ComposedProperties<S> {
+    /// Collects the properties of the given schema and (recursively) its
+    /// `allOf` schemas. Properties in the schema override those from `allOf`.
+    fn collect(&mut self, def: &S, visited: &mut Vec<String>) {
+        if let Some(name) = def.name() {
+            if visited.iter().any(|n| n == name) {
+                return;
+            }
+
+            visited.push(name.into());
+        }
+
+        for schema in def.all_of().into_iter().flatten() {
+            let schema = schema.read();
+            self.collect(&*schema, visited);
+            // The discriminator of a polymorphic parent is used for tagging
+            // its enum variants, so we don't need it in the subtypes.
+            if let Some(d) = schema.discriminator() {
+                self.props.remove(d);
+                self.required.remove(d);
+            }
+        }
+
+        if let Some(props) = def.properties() {
+            self.props
+                .extend(props.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+
+        if let Some(req) = def.required_properties() {
+            self.required.extend(req.iter().cloned());
+        }
+
+        if let Some(Either::Left(true)) = def.additional_properties() {
+            self.extra_props = true;
+        }
+    }
+}
+
 /// Abstraction which takes care of adding requirements for operations.
 struct RequirementCollector<'a, E: Emitter> {
     path: &'a str,
+    /// JSON pointer to this path in spec (for reporting errors).
+    pointer: String,
     emitter: &'a E,
     api: &'a ResolvableApi<E::Definition>,
     map: &'a ResolvablePathItem<E::Definition>,
@@ -727,17 +942,25 @@ where
     /// Given a path and an operation map, collect the stuff required
     /// for generating builders later.
     fn collect(mut self) -> Result<(), Error> {
-        self.validate_path_and_add_params()?;
+        let state = self.emitter.state();
+        if let Err(e) = self.validate_path_and_add_params() {
+            state.report(&self.pointer, e.into())?;
+        }
+
         debug!("Collecting builder requirement for {:?}", self.path);
 
         // Collect all the parameters local to some API call.
-        let (unused_params, _) = self.collect_parameters(&self.map.parameters)?;
+        let pointer = pointer_child(&self.pointer, "parameters");
+        let (unused_params, _) = self.collect_parameters(&self.map.parameters, &pointer)?;
         // FIXME: What if a body is "required" globally (for all operations)?
         // This means, operations can override the body with some other schema
         // and we may need to map it to the appropriate builders.
 
         for (&meth, op) in &self.map.methods {
-            self.collect_from_operation(meth, op, &unused_params)?;
+            let pointer = pointer_child(&self.pointer, &meth.to_string().to_lowercase());
+            if let Err(e) = self.collect_from_operation(meth, op, &unused_params, &pointer) {
+                state.report(&pointer, e)?;
+            }
         }
 
         // FIXME: If none of the parameters (local to operation or global) specify
@@ -756,11 +979,9 @@ where
         }
 
         if !self.template_params.is_empty() {
-            return Err(PaperClipError::MissingParametersInPath(
-                self.path.into(),
-                self.template_params,
-            )
-            .into());
+            let err =
+                PaperClipError::MissingParametersInPath(self.path.into(), self.template_params);
+            state.report(&self.pointer, err.into())?;
         }
 
         Ok(())
@@ -772,14 +993,12 @@ where
     /// For example, `/api/{foo}` and `/api/{bar}` are the same, and we
     /// should reject it.
     fn validate_path_and_add_params(&mut self) -> Result<(), PaperClipError> {
-        let path_fmt = ResolvableApi::<()>::path_parameters_map(self.path, |p| {
-            self.template_params.insert(p.into());
-            ":".into()
-        });
+        let (path_fmt, params) = ResolvableApi::<()>::path_template(self.path);
+        self.template_params.extend(params);
 
         let state = self.emitter.state();
         let mut paths = state.rel_paths.borrow_mut();
-        let value_absent = paths.insert(path_fmt.clone().into());
+        let value_absent = paths.insert(path_fmt);
         if value_absent {
             Ok(())
         } else {
@@ -793,8 +1012,10 @@ where
         meth: HttpMethod,
         op: &ResolvableOperation<E::Definition>,
         unused_params: &[Parameter],
+        pointer: &str,
     ) -> Result<(), Error> {
-        let (mut params, schema_path) = self.collect_parameters(&op.parameters)?;
+        let pointer = pointer_child(pointer, "parameters");
+        let (mut params, schema_path) = self.collect_parameters(&op.parameters, &pointer)?;
         // If we have unused params which don't exist in the method-specific
         // params (which take higher precedence), then we can copy those inside.
         for global_param in unused_params {
@@ -807,10 +1028,12 @@ where
             }
         }
 
+        let has_body =
+            schema_path.is_some() || params.iter().any(|p| p.presence == ParameterIn::Body);
         params = params
             .into_iter()
             .filter(|p| {
-                let skip = p.presence == ParameterIn::FormData && schema_path.is_some();
+                let skip = p.presence == ParameterIn::FormData && has_body;
                 if skip {
                     warn!(
                         "Skipping form data parameter {:?} in path {:?} because \
@@ -833,11 +1056,49 @@ where
         Ok(())
     }
 
+    /// Returns the security requirements for the given operation (or the global
+    /// requirements if the operation doesn't specify any). Each requirement is a
+    /// list of scheme names, and any one of them should be satisfied.
+    fn security_requirements(&self, op: &ResolvableOperation<E::Definition>) -> Vec<Vec<String>> {
+        let state = self.emitter.state();
+        let requirements: Vec<Vec<String>> = match op.security.as_ref() {
+            // Empty requirements override the global requirements.
+            Some(s) => s.iter().map(|r| r.keys().cloned().collect()).collect(),
+            None => {
+                let iter = self.api.security.iter();
+                iter.map(|r| r.keys().cloned().collect()).collect()
+            }
+        };
+
+        let requirements = requirements
+            .into_iter()
+            .filter(|r: &Vec<String>| {
+                let supported = r.iter().all(|s| state.has_security_scheme(s));
+                if !supported {
+                    warn!(
+                        "Skipping security requirement {:?} for path {:?} (unknown or unsupported schemes).",
+                        r, self.path
+                    );
+                }
+
+                supported
+            })
+            .collect::<Vec<_>>();
+
+        // Anonymous access doesn't need any credentials.
+        if requirements.iter().all(Vec::is_empty) {
+            return vec![];
+        }
+
+        requirements
+    }
+
     /// Collects headers as parameters for all responses in some operation.
     fn collect_response_headers(
         &self,
         responses: &BTreeMap<String, Either<Reference, ResolvableResponse<E::Definition>>>,
     ) -> Vec<Parameter> {
+        let state = self.emitter.state();
         let mut map = HeaderMap::<Parameter>::with_capacity(2);
         for resp in responses.values() {
             let r = resp.read();
@@ -855,6 +1116,11 @@ where
                     info.data_type,
                     info.format.as_ref(),
                     info.items.as_ref(),
+                    FormatTypes {
+                        binary: false,
+                        ..state.format_types
+                    },
+                    &state.normalized_mod_prefix(),
                 ) {
                     Some(t) => t,
                     None => {
@@ -893,15 +1159,35 @@ where
     fn collect_parameters(
         &mut self,
         obj_params: &[Either<Reference, ResolvableParameter<E::Definition>>],
+        pointer: &str,
     ) -> Result<(Vec<Parameter>, Option<PathBuf>), Error> {
-        let def_mods = self.emitter.state().def_mods.borrow();
+        let state = self.emitter.state();
+        let def_mods = state.def_mods.borrow();
         let mut schema_path = None;
         let mut params = vec![];
-        for param in obj_params {
+        for (i, param) in obj_params.iter().enumerate() {
             let p = param.read();
-            p.check(self.path)?; // validate the parameter
+            // Validate the parameter (and skip it if we're collecting errors).
+            if let Err(e) = p.check(self.path) {
+                state.report(&pointer_child(pointer, &i.to_string()), e.into())?;
+                continue;
+            }
 
             if let Some(def) = p.schema.as_ref() {
+                // Operations can't be bound to existing Rust types, so the
+                // body becomes a parameter of that type.
+                if let Some(ty) = state.rust_type(&*def.read()) {
+                    params.push(Parameter {
+                        name: p.name.clone(),
+                        description: p.description.clone(),
+                        ty_path: ty,
+                        presence: ParameterIn::Body,
+                        required: p.required,
+                        delimiting: vec![],
+                    });
+                    continue;
+                }
+
                 // If a schema exists, then get its path for later use.
                 let pat = self.emitter.def_mod_path(&*def.read())?;
                 if def_mods.get(&pat).is_some() {
@@ -923,17 +1209,27 @@ where
             }
 
             // Enforce that the parameter is an allowed type and collect it.
-            let (ty, mut it_fmts) =
-                match resolve_parameter_type(p.data_type, p.format.as_ref(), p.items.as_ref()) {
-                    Some(t) => t,
-                    None => {
-                        warn!(
-                            "Skipping parameter {:?} with unknown type {:?} in path {:?}",
-                            p.name, p.data_type, self.path
-                        );
-                        continue;
-                    }
-                };
+            // Binary values can only be streamed from files in form data.
+            let formats = FormatTypes {
+                binary: state.format_types.binary && p.in_ == ParameterIn::FormData,
+                ..state.format_types
+            };
+            let (ty, mut it_fmts) = match resolve_parameter_type(
+                p.data_type,
+                p.format.as_ref(),
+                p.items.as_ref(),
+                formats,
+                &state.normalized_mod_prefix(),
+            ) {
+                Some(t) => t,
+                None => {
+                    warn!(
+                        "Skipping parameter {:?} with unknown type {:?} in path {:?}",
+                        p.name, p.data_type, self.path
+                    );
+                    continue;
+                }
+            };
 
             validate_collection_format(
                 &p.name,
@@ -983,7 +1279,7 @@ where
         let mut response_contains_any = false;
         let response_ty_path = if let Some(s) = Self::get_2xx_response_schema(&op) {
             let schema = &*s.read();
-            response_contains_any = schema.contains_any();
+            response_contains_any = !state.refers_rust_type(schema) && schema.contains_any();
             Some(
                 self.emitter
                     .build_def(schema, DefinitionContext::default())?
@@ -998,6 +1294,7 @@ where
             OpRequirement {
                 listable: false,
                 id: op.operation_id.clone(),
+                tags: op.tags.clone(),
                 description: op.description.clone(),
                 deprecated: op.deprecated,
                 params,
@@ -1009,6 +1306,7 @@ where
                 body_required: true,
                 encoding: self.get_coder(op.consumes.as_ref(), &self.api.consumes),
                 decoding: self.get_coder(op.produces.as_ref(), &self.api.produces),
+                security: self.security_requirements(op),
             },
         );
 
@@ -1037,12 +1335,19 @@ where
 
         let schema = &*s.read();
         let state = self.emitter.state();
-        let listable = schema.items().and_then(|s| s.read().data_type()) == Some(DataType::Object);
+        // Existing Rust types (or arrays of them) don't have objects for binding.
+        let is_existing_type = state.rust_type(schema).is_some()
+            || schema
+                .items()
+                .map(|s| state.rust_type(&*s.read()).is_some())
+                .unwrap_or(false);
+        let listable = !is_existing_type
+            && schema.items().and_then(|s| s.read().data_type()) == Some(DataType::Object);
 
         let mut unknown_schema_context = None;
-        let s = match schema.data_type() {
+        let s = match schema.effective_data_type() {
             // We can deal with object responses.
-            Some(DataType::Object) => s.clone(),
+            Some(DataType::Object) if !is_existing_type => s.clone(),
             // We can also deal with array of objects by mapping
             // the operation to that object.
             _ if listable => Clone::clone(&**schema.items().unwrap()),
@@ -1103,6 +1408,7 @@ where
             meth,
             OpRequirement {
                 id: op.operation_id.clone(),
+                tags: op.tags.clone(),
                 description: op.description.clone(),
                 deprecated: op.deprecated,
                 params,
@@ -1110,11 +1416,12 @@ where
                 listable,
                 response: Response {
                     ty_path: response_ty_path,
-                    contains_any: schema.contains_any(),
+                    contains_any: !is_existing_type && schema.contains_any(),
                     headers: self.collect_response_headers(&op.responses),
                 },
                 encoding: self.get_coder(op.consumes.as_ref(), &self.api.consumes),
                 decoding: self.get_coder(op.produces.as_ref(), &self.api.produces),
+                security: self.security_requirements(op),
             },
         );
 
@@ -1173,15 +1480,40 @@ fn resolve_parameter_type(
     dt: Option<DataType>,
     dt_fmt: Option<&DataTypeFormat>,
     items: Option<&Items>,
+    formats: FormatTypes,
+    prefix: &str,
 ) -> Option<(String, Vec<CollectionFormat>)> {
-    match matching_unit_type(dt_fmt, dt) {
+    // Parameters use a wrapper for writing date-time values as RFC 3339 strings.
+    if dt == Some(DataType::String)
+        && dt_fmt == Some(&DataTypeFormat::DateTime)
+        && formats.date_time
+    {
+        return Some((String::from(prefix) + "util::DateTimeParam", vec![]));
+    }
+
+    // Bytes are sent as they are (i.e., base64-encoded strings) in parameters.
+    let formats = FormatTypes {
+        byte: false,
+        ..formats
+    };
+
+    match matching_unit_type(dt_fmt, dt, formats, prefix) {
         Some(t) => return Some((t.into(), vec![])),
         None if dt == Some(DataType::File) => return Some((FILE_MARKER.into(), vec![])),
         None if dt == Some(DataType::Array) => {
             if let Some(i) = items {
-                if let Some((ty, mut fmts)) =
-                    resolve_parameter_type(i.data_type, i.format.as_ref(), i.items.as_deref())
-                {
+                // Items can't be files.
+                let formats = FormatTypes {
+                    binary: false,
+                    ..formats
+                };
+                if let Some((ty, mut fmts)) = resolve_parameter_type(
+                    i.data_type,
+                    i.format.as_ref(),
+                    i.items.as_deref(),
+                    formats,
+                    prefix,
+                ) {
                     fmts.insert(0, i.collection_format.unwrap_or_default());
                     // We collect it as `Vec` for now - we'll replace it with our
                     // `Delimited` wrapper when we actually write the code.
@@ -1196,25 +1528,61 @@ fn resolve_parameter_type(
 }
 
 /// Checks if the given type/format matches a known Rust type and returns it.
+/// String formats are mapped to their types only if they've been enabled.
 fn matching_unit_type(
     format: Option<&DataTypeFormat>,
     type_: Option<DataType>,
-) -> Option<&'static str> {
+    formats: FormatTypes,
+    prefix: &str,
+) -> Option<Cow<'static, str>> {
+    if type_ == Some(DataType::String) {
+        match format {
+            Some(DataTypeFormat::Date) if formats.date => {
+                return Some((String::from(prefix) + "util::Date").into())
+            }
+            Some(DataTypeFormat::DateTime) if formats.date_time => {
+                return Some((String::from(prefix) + "util::DateTime").into())
+            }
+            Some(DataTypeFormat::Uuid) if formats.uuid => {
+                return Some((String::from(prefix) + "util::Uuid").into())
+            }
+            Some(DataTypeFormat::Byte) if formats.byte => {
+                return Some((String::from(prefix) + "util::Bytes").into())
+            }
+            Some(DataTypeFormat::Binary) if formats.binary => return Some(FILE_MARKER.into()),
+            _ => (),
+        }
+    }
+
     match format {
-        Some(DataTypeFormat::Int32) => Some("i32"),
-        Some(DataTypeFormat::Int64) => Some("i64"),
-        Some(DataTypeFormat::Float) => Some("f32"),
-        Some(DataTypeFormat::Double) => Some("f64"),
+        Some(DataTypeFormat::Int32) => Some("i32".into()),
+        Some(DataTypeFormat::Int64) => Some("i64".into()),
+        Some(DataTypeFormat::Float) => Some("f32".into()),
+        Some(DataTypeFormat::Double) => Some("f64".into()),
         _ => match type_ {
-            Some(DataType::Integer) => Some("i64"),
-            Some(DataType::Number) => Some("f64"),
-            Some(DataType::Boolean) => Some("bool"),
-            Some(DataType::String) => Some("String"),
+            Some(DataType::Integer) => Some("i64".into()),
+            Some(DataType::Number) => Some("f64".into()),
+            Some(DataType::Boolean) => Some("bool".into()),
+            Some(DataType::String) => Some("String".into()),
             _ => None,
         },
     }
 }
 
+/// Collects the validation constraints from the given schema.
+fn field_constraints<S: Schema>(schema: &S) -> FieldConstraints {
+    FieldConstraints {
+        min_length: schema.min_length(),
+        max_length: schema.max_length(),
+        pattern: schema.pattern().map(String::from),
+        minimum: schema.minimum().map(|m| (m, schema.exclusive_minimum())),
+        maximum: schema.maximum().map(|m| (m, schema.exclusive_maximum())),
+        multiple_of: schema.multiple_of(),
+        min_items: schema.min_items(),
+        max_items: schema.max_items(),
+    }
+}
+
 /// If the parameter is an array, then validate the collection formats and
 /// default if needed.
 fn validate_collection_format(
@@ -1232,43 +1600,48 @@ fn validate_collection_format(
     it_fmts.insert(0, collection_format.unwrap_or(default_fmt));
     it_fmts.pop(); // pop the final format, as it's unnecessary.
     let is_url_encoded = in_ == ParameterIn::Query || in_ == ParameterIn::FormData;
-    if it_fmts.contains(&CollectionFormat::Multi) {
-        let needs_override = if is_url_encoded {
-            let mut fmt_idx_iter = it_fmts
-                .iter()
-                .enumerate()
-                .filter(|&(_, &fmt)| fmt == CollectionFormat::Multi);
-            fmt_idx_iter.next().expect("expected collection format?");
-            // We support URL encoding multiple values only when it's specified in root.
-            fmt_idx_iter.next().is_some()
-        } else {
-            true
-        };
-
-        if needs_override {
-            if is_url_encoded {
-                info!(
-                    "Parameter {:?} in {:?} doesn't allow multiple instances in nested arrays. \
-                            Replacing with default ({:?}).",
-                    name, in_, default_fmt
-                );
-            } else {
-                info!(
-                    "Parameter {:?} is in {:?}, which doesn't allow array values as multiple \
-                        instances. Replacing with default ({:?}).",
-                    name, in_, default_fmt
-                );
-            }
+    // We support URL encoding multiple values only when it's specified in root.
+    if CollectionFormat::is_valid_nesting(in_, it_fmts) {
+        return;
+    }
 
-            for (i, f) in it_fmts.iter_mut().enumerate() {
-                if *f == CollectionFormat::Multi {
-                    if i == 0 && is_url_encoded {
-                        continue;
-                    }
+    if is_url_encoded {
+        info!(
+            "Parameter {:?} in {:?} doesn't allow multiple instances in nested arrays. \
+                    Replacing with default ({:?}).",
+            name, in_, default_fmt
+        );
+    } else {
+        info!(
+            "Parameter {:?} is in {:?}, which doesn't allow array values as multiple \
+                instances. Replacing with default ({:?}).",
+            name, in_, default_fmt
+        );
+    }
 
-                    *f = default_fmt;
-                }
+    for (i, f) in it_fmts.iter_mut().enumerate() {
+        if *f == CollectionFormat::Multi {
+            if i == 0 && is_url_encoded {
+                continue;
             }
+
+            *f = default_fmt;
         }
     }
 }
+
+/// Returns the [CamelCase](https://docs.rs/heck/*/heck/trait.CamelCase.html)
+/// name for an anonymous definition based on its parent names (if any).
+pub(crate) fn anon_name(parents: &[&str]) -> Option<String> {
+    let mut name = String::new();
+    parents.iter().for_each(|s| {
+        name.push_str(s);
+        name.push_str("_");
+    });
+
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_camel_case())
+    }
+}
@@ -1,7 +1,11 @@
 use super::emitter::ANY_GENERIC_PARAMETER;
-use super::object::{ApiObject, ApiObjectBuilder, Response, StructField, TypeParameters};
+use super::object::{
+    ApiObject, ApiObjectBuilder, FieldConstraints, ObjectContainer, ObjectField, Property,
+    Response, StructField, TypeParameters,
+};
 use super::RUST_KEYWORDS;
 use crate::v2::models::{CollectionFormat, ParameterIn, JSON_CODER, JSON_MIME};
+use crate::v2::pointer_child;
 use heck::{CamelCase, KebabCase, SnekCase};
 
 use std::fmt::{self, Display, Write};
@@ -11,6 +15,9 @@ use std::rc::Rc;
 /// Represents the API object impl.
 pub struct ApiObjectImpl<'a> {
     inner: &'a ApiObject,
+    helper_module_prefix: &'a str,
+    /// Whether the API client is blocking (synchronous).
+    blocking: bool,
     // NOTE: `Rc<[T]>` because we shouldn't mutate the stuff later.
     pub(super) builders: Rc<[ApiObjectBuilder<'a>]>,
 }
@@ -25,9 +32,21 @@ impl ApiObject {
     // FIXME: Make operations generic across builders. This will reduce the
     // number of structs generated.
     pub fn impl_repr<'a>(&'a self, helper_module_prefix: &'a str) -> ApiObjectImpl<'a> {
+        self.impl_repr_for_client(helper_module_prefix, false)
+    }
+
+    /// Same as `impl_repr`, but the operations are sent using a blocking
+    /// API client (if specified).
+    pub(super) fn impl_repr_for_client<'a>(
+        &'a self,
+        helper_module_prefix: &'a str,
+        blocking: bool,
+    ) -> ApiObjectImpl<'a> {
         if self.inner.is_enum() {
             return ApiObjectImpl {
                 inner: self,
+                helper_module_prefix,
+                blocking,
                 builders: vec![].into(),
             };
         }
@@ -65,6 +84,7 @@ impl ApiObject {
                         description: req.description.as_deref(),
                         object: &self.name,
                         op_id: req.id.as_deref(),
+                        tags: &req.tags,
                         deprecated: req.deprecated,
                         method: Some(method),
                         body_required: req.body_required,
@@ -74,6 +94,8 @@ impl ApiObject {
                         global_params: &path_ops.params,
                         local_params: &req.params,
                         needs_any: needs_any && req.body_required,
+                        blocking,
+                        security: &req.security,
                         response: Response {
                             ty_path: req.response.ty_path.as_deref(),
                             contains_any: req.response.contains_any,
@@ -84,6 +106,8 @@ impl ApiObject {
 
         ApiObjectImpl {
             inner: self,
+            helper_module_prefix,
+            blocking,
             builders: iter::once(if main_builder.fields.is_empty() {
                 None
             } else {
@@ -132,6 +156,25 @@ impl<'a> ApiObjectImpl<'a> {
             }
 
             iter.try_for_each(|field| {
+                // Bodies with existing Rust types are read from the payload.
+                if field.param_loc == Some(ParameterIn::Body) {
+                    write!(
+                        f,
+                        "
+        - payload:
+            long: payload
+            help: \"Path to payload (type: {ty}) or pass '-' for stdin\"
+            takes_value: true",
+                        ty = field.ty
+                    )?;
+
+                    if field.prop.is_required() {
+                        f.write_str("\n            required: true")?;
+                    }
+
+                    return Ok(());
+                }
+
                 let field_name = field.name.to_kebab_case();
                 write!(f, "\n        - {}:", &field_name)?;
                 f.write_str("\n            long: ")?;
@@ -164,11 +207,12 @@ impl<'a> ApiObjectImpl<'a> {
             f.write_str(&self.inner.path)?;
             f.write_str("::")?;
             builder.write_name(f)?;
-            f.write_str(
-                "::from_args(sub_matches)?;
-            builder.send_raw(client).await
-        },",
-            )
+            f.write_str("::from_args(sub_matches)?;\n            builder.send_raw(client)")?;
+            if !self.blocking {
+                f.write_str(".await")?;
+            }
+
+            f.write_str("\n        },")
         })
     }
 
@@ -322,6 +366,75 @@ impl<'a> ApiObjectImpl<'a> {
 
         f.write_str("body\n    }\n}\n")
     }
+
+    /// Writes the method (with the given name) for creating the given operation
+    /// builder bound to a client. This goes into the facade of some tag.
+    ///
+    /// `any_value` is used for the object's `Any` parameter when the builder
+    /// doesn't need it.
+    pub(super) fn write_tag_method<F>(
+        &self,
+        builder: &ApiObjectBuilder<'_>,
+        name: &str,
+        any_value: &str,
+        f: &mut F,
+    ) -> fmt::Result
+    where
+        F: Write,
+    {
+        let constructor = match builder.constructor_fn_name() {
+            Some(n) => n,
+            None => return Ok(()),
+        };
+
+        let prefix = self.helper_module_prefix;
+        if builder.description.is_none() {
+            f.write_str("\n")?;
+        }
+
+        ApiObject::write_docs(builder.description.as_ref(), f, 1)?;
+        // Deprecated operations get deprecated facade methods (which
+        // call the deprecated constructors).
+        if builder.deprecated {
+            f.write_str("    #[deprecated]\n    #[allow(deprecated)]\n")?;
+        }
+
+        f.write_str("    #[inline]\n    pub fn ")?;
+        f.write_str(name)?;
+        if builder.needs_any {
+            f.write_str("<")?;
+            f.write_str(ANY_GENERIC_PARAMETER)?;
+            f.write_str(": Default>")?;
+        }
+
+        write!(
+            f,
+            "(&self) -> {prefix}client::Bound<'a, Client, {prefix}{path}::",
+            prefix = prefix,
+            path = self.inner.path
+        )?;
+        builder.write_name(f)?;
+        builder.write_generics_if_necessary(f, None, TypeParameters::ReplaceAll)?;
+        write!(
+            f,
+            "> {{\n        {prefix}client::Bound::new(self.client, {prefix}{path}::{name}",
+            prefix = prefix,
+            path = self.inner.path,
+            name = self.inner.name
+        )?;
+
+        if self.inner.fields().iter().any(|f| f.needs_any) {
+            f.write_str("::<")?;
+            f.write_str(if builder.needs_any {
+                ANY_GENERIC_PARAMETER
+            } else {
+                any_value
+            })?;
+            f.write_str(">")?;
+        }
+
+        write!(f, "::{}())\n    }}\n", constructor)
+    }
 }
 
 /// Represents the API object builder impl.
@@ -402,6 +515,16 @@ where
 
             f.write_str("\n            param_")?;
             f.write_str(&sk)?;
+            if field.param_loc == Some(ParameterIn::Body) {
+                return writeln!(
+                    f,
+                    ": match matches.and_then(|m| m.value_of(\"payload\")) {{
+                    Some(_) => Some(crate::cli::read_from_input(matches)?),
+                    None => None,
+                }},"
+                );
+            }
+
             let mut ty = String::new();
             ApiObjectBuilder::write_wrapped_ty(
                 self.0.helper_module_prefix,
@@ -469,6 +592,9 @@ where
                 f.write_str("impl Iterator<Item = (String, ")?;
                 self.write_builder_ty(&ty[i + 9..ty.len() - 1], req, needs_any, f)?;
                 f.write_str(")>")?;
+            } else {
+                // Other generic types are existing Rust types.
+                write!(f, "impl Into<{}>", ty)?;
             }
         } else if ApiObject::is_simple_type(ty) {
             write!(f, "impl Into<{}", ty)?;
@@ -526,6 +652,8 @@ where
                 f.write_str("value.map(|(key, value)| (key, ")?;
                 Self::write_value_map(&ty[i + 9..ty.len() - 1], f)?;
                 f.write_str(")).collect::<std::collections::BTreeMap<_, _>>()")?;
+            } else {
+                f.write_str("value")?;
             }
         } else {
             f.write_str("value")?;
@@ -649,6 +777,8 @@ struct SendableCodegen<'a, 'b> {
     form: String,
     query: String,
     multi_value_query: Vec<String>,
+    /// Body parameter (for existing Rust types) and whether it's required.
+    body_param: Option<(String, bool)>,
 }
 
 impl<'a, 'b> From<&'a ApiObjectBuilder<'b>> for SendableCodegen<'a, 'b> {
@@ -662,6 +792,7 @@ impl<'a, 'b> From<&'a ApiObjectBuilder<'b>> for SendableCodegen<'a, 'b> {
             form: String::new(),
             query: String::new(),
             multi_value_query: vec![],
+            body_param: None,
         }
     }
 }
@@ -678,7 +809,7 @@ impl<'a, 'b> SendableCodegen<'a, 'b> {
         };
 
         f.write_str("\n")?;
-        if self.builder.response.is_file() {
+        if self.builder.response.is_file() && !self.builder.blocking {
             f.write_str("#[async_trait::async_trait]\n")?;
         }
 
@@ -710,6 +841,7 @@ impl<'a, 'b> SendableCodegen<'a, 'b> {
                 Some(ParameterIn::Header) => self.handle_header_param(field),
                 Some(ParameterIn::FormData) => self.handle_form_param(field),
                 Some(ParameterIn::Query) => self.handle_query_param(field),
+                Some(ParameterIn::Body) => self.handle_body_param(field),
                 _ => (),
             });
 
@@ -724,10 +856,12 @@ impl<'a, 'b> SendableCodegen<'a, 'b> {
 
         // Check whether `modify` method needs to be overridden (i.e. body and other params).
         if self.builder.body_required
+            || self.body_param.is_some()
             || !self.form.is_empty()
             || !self.query.is_empty()
             || !self.multi_value_query.is_empty()
             || !self.headers.is_empty()
+            || !self.builder.security.is_empty()
         {
             self.write_modify_method(f, accepted_range)?;
         }
@@ -860,6 +994,23 @@ impl<'a, 'b> SendableCodegen<'a, 'b> {
         );
     }
 
+    /// Handle field for a body parameter (which has an existing Rust type).
+    fn handle_body_param(&mut self, field: StructField) {
+        let name = field.name.to_snek_case();
+        let mut param_ref = String::from("self.");
+        if self.needs_container {
+            param_ref.push_str("inner.");
+        }
+
+        let _ = write!(param_ref, "param_{}.as_ref()", name);
+        let is_required = field.prop.is_required();
+        if is_required {
+            let _ = write!(param_ref, ".expect(\"missing parameter {}?\")", name);
+        }
+
+        self.body_param = Some((param_ref, is_required));
+    }
+
     /// Handle field for a header parameter.
     fn handle_header_param(&mut self, field: StructField) {
         let is_required = field.prop.is_required();
@@ -1000,9 +1151,37 @@ impl<'a, 'b> SendableCodegen<'a, 'b> {
         f.write_str("\n\n    fn modify(&self, req: Client::Request) -> Result<Client::Request, ")?;
         f.write_str(&self.builder.helper_module_prefix)?;
         f.write_str("client::ApiError<Client::Response>> {")?;
-        f.write_str("\n        use ")?;
-        f.write_str(&self.builder.helper_module_prefix)?;
-        f.write_str("client::Request;")?;
+
+        // Authorization alone doesn't need the trait in scope.
+        let uses_request = !self.headers.is_empty()
+            || self.builder.body_required
+            || self.body_param.is_some()
+            || accepted_range.is_some()
+            || !self.form.is_empty()
+            || !self.query.is_empty()
+            || !self.multi_value_query.is_empty();
+        if uses_request {
+            f.write_str("\n        use ")?;
+            f.write_str(&self.builder.helper_module_prefix)?;
+            f.write_str("client::Request;")?;
+        }
+
+        if !self.builder.security.is_empty() {
+            write!(
+                f,
+                "\n        let req = {}client::security::authorize(req, &[",
+                self.builder.helper_module_prefix
+            )?;
+            for (i, schemes) in self.builder.security.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+
+                write!(f, "&{:?}", schemes)?;
+            }
+
+            f.write_str("]);")?;
+        }
 
         if !self.headers.is_empty() {
             f.write_str("\n        let mut req = req;")?;
@@ -1010,37 +1189,28 @@ impl<'a, 'b> SendableCodegen<'a, 'b> {
             f.write_str("\n")?;
         }
 
+        // Optional bodies are set only if they exist.
+        if let Some((param_ref, false)) = self.body_param.as_ref() {
+            write!(
+                f,
+                "\n        let req = match {} {{\n            Some(v) => req",
+                param_ref
+            )?;
+            self.write_body(f, "v", "            ")?;
+            f.write_str(",\n            None => req,\n        };\n")?;
+        }
+
         f.write_str("\n        Ok(req")?;
         if self.builder.body_required {
-            f.write_str("\n        ")?;
-            if let Some((range, coder)) = self.builder.encoding {
-                write!(
-                    f,
-                    ".header(http::header::CONTENT_TYPE.as_str(), {:?})",
-                    range
-                )?;
-
-                f.write_str(
-                    "\n        .body_bytes({
-            let mut vec = vec![];
-            ",
-                )?;
-                f.write_str(&coder.encoder_path)?;
-                f.write_str("(&mut vec, ")?;
-            } else {
-                f.write_str(".json(")?;
-            }
-
-            f.write_str("&self.")?;
+            let mut body_ref = String::from("&self.");
             if self.needs_container {
-                f.write_str("inner.")?;
+                body_ref.push_str("inner.");
             }
 
-            f.write_str("body)")?;
-
-            if self.builder.encoding.is_some() {
-                f.write_str("?;\n            vec\n        })")?;
-            }
+            body_ref.push_str("body");
+            self.write_body(f, &body_ref, "        ")?;
+        } else if let Some((param_ref, true)) = self.body_param.as_ref() {
+            self.write_body(f, param_ref, "        ")?;
         }
 
         if let Some(r) = accepted_range {
@@ -1096,11 +1266,64 @@ impl<'a, 'b> SendableCodegen<'a, 'b> {
         f.write_str(")\n    }")
     }
 
-    /// Writes async `send` method for this operation assuming that the response is a file.
+    /// Writes the method calls for encoding the given body value in the request.
+    fn write_body<F>(&self, f: &mut F, value: &str, indent: &str) -> fmt::Result
+    where
+        F: Write,
+    {
+        f.write_str("\n")?;
+        f.write_str(indent)?;
+        if let Some((range, coder)) = self.builder.encoding {
+            write!(
+                f,
+                ".header(http::header::CONTENT_TYPE.as_str(), {:?})",
+                range
+            )?;
+
+            write!(
+                f,
+                "\n{indent}.body_bytes({{
+{indent}    let mut vec = vec![];
+{indent}    ",
+                indent = indent
+            )?;
+            f.write_str(&coder.encoder_path)?;
+            f.write_str("(&mut vec, ")?;
+        } else {
+            f.write_str(".json(")?;
+        }
+
+        f.write_str(value)?;
+        f.write_str(")")?;
+
+        if self.builder.encoding.is_some() {
+            write!(f, "?;\n{indent}    vec\n{indent}}})", indent = indent)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes `send` method for this operation assuming that the response is a file.
     fn write_file_acceptor<F>(&self, f: &mut F) -> fmt::Result
     where
         F: Write,
     {
+        if self.builder.blocking {
+            return write!(
+                f,
+                "
+
+    fn send(&self, client: &Client) -> Result<{prefix}client::ResponseWrapper<Self::Output, Self>, {prefix}client::ApiError<Client::Response>> {{
+        use {prefix}client::Response;
+        let resp = self.send_raw(client)?;
+        Ok({prefix}client::ResponseWrapper::wrap(resp, |r| {{
+            Ok({prefix}util::ResponseStream(r.stream()))
+        }}).unwrap())
+    }}",
+                prefix = self.builder.helper_module_prefix
+            );
+        }
+
         write!(
             f,
             "
@@ -1117,6 +1340,283 @@ impl<'a, 'b> SendableCodegen<'a, 'b> {
     }
 }
 
+/// Rust types for numbers in the generated code.
+const NUMBER_TYPES: &[&str] = &["i32", "i64", "f32", "f64"];
+
+impl<'a> ApiObjectImpl<'a> {
+    /// Writes the `validate` method for this object and for its builders
+    /// (which have a body), for checking the constraints from the spec.
+    pub(super) fn write_validation<F>(&self, f: &mut F) -> fmt::Result
+    where
+        F: Write,
+    {
+        let prefix = self.helper_module_prefix;
+        let needs_any = match &self.inner.inner {
+            ObjectContainer::Struct { fields } => fields.iter().any(|f| f.needs_any),
+            ObjectContainer::TaggedEnum { variants, .. } => variants.iter().any(|v| v.needs_any),
+            ObjectContainer::Enum { .. } => false,
+        };
+
+        f.write_str("\nimpl")?;
+        if needs_any {
+            ApiObject::write_any_generic(f)?;
+        }
+
+        f.write_str(" ")?;
+        f.write_str(&self.inner.name)?;
+        if needs_any {
+            ApiObject::write_any_generic(f)?;
+        }
+
+        write!(
+            f,
+            " {{
+    /// Validates this object against the constraints in the spec.
+    pub fn validate(&self) -> Result<(), {prefix}util::ValidationErrors> {{
+        let mut errors = {prefix}util::ValidationErrors::default();
+        self.validate_at(\"\", &mut errors);
+        errors.into_result()
+    }}
+
+    #[doc(hidden)]
+    pub fn validate_at(&self, path: &str, errors: &mut {prefix}util::ValidationErrors) {{",
+            prefix = prefix
+        )?;
+
+        let mut body = String::new();
+        match &self.inner.inner {
+            ObjectContainer::Struct { fields } => fields
+                .iter()
+                .try_for_each(|field| Self::write_field_validation(field, prefix, &mut body))?,
+            ObjectContainer::TaggedEnum { variants, .. } if !variants.is_empty() => {
+                body.push_str("\n        match self {");
+                for var in variants {
+                    write!(
+                        body,
+                        "\n            {}::{}(value) => value.validate_at(path, errors),",
+                        self.inner.name, var.name
+                    )?;
+                }
+
+                body.push_str("\n        }");
+            }
+            _ => (),
+        }
+
+        if body.is_empty() {
+            f.write_str("\n        let _ = (path, errors);")?;
+        } else {
+            f.write_str(&body)?;
+        }
+
+        f.write_str("\n    }\n}\n")?;
+
+        for builder in self.builders.iter().filter(|b| b.body_required) {
+            let mut generics = String::new();
+            builder.write_generics_if_necessary(&mut generics, None, TypeParameters::Generic)?;
+            f.write_str("\nimpl")?;
+            f.write_str(&generics)?;
+            f.write_str(" ")?;
+            builder.write_name(f)?;
+            f.write_str(&generics)?;
+
+            // Required fields are tracked by the type parameters of the builder,
+            // so we can check whether they've been set.
+            let required = builder
+                .struct_fields_iter()
+                .filter(|f| f.prop == Property::RequiredField)
+                .map(|f| f.name)
+                .collect::<Vec<_>>();
+            if !required.is_empty() {
+                f.write_str("\nwhere")?;
+                for name in &required {
+                    write!(f, "\n    {}: 'static,", name.to_camel_case())?;
+                }
+
+                f.write_str("\n")?;
+            } else {
+                f.write_str(" ")?;
+            }
+
+            write!(
+                f,
+                "{{
+    /// Validates the object built so far against the constraints in the spec
+    /// (including the required fields which haven't been set yet).
+    pub fn validate(&self) -> Result<(), {prefix}util::ValidationErrors> {{
+        let mut errors = {prefix}util::ValidationErrors::default();",
+                prefix = prefix,
+            )?;
+
+            for name in &required {
+                let ty = name.to_camel_case();
+                write!(
+                    f,
+                    "\n        errors.check_required::<{ty}, {prefix}generics::Missing{ty}>({path:?});",
+                    ty = ty,
+                    prefix = prefix,
+                    path = pointer_child("", name),
+                )?;
+            }
+
+            write!(
+                f,
+                "
+        self.{container}body.validate_at(\"\", &mut errors);
+        errors.into_result()
+    }}
+}}
+",
+                container = if builder.needs_container() {
+                    "inner."
+                } else {
+                    ""
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the validation for the given field (if it's needed).
+    fn write_field_validation<F>(field: &ObjectField, prefix: &str, f: &mut F) -> fmt::Result
+    where
+        F: Write,
+    {
+        let mut checks = String::new();
+        Self::write_value_validation(
+            &field.ty_path,
+            Some(&field.constraints),
+            prefix,
+            3,
+            &mut checks,
+        )?;
+
+        if checks.is_empty() {
+            return Ok(());
+        }
+
+        let mut name = field.name.to_snek_case();
+        if RUST_KEYWORDS.iter().any(|&k| k == name) {
+            name.push('_');
+        }
+
+        if field.is_required {
+            write!(f, "\n        {{\n            let value = &self.{};", name)?;
+        } else {
+            write!(
+                f,
+                "\n        if let Some(value) = self.{}.as_ref() {{",
+                name
+            )?;
+        }
+
+        write!(
+            f,
+            "\n            let path = {}util::json_pointer(path, {:?});",
+            prefix, field.name
+        )?;
+        f.write_str(&checks)?;
+        f.write_str("\n        }")
+    }
+
+    /// Writes the checks for a value (bound to `value` and `path`) of the given type.
+    /// Constraints (if any) apply only to the outermost type, while the objects
+    /// nested in arrays and maps are validated recursively.
+    fn write_value_validation<F>(
+        ty: &str,
+        constraints: Option<&FieldConstraints>,
+        prefix: &str,
+        level: usize,
+        f: &mut F,
+    ) -> fmt::Result
+    where
+        F: Write,
+    {
+        let indent = " ".repeat(level * 4);
+        let default = FieldConstraints::default();
+        let c = constraints.unwrap_or(&default);
+
+        let (inner_ty, iterator) = if ty.starts_with("Vec<") {
+            (
+                Some(&ty[4..ty.len() - 1]),
+                "for (i, value) in value.iter().enumerate() {",
+            )
+        } else if ty.starts_with("std::collections::BTreeMap<String, ") {
+            (
+                Some(&ty[35..ty.len() - 1]),
+                "for (i, value) in value.iter() {",
+            )
+        } else {
+            (None, "")
+        };
+
+        if let Some(inner_ty) = inner_ty {
+            if ty.starts_with("Vec<") && (c.min_items.is_some() || c.max_items.is_some()) {
+                write!(
+                    f,
+                    "\n{}errors.check_items(&path, value.len(), {:?}, {:?});",
+                    indent,
+                    c.min_items.map(|v| v as usize),
+                    c.max_items.map(|v| v as usize)
+                )?;
+            }
+
+            let mut inner = String::new();
+            Self::write_value_validation(inner_ty, None, prefix, level + 1, &mut inner)?;
+            if !inner.is_empty() {
+                write!(
+                    f,
+                    "\n{indent}{iter}\n{indent}    let path = {prefix}util::json_pointer(&path, &i.to_string());{inner}\n{indent}}}",
+                    indent = indent,
+                    iter = iterator,
+                    prefix = prefix,
+                    inner = inner
+                )?;
+            }
+        } else if ty == "String" {
+            if c.min_length.is_some() || c.max_length.is_some() {
+                write!(
+                    f,
+                    "\n{}errors.check_length(&path, value.chars().count(), {:?}, {:?});",
+                    indent,
+                    c.min_length.map(|v| v as usize),
+                    c.max_length.map(|v| v as usize)
+                )?;
+            }
+
+            if let Some(p) = c.pattern.as_ref() {
+                write!(
+                    f,
+                    "\n{}errors.check_pattern(&path, value, {:?});",
+                    indent, p
+                )?;
+            }
+        } else if NUMBER_TYPES.contains(&ty) {
+            if c.minimum.is_some() || c.maximum.is_some() {
+                write!(
+                    f,
+                    "\n{}errors.check_range(&path, *value as f64, {:?}, {:?});",
+                    indent, c.minimum, c.maximum
+                )?;
+            }
+
+            if let Some(m) = c.multiple_of {
+                write!(
+                    f,
+                    "\n{}errors.check_multiple_of(&path, *value as f64, {:?});",
+                    indent, m
+                )?;
+            }
+        } else if ty.starts_with(prefix) && !ApiObject::is_simple_type(ty) {
+            // Only the objects generated by us can be validated.
+            write!(f, "\n{}value.validate_at(&path, errors);", indent)?;
+        }
+
+        Ok(())
+    }
+}
+
 impl<'a> Display for ApiObjectImpl<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if self.builders.is_empty() {
@@ -8,6 +8,7 @@ pub mod object;
 mod state;
 include!(concat!(env!("OUT_DIR"), "/template.rs"));
 
+pub(crate) use self::emitter::anon_name;
 pub use self::emitter::{EmittedUnit, Emitter};
 pub use self::state::EmitterState;
 
@@ -63,10 +64,72 @@ pub struct CrateMeta {
     pub authors: Option<Vec<String>>,
     /// Whether we're planning to emit a lib, app or module.
     pub mode: EmitMode,
+    /// Whether to emit a blocking (synchronous) API client (backed by
+    /// `reqwest::blocking`) instead of an async one. Unlike other fields,
+    /// this also applies to modules (i.e., without `"cli"` feature).
+    pub blocking: bool,
     // Marker to avoid potential breakage when more public fields come in.
     _marker: (),
 }
 
+/// String formats to be mapped onto dedicated Rust types (instead of `String`)
+/// during codegen. These are aliases in the generated `util` module, which
+/// resolve to the actual types only when the corresponding (optional)
+/// dependencies are enabled as features of the generated crate (they fall
+/// back to `String` otherwise).
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct FormatTypes {
+    /// `date` as `util::Date` (`chrono::NaiveDate` with `chrono` feature).
+    pub date: bool,
+    /// `date-time` as `util::DateTime` (`chrono::DateTime<chrono::Utc>` with
+    /// `chrono` feature).
+    pub date_time: bool,
+    /// `uuid` as `util::Uuid` (`uuid::Uuid` with `uuid` feature).
+    pub uuid: bool,
+    /// `byte` as `util::Bytes` (`Vec<u8>` (de)serialized as base64 strings
+    /// with `base64` feature).
+    pub byte: bool,
+    /// `binary` as a streaming body (like `file`) in responses and form data.
+    pub binary: bool,
+}
+
+impl FormatTypes {
+    /// Mapping for all the supported formats.
+    pub fn all() -> Self {
+        FormatTypes {
+            date: true,
+            date_time: true,
+            uuid: true,
+            byte: true,
+            binary: true,
+        }
+    }
+
+    /// Whether the generated code needs `chrono` crate.
+    #[cfg(feature = "cli")]
+    pub(crate) fn needs_chrono(&self) -> bool {
+        self.date || self.date_time
+    }
+}
+
+/// HTTP backends (other than `reqwest`) for which the API client is implemented
+/// in the generated code. Each backend is gated behind a feature of the same
+/// name (and `reqwest` becomes a default feature). These only apply to async clients.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct HttpBackends {
+    /// `hyper::Client` (with the responses as `hyper::Response<hyper::Body>`).
+    pub hyper: bool,
+    /// `client::AwcClient` backed by actix's `awc::Client`.
+    pub awc: bool,
+}
+
+impl HttpBackends {
+    /// Whether any of the backends have been enabled.
+    pub fn any(&self) -> bool {
+        self.hyper || self.awc
+    }
+}
+
 /// Mode of codegen (module, crate or CLI app).
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum EmitMode {
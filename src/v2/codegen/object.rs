@@ -22,6 +22,9 @@ lazy_static! {
     static ref DOC_REGEX: Regex = Regex::new(r"\[|\]").expect("invalid doc regex?");
 }
 
+/// Aliases in the generated `util` module for the types of string formats.
+const UTIL_FORMAT_TYPES: &[&str] = &["Date", "DateTime", "DateTimeParam", "Uuid", "Bytes"];
+
 /// Represents a (simplified) Rust struct or enum.
 #[derive(Default, Debug, Clone)]
 pub struct ApiObject {
@@ -92,13 +95,22 @@ pub enum ObjectContainer {
         /// Fields if it's a struct.
         fields: Vec<ObjectField>,
     },
+    TaggedEnum {
+        /// Name of the property used for tagging the variants
+        /// (i.e., the discriminator).
+        tag: String,
+        /// Variants wrapping the subtypes of the polymorphic object (preceded
+        /// by the variant for the base object, if it can be instantiated).
+        /// The first variant is used for the `Default` impl.
+        variants: Vec<TaggedVariant>,
+    },
 }
 
 impl ObjectContainer {
     /// Returns whether this object is an enum.
     pub fn is_enum(&self) -> bool {
         match self {
-            ObjectContainer::Enum { .. } => true,
+            ObjectContainer::Enum { .. } | ObjectContainer::TaggedEnum { .. } => true,
             _ => false,
         }
     }
@@ -129,6 +141,8 @@ pub struct OpRequirement {
     /// If there are multiple operations for the same path, then we
     /// attempt to use this.
     pub id: Option<String>,
+    /// Tags of this operation (used for grouping operations by tags).
+    pub tags: Vec<String>,
     /// Description of this operation (if any), to be used for docs.
     pub description: Option<String>,
     /// Whether the operation is deprecated or not.
@@ -149,6 +163,9 @@ pub struct OpRequirement {
     /// when objects make use of `Any` type. If there's no coder, then JSON
     /// encoding is assumed.
     pub decoding: Option<(String, Arc<Coder>)>,
+    /// Security requirements for this operation. Each requirement is a list
+    /// of security scheme names (and any one of them should be satisfied).
+    pub security: Vec<Vec<String>>,
 }
 
 #[derive(Default, Debug, Clone)]
@@ -203,6 +220,21 @@ pub struct ObjectVariant {
     pub value: serde_json::Value,
 }
 
+/// Represents a variant of a tagged enum, which wraps some (subtype) object.
+#[derive(Debug, Clone)]
+pub struct TaggedVariant {
+    /// Name of the variant (camel-cased).
+    pub name: String,
+    /// Value of the tag for this variant.
+    pub tag_value: String,
+    /// Type of the wrapped object as a path.
+    pub ty_path: String,
+    /// Whether the wrapped object "is" or "has" an `Any` type.
+    pub needs_any: bool,
+    /// Whether the wrapped object should be boxed.
+    pub boxed: bool,
+}
+
 /// Represents a struct field.
 #[derive(Debug, Clone)]
 pub struct ObjectField {
@@ -229,6 +261,40 @@ pub struct ObjectField {
     ///
     /// Yours sincerely.
     pub child_req_fields: Vec<String>,
+    /// Constraints (from the schema) on the value of this field.
+    pub constraints: FieldConstraints,
+}
+
+impl ObjectField {
+    /// Whether the `validate` method checks the value of this field against
+    /// a regex pattern.
+    pub(super) fn checks_pattern(&self) -> bool {
+        self.ty_path == "String" && self.constraints.pattern.is_some()
+    }
+}
+
+/// Constraints on the value of some field, used for generating validation.
+///
+/// Length and pattern constraints apply to strings, range constraints apply to
+/// numbers and item constraints apply to arrays.
+#[derive(Debug, Default, Clone)]
+pub struct FieldConstraints {
+    /// Minimum length of the string (if any).
+    pub min_length: Option<u32>,
+    /// Maximum length of the string (if any).
+    pub max_length: Option<u32>,
+    /// Regex pattern to be matched by the string (if any).
+    pub pattern: Option<String>,
+    /// Minimum value of the number (if any) and whether it's exclusive.
+    pub minimum: Option<(f32, bool)>,
+    /// Maximum value of the number (if any) and whether it's exclusive.
+    pub maximum: Option<(f32, bool)>,
+    /// The number should be a multiple of this value (if any).
+    pub multiple_of: Option<f32>,
+    /// Minimum number of items in the array (if any).
+    pub min_items: Option<u32>,
+    /// Maximum number of items in the array (if any).
+    pub max_items: Option<u32>,
 }
 
 impl ApiObject {
@@ -291,7 +357,11 @@ impl ApiObject {
     /// Returns whether this type is simple (i.e., not an object defined by us).
     #[inline]
     pub(super) fn is_simple_type(ty: &str) -> bool {
-        !ty.contains("::") || ty.ends_with("Delimited")
+        !ty.contains("::")
+            || ty.ends_with("Delimited")
+            || UTIL_FORMAT_TYPES
+                .iter()
+                .any(|t| ty.ends_with(&format!("util::{}", t)))
     }
 
     /// Assuming that the given type "is" or "has" `Any`, this adds
@@ -308,7 +378,8 @@ impl ApiObject {
                 f.write_str(&ty[..i + 9])?;
                 Self::write_field_with_any(&ty[i + 9..ty.len() - 1], f)?;
             } else {
-                unreachable!("no other generics expected.");
+                // Other generic types are existing Rust types.
+                return f.write_str(ty);
             }
 
             f.write_str(">")?;
@@ -337,6 +408,8 @@ pub(super) struct ApiObjectBuilder<'a> {
     pub helper_module_prefix: &'a str,
     /// Operation ID, if any.
     pub op_id: Option<&'a str>,
+    /// Tags of the operation, if any.
+    pub tags: &'a [String],
     /// Whether the operation is deprecated or not.
     pub deprecated: bool,
     /// HTTP method for the operation - all builders (other than object builders)
@@ -367,6 +440,10 @@ pub(super) struct ApiObjectBuilder<'a> {
     pub local_params: &'a [Parameter],
     /// Whether this builder is generic over `Any` type.
     pub needs_any: bool,
+    /// Whether the API client is blocking (synchronous).
+    pub blocking: bool,
+    /// Security requirements for the operation (if any).
+    pub security: &'a [Vec<String>],
 }
 
 /// The property we're dealing with.
@@ -929,6 +1006,15 @@ impl Display for ApiObject {
                     f.write_str("\")]\n    ")?;
                 }
 
+                if let Some(module) = base64_module(&field.ty_path) {
+                    f.write_str("#[serde(")?;
+                    if !field.is_required {
+                        f.write_str("default, ")?;
+                    }
+
+                    write!(f, "with = {:?})]\n    ", module)?;
+                }
+
                 f.write_str("pub ")?;
                 f.write_str(&new_name)?;
                 f.write_str(": ")?;
@@ -972,6 +1058,10 @@ impl ApiObject {
     where
         F: fmt::Write,
     {
+        if let ObjectContainer::TaggedEnum { tag, variants } = &self.inner {
+            return self.write_tagged_enum(tag, variants, f);
+        }
+
         let is_string = self.inner.is_string_enum();
         f.write_str("#[derive(Debug, Clone")?;
         if is_string {
@@ -1024,6 +1114,76 @@ impl ApiObject {
     }
 }
 
+impl ApiObject {
+    /// Writes an (internally) tagged enum declaration for polymorphic objects.
+    fn write_tagged_enum<F>(&self, tag: &str, variants: &[TaggedVariant], f: &mut F) -> fmt::Result
+    where
+        F: fmt::Write,
+    {
+        let needs_any = variants.iter().any(|v| v.needs_any);
+        f.write_str("#[derive(Debug, Clone, Serialize, Deserialize)]\n")?;
+        writeln!(f, "#[serde(tag = {:?})]", tag)?;
+        f.write_str("pub enum ")?;
+        f.write_str(&self.name)?;
+        if needs_any {
+            ApiObject::write_any_generic(f)?;
+        }
+
+        f.write_str(" {")?;
+        variants.iter().try_for_each(|var| {
+            write!(f, "\n    #[serde(rename = {:?})]", var.tag_value)?;
+            f.write_str("\n    ")?;
+            f.write_str(&var.name)?;
+            f.write_str("(")?;
+            if var.boxed {
+                f.write_str("Box<")?;
+            }
+
+            if var.needs_any {
+                Self::write_field_with_any(&var.ty_path, f)?;
+            } else {
+                f.write_str(&var.ty_path)?;
+            }
+
+            if var.boxed {
+                f.write_str(">")?;
+            }
+
+            f.write_str("),")
+        })?;
+
+        f.write_str("\n}\n")?;
+
+        if let Some(var) = variants.first() {
+            f.write_str("\nimpl")?;
+            if needs_any {
+                f.write_str("<")?;
+                f.write_str(ANY_GENERIC_PARAMETER)?;
+                f.write_str(": Default>")?;
+            }
+
+            f.write_str(" Default for ")?;
+            f.write_str(&self.name)?;
+            if needs_any {
+                ApiObject::write_any_generic(f)?;
+            }
+
+            writeln!(
+                f,
+                " {{
+    fn default() -> Self {{
+        {name}::{first_var}(Default::default())
+    }}
+}}",
+                name = &self.name,
+                first_var = &var.name
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
 /// Abstraction for implementing Serialize/Deserialize mechanism
 /// for non-string enums.
 struct EnumSerdeImpl<'a> {
@@ -1182,3 +1342,11 @@ impl Default for ObjectContainer {
         ObjectContainer::Struct { fields: vec![] }
     }
 }
+
+/// Returns the path to the `util::base64` module (for use with serde) if
+/// the given type has bytes (`byte` format).
+fn base64_module(ty: &str) -> Option<String> {
+    let idx = ty.find("util::Bytes")?;
+    let start = ty[..idx].rfind(&['<', ' '][..]).map(|i| i + 1).unwrap_or(0);
+    Some(format!("{}util::base64", &ty[start..idx]))
+}
@@ -1,25 +1,40 @@
+use super::object::{ApiObject, ApiObjectImpl, ObjectContainer};
 use super::template::{self, TEMPLATE};
-use super::{object::ApiObject, CrateMeta, EmitMode};
+use super::{CrateMeta, EmitMode, FormatTypes, HttpBackends, RUST_KEYWORDS};
 use crate::error::PaperClipError;
-use crate::v2::models::{Coders, SpecFormat};
+use crate::v2::filter::Filter;
+use crate::v2::models::{Coders, Either, HttpMethod, SecurityScheme, SpecFormat, Tag};
+use crate::v2::Schema;
 use anyhow::Error;
-use heck::CamelCase;
-#[cfg(feature = "cli")]
-use heck::SnekCase;
+use heck::{CamelCase, SnekCase};
 use itertools::Itertools;
+use paperclip_core::{Report, ValidationError};
 use url::Url;
 
 use std::cell::RefCell;
-use std::collections::{BTreeSet, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::fmt::Write as _;
-#[cfg(feature = "cli")]
-use std::fs;
-use std::fs::OpenOptions;
+use std::fs::{self, OpenOptions};
 use std::hash::{Hash, Hasher};
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
+/// Name of the module (in root) containing the modules for tags.
+const TAGS_MODULE: &str = "tags";
+
+/// Items in the generated `security` module, which can't be used for the
+/// credentials of security schemes.
+const SECURITY_MODULE_ITEMS: &[&str] = &[
+    "ApiClient",
+    "ApiError",
+    "Arc",
+    "Authenticated",
+    "AuthenticatedRequest",
+    "Credentials",
+    "Request",
+];
+
 /// Holds the state for your schema emitter.
 #[derive(Debug)]
 pub struct EmitterState {
@@ -29,6 +44,25 @@ pub struct EmitterState {
     pub ns_sep: &'static str,
     /// Module prefix for using in generated code.
     pub mod_prefix: &'static str,
+    /// Whether to collect all the errors in spec (and report them at the end)
+    /// instead of failing at the first error.
+    pub diagnostics: bool,
+    /// Filter for the operations to be generated. Definitions which aren't
+    /// used by the selected operations are left out.
+    pub filter: Filter,
+    /// Whether to group operations into per-tag modules (inside `tags` module),
+    /// each with a facade for calling its operations through the API client.
+    pub group_by_tags: bool,
+    /// Existing Rust types (paths) to be used for definitions (mapped by their
+    /// names) instead of generating them. These take precedence over the
+    /// `x-rust-type` extension in schema.
+    pub rust_types: HashMap<String, String>,
+    /// String formats to be mapped onto dedicated Rust types (`chrono::DateTime`,
+    /// `uuid::Uuid`, etc.) instead of `String`.
+    pub format_types: FormatTypes,
+    /// HTTP backends for which the API client should be implemented (apart
+    /// from `reqwest`), each gated behind a feature of the same name.
+    pub http_backends: HttpBackends,
 
     /* MARK: Private fields. */
     /// Base URL for the API.
@@ -36,9 +70,12 @@ pub struct EmitterState {
     /// Fallback encoding when we don't have a choice (obtained from `Api.spec_format`).
     default_encoding: RefCell<SpecFormat>,
     /// If crate metadata is specified, then `lib.rs` and `Cargo.toml` are generated
-    /// along with the modules. This is gated behind `"cli"` feature.
-    #[cfg(feature = "cli")]
+    /// along with the modules. This is gated behind `"cli"` feature (except for
+    /// `blocking`, which applies to modules as well).
     crate_meta: Rc<RefCell<Option<CrateMeta>>>,
+    /// Errors found while resolving the spec (in diagnostics mode), which are
+    /// reported along with the errors found during codegen.
+    resolver_report: RefCell<Report<Error>>,
 
     /* MARK: Internal fields that should be reset for each session. */
     /// Maps parent mod to immediate children. Used for declaring modules.
@@ -55,6 +92,13 @@ pub struct EmitterState {
     cli_yaml: RefCell<String>,
     /// Generated match arms for clap subcommands and matches.
     cli_match_arms: RefCell<String>,
+    /// Modules for grouping operations by tags (if enabled), mapped to the tags.
+    tag_mods: RefCell<BTreeMap<String, TagModule>>,
+    /// Supported security schemes (from `securityDefinitions`) for which
+    /// credentials are generated.
+    security_schemes: RefCell<Vec<SecuritySchemeContext>>,
+    /// Errors collected so far (in diagnostics mode).
+    report: RefCell<Report<Error>>,
 }
 
 /// Indicates a child module in codegen working directory.
@@ -66,6 +110,15 @@ pub(super) struct ChildModule {
     pub is_final: bool,
 }
 
+/// Module (inside `tags` module) holding the facade for operations with some tag.
+#[derive(Debug, Clone)]
+struct TagModule {
+    /// Name of the tag.
+    tag: String,
+    /// Description of the tag (if any), to be used for docs.
+    description: Option<String>,
+}
+
 impl EmitterState {
     /// Sets the base URL for this session.
     ///
@@ -85,6 +138,66 @@ impl EmitterState {
         self.base_url.borrow().to_string()
     }
 
+    /// Sets the crate metadata for this session.
+    pub fn set_meta(&self, meta: CrateMeta) {
+        *self.crate_meta.borrow_mut() = Some(meta)
+    }
+
+    pub(super) fn get_meta(&self) -> Rc<RefCell<Option<CrateMeta>>> {
+        self.crate_meta.clone()
+    }
+
+    /// Returns whether this session is for emitting a blocking API client.
+    pub(crate) fn is_blocking(&self) -> bool {
+        self.crate_meta
+            .borrow()
+            .as_ref()
+            .map(|m| m.blocking)
+            .unwrap_or(false)
+    }
+
+    /// Returns the HTTP backends to be emitted for the API client. These are
+    /// ignored for blocking clients.
+    pub(crate) fn http_backends(&self) -> HttpBackends {
+        if self.is_blocking() {
+            if self.http_backends.any() {
+                warn!("HTTP backends are not supported for blocking clients. Ignoring them.");
+            }
+
+            return HttpBackends::default();
+        }
+
+        self.http_backends
+    }
+
+    /// Returns the existing Rust type to be used for the given schema (if any),
+    /// either from `rust_types` or from the `x-rust-type` extension.
+    pub(crate) fn rust_type<S: Schema>(&self, def: &S) -> Option<String> {
+        def.name()
+            .and_then(|n| self.rust_types.get(n))
+            .map(String::as_str)
+            .or_else(|| def.rust_type())
+            .map(String::from)
+    }
+
+    /// Checks whether the given schema (or the innermost schema of an array
+    /// or a map) is an existing Rust type, in which case its type doesn't need
+    /// the `Any` parameter.
+    pub(crate) fn refers_rust_type<S: Schema>(&self, schema: &S) -> bool {
+        if self.rust_type(schema).is_some() {
+            return true;
+        }
+
+        if let Some(s) = schema.items() {
+            return self.refers_rust_type(&*s.read());
+        }
+
+        match schema.additional_properties() {
+            Some(Either::Right(s)) => self.refers_rust_type(&*s.read()),
+            _ => false,
+        }
+    }
+
     /// Resets internal state-related information used by the emitter.
     pub(crate) fn reset_internal_fields(&self) {
         *self.mod_children.borrow_mut() = Default::default();
@@ -93,7 +206,42 @@ impl EmitterState {
         *self.unit_types.borrow_mut() = Default::default();
         *self.cli_yaml.borrow_mut() = Default::default();
         *self.cli_match_arms.borrow_mut() = Default::default();
+        *self.tag_mods.borrow_mut() = Default::default();
+        *self.security_schemes.borrow_mut() = Default::default();
         *self.media_coders.borrow_mut() = Default::default();
+        *self.report.borrow_mut() = Default::default();
+    }
+
+    /// Reports the error found at the given location in spec. In diagnostics
+    /// mode, it's collected (so that we can move on), otherwise it's returned.
+    pub(crate) fn report(&self, pointer: &str, err: Error) -> Result<(), Error> {
+        if !self.diagnostics {
+            return Err(err);
+        }
+
+        debug!("Error at {:?}: {}", pointer, err);
+        self.report.borrow_mut().push(pointer, err);
+        Ok(())
+    }
+
+    /// Sets the errors found while resolving the spec (in diagnostics mode),
+    /// so that they're reported along with the errors found during codegen.
+    pub fn set_resolver_report(&self, report: Report<ValidationError>) {
+        let mut r = self.resolver_report.borrow_mut();
+        r.diagnostics.clear();
+        for d in report.diagnostics {
+            r.push(d.pointer, d.error.into());
+        }
+    }
+
+    /// Returns the errors collected so far (if any), including the ones
+    /// found while resolving the spec.
+    pub(crate) fn take_report(&self) -> Result<(), PaperClipError> {
+        let mut report = std::mem::take(&mut *self.resolver_report.borrow_mut());
+        report
+            .diagnostics
+            .append(&mut self.report.borrow_mut().diagnostics);
+        report.into_result(()).map_err(PaperClipError::Diagnostics)
     }
 
     /// Sets the media type information for encoder/decoders.
@@ -112,6 +260,177 @@ impl EmitterState {
             .collect();
     }
 
+    /// Sets the security schemes for which credentials should be generated.
+    /// Unsupported schemes are skipped.
+    pub(crate) fn set_security_schemes(&self, defs: &BTreeMap<String, SecurityScheme>) {
+        let mut schemes = self.security_schemes.borrow_mut();
+        schemes.clear();
+        for (name, def) in defs {
+            let mut scheme = SecuritySchemeContext {
+                name: format!("{:?}", name),
+                field: name.to_snek_case(),
+                ty: name.to_camel_case(),
+                key: String::new(),
+                in_header: false,
+                in_query: false,
+                basic: false,
+                bearer: false,
+            };
+
+            match (def.type_.as_str(), def.in_.as_deref(), def.name.as_ref()) {
+                ("apiKey", Some("header"), Some(n)) => {
+                    scheme.in_header = true;
+                    scheme.key = format!("{:?}", n);
+                }
+                ("apiKey", Some("query"), Some(n)) => {
+                    scheme.in_query = true;
+                    scheme.key = format!("{:?}", n);
+                }
+                ("basic", _, _) => scheme.basic = true,
+                ("oauth2", _, _) => scheme.bearer = true,
+                _ => {
+                    warn!("Unsupported security scheme {:?}. Skipping.", name);
+                    continue;
+                }
+            }
+
+            if scheme.field.is_empty() || scheme.field.starts_with(|c: char| c.is_ascii_digit()) {
+                warn!(
+                    "Unable to generate credentials for security scheme {:?}. Skipping.",
+                    name
+                );
+                continue;
+            }
+
+            if RUST_KEYWORDS.contains(&scheme.field.as_str()) {
+                scheme.field.push('_');
+            }
+
+            // Names could clash with other items in the module or with the
+            // other schemes (`api_key` and `apiKey`), in which case we rename them.
+            let (ty, field) = (scheme.ty.clone(), scheme.field.clone());
+            let mut suffix = 0;
+            while SECURITY_MODULE_ITEMS.contains(&scheme.ty.as_str())
+                || schemes
+                    .iter()
+                    .any(|s| s.ty == scheme.ty || s.field == scheme.field)
+            {
+                suffix += 1;
+                scheme.ty = format!("{}{}", ty, suffix);
+                scheme.field = format!("{}_{}", field.trim_end_matches('_'), suffix);
+            }
+
+            if suffix > 0 {
+                warn!(
+                    "Credentials for security scheme {:?} clash with other items. \
+                     Renaming to {:?} (and {:?} field).",
+                    name, scheme.ty, scheme.field
+                );
+            }
+
+            schemes.push(scheme);
+        }
+    }
+
+    /// Checks whether credentials have been generated for the given security scheme.
+    pub(crate) fn has_security_scheme(&self, name: &str) -> bool {
+        let name = format!("{:?}", name);
+        self.security_schemes
+            .borrow()
+            .iter()
+            .any(|s| s.name == name)
+    }
+
+    /// Whether the generated code needs `regex` crate (for checking patterns).
+    fn checks_patterns(&self) -> bool {
+        self.def_mods
+            .borrow()
+            .values()
+            .flatten()
+            .any(|o| match &o.inner {
+                ObjectContainer::Struct { fields } => fields.iter().any(|f| f.checks_pattern()),
+                _ => false,
+            })
+    }
+
+    /// Whether the generated code needs `base64` crate.
+    #[cfg(feature = "cli")]
+    fn needs_base64(&self) -> bool {
+        self.format_types.byte || self.needs_basic_auth()
+    }
+
+    /// Whether any of the security schemes needs basic authentication
+    /// (which always requires `base64` crate).
+    fn needs_basic_auth(&self) -> bool {
+        self.security_schemes.borrow().iter().any(|s| s.basic)
+    }
+
+    /// Once the emitter has collected requirements for paths, we can use this
+    /// method to register the modules for grouping operations by their tags
+    /// (if it's enabled).
+    pub(crate) fn add_tag_modules(&self, tags: &[Tag]) -> Result<(), Error> {
+        if !self.group_by_tags {
+            return Ok(());
+        }
+
+        let mut tag_mods = self.tag_mods.borrow_mut();
+        let def_mods = self.def_mods.borrow();
+        let op_tags = def_mods
+            .values()
+            .flat_map(|objects| objects.iter())
+            .flat_map(|obj| obj.paths.values())
+            .flat_map(|ops| ops.req.values())
+            .flat_map(|req| req.tags.iter());
+        for tag in op_tags {
+            let name = match tag_module_name(tag) {
+                Some(n) => n,
+                None => {
+                    warn!("Unable to generate module for tag {:?}. Skipping.", tag);
+                    continue;
+                }
+            };
+
+            tag_mods.entry(name).or_insert_with(|| TagModule {
+                tag: tag.clone(),
+                description: tags
+                    .iter()
+                    .find(|t| &t.name == tag)
+                    .and_then(|t| t.description.clone()),
+            });
+        }
+
+        if tag_mods.is_empty() {
+            return Ok(());
+        }
+
+        let mut mods = self.mod_children.borrow_mut();
+        let root = mods.entry(PathBuf::new()).or_default();
+        let child = ChildModule {
+            name: TAGS_MODULE.into(),
+            is_final: false,
+        };
+
+        // Definitions could've taken this name already.
+        if root.contains(&child) {
+            return Err(PaperClipError::TagModuleConflict(TAGS_MODULE.into()).into());
+        }
+
+        root.insert(child);
+        mods.insert(
+            TAGS_MODULE.into(),
+            tag_mods
+                .keys()
+                .map(|name| ChildModule {
+                    name: name.clone(),
+                    is_final: true,
+                })
+                .collect(),
+        );
+
+        fs::create_dir_all(self.working_dir.join(TAGS_MODULE))?;
+        Ok(())
+    }
+
     /// Once the emitter has generated the struct definitions,
     /// we can call this method to generate the module declarations
     /// from root.
@@ -200,13 +519,16 @@ pub mod {name} {{
         let mut cli_yaml = self.cli_yaml.borrow_mut();
         let mut match_arms = self.cli_match_arms.borrow_mut();
         let is_cli = self.is_cli()?;
+        let is_blocking = self.is_blocking();
 
+        let mut validations = HashMap::new();
+        let mut reprs = vec![];
         for (mod_path, object) in def_mods
             .iter()
             .flat_map(move |(p, l)| l.iter().map(move |o| (p, o)))
         {
             let mut builder_content = String::new();
-            let repr = object.impl_repr(&module_prefix);
+            let repr = object.impl_repr_for_client(&module_prefix, is_blocking);
             for builder in &*repr.builders {
                 builder
                     .struct_fields_iter()
@@ -235,9 +557,136 @@ pub mod {name} {{
 
             self.append_contents(&impl_content, mod_path)?;
             self.append_contents(&builder_content, mod_path)?;
+
+            // Validation impls go after all the objects in a module.
+            let content = validations.entry(mod_path).or_insert_with(String::new);
+            repr.write_validation(content)?;
+            if self.group_by_tags {
+                reprs.push(repr);
+            }
         }
 
-        Ok(())
+        for (mod_path, content) in validations {
+            self.append_contents(&content, mod_path)?;
+        }
+
+        self.write_tag_modules(&reprs)
+    }
+
+    /// Writes the facades for operations grouped by their tags (if any) and
+    /// the extension trait for accessing them through the API client.
+    fn write_tag_modules(&self, reprs: &[ApiObjectImpl<'_>]) -> Result<(), Error> {
+        let tag_mods = self.tag_mods.borrow();
+        if tag_mods.is_empty() {
+            return Ok(());
+        }
+
+        info!("Adding facades for tags.");
+        let module_prefix = self.normalized_mod_prefix();
+        let any_value = self.default_encoding.borrow().coder().any_value.clone();
+
+        // Sort operations by their paths and methods, so that the names
+        // of facade methods don't change across sessions.
+        let mut tag_ops = HashMap::new();
+        for repr in reprs {
+            for builder in &*repr.builders {
+                let key = match (builder.rel_path, builder.method) {
+                    (Some(p), Some(m)) => (p, m),
+                    _ => continue,
+                };
+
+                for name in builder.tags.iter().filter_map(|t| tag_module_name(t)) {
+                    tag_ops
+                        .entry(name)
+                        .or_insert_with(BTreeMap::<(&str, HttpMethod), _>::new)
+                        .insert(key, (repr, builder));
+                }
+            }
+        }
+
+        let mut trait_methods = String::new();
+        for (mod_name, module) in &*tag_mods {
+            let struct_name = module.tag.to_camel_case();
+            let mut docs = format!("Operations tagged with `{}`.", module.tag);
+            if let Some(desc) = module.description.as_ref() {
+                docs.push_str("\n\n");
+                docs.push_str(desc);
+            }
+
+            let mut contents = String::new();
+            ApiObject::write_docs(Some(&docs), &mut contents, 0)?;
+            write!(
+                contents,
+                "pub struct {name}<'a, Client> {{
+    client: &'a Client,
+}}
+
+impl<'a, Client: {prefix}client::ApiClient + Sync + 'static> {name}<'a, Client> {{
+    /// Creates the facade for the given client.
+    #[inline]
+    pub fn new(client: &'a Client) -> Self {{
+        {name} {{ client }}
+    }}
+",
+                name = struct_name,
+                prefix = module_prefix
+            )?;
+
+            let mut names = HashSet::new();
+            names.insert(String::from("new"));
+            for (repr, builder) in tag_ops.get(mod_name).into_iter().flat_map(|o| o.values()) {
+                let base = match builder.constructor_fn_name() {
+                    Some(n) => n,
+                    None => continue,
+                };
+
+                // Operations from different objects could have the same names,
+                // in which case we prefix them with the object names.
+                let mut name = base.clone();
+                if names.contains(&name) {
+                    name = format!("{}_{}", builder.object.to_snek_case(), base);
+                }
+
+                let mut idx = 1;
+                while !names.insert(name.clone()) {
+                    name = format!("{}_{}_{}", builder.object.to_snek_case(), base, idx);
+                    idx += 1;
+                }
+
+                repr.write_tag_method(builder, &name, &any_value, &mut contents)?;
+            }
+
+            contents.push_str("}\n");
+            let path = self.working_dir.join(TAGS_MODULE).join(mod_name);
+            self.write_contents(&contents, &path.with_extension("rs"))?;
+
+            ApiObject::write_docs(Some(&docs), &mut trait_methods, 1)?;
+            write!(
+                trait_methods,
+                "    #[inline]
+    fn {mod_name}(&self) -> {mod_name}::{name}<'_, Self> {{
+        {mod_name}::{name}::new(self)
+    }}
+",
+                mod_name = mod_name,
+                name = struct_name
+            )?;
+        }
+
+        let contents = format!(
+            "
+/// Extension trait for accessing the operations (grouped by their tags)
+/// through the API client.
+pub trait ApiClientExt: {prefix}client::ApiClient + Sync + Sized + 'static {{{methods}}}
+
+impl<Client: {prefix}client::ApiClient + Sync + 'static> ApiClientExt for Client {{}}
+",
+            prefix = module_prefix,
+            methods = trait_methods
+        );
+
+        let path = self.working_dir.join(TAGS_MODULE).join("mod.rs");
+        self.append_contents(&contents, &path)
     }
 
     /// Once the builders have been added, we can add unit types
@@ -275,7 +724,18 @@ pub mod util {
         self.write_contents(&content, &module)?;
 
         module.set_file_name("util.rs");
-        let contents = template::render(TEMPLATE::UTIL_MOD, &EmptyContext {})?;
+        let contents = template::render(
+            TEMPLATE::UTIL_MOD,
+            &UtilModContext {
+                date: self.format_types.date,
+                date_time: self.format_types.date_time,
+                uuid: self.format_types.uuid,
+                bytes: self.format_types.byte,
+                base64_optional: !self.needs_basic_auth(),
+                patterns: self.checks_patterns(),
+                blocking: self.is_blocking(),
+            },
+        )?;
         self.write_contents(&contents, &module)?;
 
         self.add_cli_deps_if_needed()?;
@@ -285,12 +745,19 @@ pub mod util {
     /// Once the builders have been added, we can add API client dependencies.
     pub(crate) fn add_client_deps(&self) -> Result<(), Error> {
         let module = self.root_module_path();
+        let backends = self.http_backends();
         let contents = template::render(
             TEMPLATE::CLIENT_MOD,
             &ClientModContext {
                 mod_prefix: &self.normalized_mod_prefix(),
                 media_coders: &*self.media_coders.borrow(),
                 base_url: self.base_url.borrow().as_str(),
+                group_by_tags: !self.tag_mods.borrow().is_empty(),
+                blocking: self.is_blocking(),
+                backends: backends.any(),
+                hyper: backends.hyper,
+                awc: backends.awc,
+                security_schemes: &self.security_schemes.borrow(),
             },
         )?;
 
@@ -346,6 +813,7 @@ pub mod util {
                 &CliUtilContext {
                     match_arms: &*self.cli_match_arms.borrow(),
                     media_coders: &*self.media_coders.borrow(),
+                    blocking: self.is_blocking(),
                 },
             )?;
 
@@ -353,7 +821,12 @@ pub mod util {
         }
 
         // `main.rs`
-        let contents = template::render(TEMPLATE::CLI_MAIN, &EmptyContext {})?;
+        let contents = template::render(
+            TEMPLATE::CLI_MAIN,
+            &CliMainContext {
+                blocking: self.is_blocking(),
+            },
+        )?;
         self.append_contents(&contents, &root)
     }
 
@@ -368,7 +841,7 @@ pub mod util {
     }
 
     /// Normalized module prefix used by codegen.
-    fn normalized_mod_prefix(&self) -> String {
+    pub(super) fn normalized_mod_prefix(&self) -> String {
         format!("{}::", self.mod_prefix.trim_matches(':'))
     }
 }
@@ -377,15 +850,6 @@ pub mod util {
 
 #[cfg(feature = "cli")]
 impl EmitterState {
-    /// Sets the crate metadata for this session.
-    pub fn set_meta(&self, meta: CrateMeta) {
-        *self.crate_meta.borrow_mut() = Some(meta)
-    }
-
-    pub(super) fn get_meta(&self) -> Rc<RefCell<Option<CrateMeta>>> {
-        self.crate_meta.clone()
-    }
-
     /// Checks whether this session is for emitting a crate or CLI.
     fn needs_root_module(&self) -> bool {
         self.crate_meta
@@ -423,6 +887,7 @@ impl EmitterState {
         };
 
         if self.needs_root_module() {
+            let backends = self.http_backends();
             let contents = template::render(
                 TEMPLATE::CARGO_MANIFEST,
                 &ManifestContext {
@@ -430,6 +895,15 @@ impl EmitterState {
                     version: &format!("{:?}", meta.version.as_ref().unwrap()),
                     authors: &format!("{:?}", meta.authors.as_ref().unwrap()),
                     is_cli,
+                    chrono: self.format_types.needs_chrono(),
+                    uuid: self.format_types.uuid,
+                    base64: self.needs_base64(),
+                    base64_optional: !self.needs_basic_auth(),
+                    regex: self.checks_patterns(),
+                    blocking: meta.blocking,
+                    backends: backends.any(),
+                    hyper: backends.hyper,
+                    awc: backends.awc,
                 },
             )?;
 
@@ -476,14 +950,6 @@ impl EmitterState {
 
 #[cfg(not(feature = "cli"))]
 impl EmitterState {
-    /// This is a no-op.
-    pub fn set_meta(&self, _: CrateMeta) {}
-
-    /// This is a no-op
-    pub fn get_meta(&self) -> Rc<RefCell<Option<CrateMeta>>> {
-        Rc::new(RefCell::new(None))
-    }
-
     /// Always returns `Ok(None)`
     fn infer_crate_meta(&self) -> Result<Rc<RefCell<Option<CrateMeta>>>, Error> {
         Ok(Rc::new(RefCell::new(None)))
@@ -513,7 +979,12 @@ impl Clone for EmitterState {
             working_dir: self.working_dir.clone(),
             mod_prefix: self.mod_prefix,
             ns_sep: self.ns_sep,
-            #[cfg(feature = "cli")]
+            diagnostics: self.diagnostics,
+            filter: self.filter.clone(),
+            group_by_tags: self.group_by_tags,
+            rust_types: self.rust_types.clone(),
+            format_types: self.format_types,
+            http_backends: self.http_backends,
             crate_meta: self.crate_meta.clone(),
             base_url: self.base_url.clone(),
             default_encoding: self.default_encoding.clone(),
@@ -529,7 +1000,12 @@ impl Default for EmitterState {
             working_dir: PathBuf::from("."),
             mod_prefix: "crate::",
             ns_sep: ".",
-            #[cfg(feature = "cli")]
+            diagnostics: false,
+            filter: Filter::default(),
+            group_by_tags: false,
+            rust_types: HashMap::new(),
+            format_types: FormatTypes::default(),
+            http_backends: HttpBackends::default(),
             crate_meta: Rc::new(RefCell::new(None)),
             base_url: RefCell::new("https://example.com".parse().expect("invalid URL?")),
             def_mods: RefCell::new(HashMap::new()),
@@ -538,8 +1014,12 @@ impl Default for EmitterState {
             unit_types: RefCell::new(BTreeSet::new()),
             cli_yaml: RefCell::new(String::new()),
             cli_match_arms: RefCell::new(String::new()),
+            tag_mods: RefCell::new(BTreeMap::new()),
+            security_schemes: RefCell::new(vec![]),
             media_coders: RefCell::new(vec![]),
             default_encoding: RefCell::new(SpecFormat::Json),
+            resolver_report: RefCell::new(Report::default()),
+            report: RefCell::new(Report::default()),
         }
     }
 }
@@ -565,12 +1045,33 @@ struct ManifestContext<'a> {
     version: &'a str,
     authors: &'a str,
     is_cli: bool,
+    chrono: bool,
+    uuid: bool,
+    base64: bool,
+    base64_optional: bool,
+    regex: bool,
+    blocking: bool,
+    backends: bool,
+    hyper: bool,
+    awc: bool,
+}
+
+#[derive(serde::Serialize)]
+struct UtilModContext {
+    date: bool,
+    date_time: bool,
+    uuid: bool,
+    bytes: bool,
+    base64_optional: bool,
+    patterns: bool,
+    blocking: bool,
 }
 
 #[derive(serde::Serialize)]
 struct CliUtilContext<'a> {
     match_arms: &'a str,
     media_coders: &'a [MediaCoder],
+    blocking: bool,
 }
 
 #[derive(serde::Serialize)]
@@ -578,6 +1079,29 @@ struct ClientModContext<'a> {
     base_url: &'a str,
     mod_prefix: &'a str,
     media_coders: &'a [MediaCoder],
+    group_by_tags: bool,
+    blocking: bool,
+    backends: bool,
+    hyper: bool,
+    awc: bool,
+    security_schemes: &'a [SecuritySchemeContext],
+}
+
+/// Security scheme for which credentials are generated.
+#[derive(Debug, serde::Serialize)]
+struct SecuritySchemeContext {
+    /// Name of the scheme (as a string literal).
+    name: String,
+    /// Name of the field holding the credentials.
+    field: String,
+    /// Name of the credentials type.
+    ty: String,
+    /// Name of the header or query parameter (as a string literal) for API keys.
+    key: String,
+    in_header: bool,
+    in_query: bool,
+    basic: bool,
+    bearer: bool,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -595,4 +1119,20 @@ struct ClapYamlContext<'a> {
 }
 
 #[derive(serde::Serialize)]
-struct EmptyContext {}
+struct CliMainContext {
+    blocking: bool,
+}
+
+/// Returns the name of the module for the given tag (if it can be used as an identifier).
+fn tag_module_name(tag: &str) -> Option<String> {
+    let mut name = tag.to_snek_case();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    if RUST_KEYWORDS.contains(&name.as_str()) {
+        name.push('_');
+    }
+
+    Some(name)
+}
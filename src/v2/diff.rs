@@ -0,0 +1,828 @@
+//! Comparing OpenAPI v2 specs for breaking changes.
+//!
+//! [`diff`](fn.diff.html) compares two versions of a spec and classifies the
+//! [changes](enum.ChangeKind.html) as breaking or non-breaking (from the point
+//! of view of the existing clients).
+//!
+//! ```rust,no_run
+//! use paperclip::v2::{self, diff, DefaultSchema, ResolvableApi};
+//!
+//! use std::fs::File;
+//!
+//! let old: ResolvableApi<DefaultSchema> = v2::from_reader(File::open("old.yaml").unwrap()).unwrap();
+//! let new: ResolvableApi<DefaultSchema> = v2::from_reader(File::open("new.yaml").unwrap()).unwrap();
+//! for change in diff::diff(&old, &new) {
+//!     println!("{}", change);
+//! }
+//! ```
+
+use super::{
+    models::{
+        Api, DataType, DataTypeFormat, Either, Items, Parameter, ParameterIn, Reference,
+        Resolvable, ResolvableApi, ResolvableParameter, ResolvablePathItem, ResolvableResponse,
+    },
+    pointer_child, raw_schema, Schema, DEF_REF_PREFIX, PARAM_REF_PREFIX, RESP_REF_PREFIX,
+};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+/// Changes reported by the differ.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChangeKind {
+    /// Path was removed.
+    PathRemoved,
+    /// Path was added.
+    PathAdded,
+    /// Operation was removed from a path.
+    OperationRemoved,
+    /// Operation was added to a path.
+    OperationAdded,
+    /// Response (status code) was removed from an operation.
+    ResponseRemoved,
+    /// Required parameter was added to an operation (or an optional
+    /// parameter became required).
+    RequiredParameterAdded,
+    /// Optional parameter was added to an operation.
+    OptionalParameterAdded,
+    /// Parameter was removed from an operation.
+    ParameterRemoved,
+    /// Required property was added to a request body (or an optional
+    /// property became required).
+    RequiredPropertyAdded,
+    /// Type (or format) of a parameter or a schema has changed.
+    TypeChanged,
+    /// Values were removed from an enum (breaking for requests).
+    EnumNarrowed,
+    /// Values were added to an enum (breaking for responses).
+    EnumWidened,
+    /// Property was removed from a response.
+    ResponseFieldRemoved,
+    /// Property was added to a response.
+    ResponseFieldAdded,
+}
+
+impl ChangeKind {
+    /// Name of this change (as used in reports).
+    pub fn name(self) -> &'static str {
+        match self {
+            ChangeKind::PathRemoved => "path-removed",
+            ChangeKind::PathAdded => "path-added",
+            ChangeKind::OperationRemoved => "operation-removed",
+            ChangeKind::OperationAdded => "operation-added",
+            ChangeKind::ResponseRemoved => "response-removed",
+            ChangeKind::RequiredParameterAdded => "required-parameter-added",
+            ChangeKind::OptionalParameterAdded => "optional-parameter-added",
+            ChangeKind::ParameterRemoved => "parameter-removed",
+            ChangeKind::RequiredPropertyAdded => "required-property-added",
+            ChangeKind::TypeChanged => "type-changed",
+            ChangeKind::EnumNarrowed => "enum-narrowed",
+            ChangeKind::EnumWidened => "enum-widened",
+            ChangeKind::ResponseFieldRemoved => "response-field-removed",
+            ChangeKind::ResponseFieldAdded => "response-field-added",
+        }
+    }
+
+    /// Whether this change breaks the existing clients.
+    ///
+    /// **NOTE:** Enum changes are classified as they are for requests. The
+    /// differ classifies them based on where the enum is used.
+    pub fn compatibility(self) -> Compatibility {
+        match self {
+            ChangeKind::PathRemoved
+            | ChangeKind::OperationRemoved
+            | ChangeKind::ResponseRemoved
+            | ChangeKind::RequiredParameterAdded
+            | ChangeKind::RequiredPropertyAdded
+            | ChangeKind::TypeChanged
+            | ChangeKind::EnumNarrowed
+            | ChangeKind::ResponseFieldRemoved => Compatibility::Breaking,
+            _ => Compatibility::NonBreaking,
+        }
+    }
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Whether a change breaks the existing clients.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Compatibility {
+    NonBreaking,
+    Breaking,
+}
+
+impl fmt::Display for Compatibility {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Compatibility::NonBreaking => "non-breaking",
+            Compatibility::Breaking => "breaking",
+        })
+    }
+}
+
+/// Change found between two specs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Change {
+    /// Kind of this change.
+    pub kind: ChangeKind,
+    /// Whether this change breaks the existing clients.
+    pub compatibility: Compatibility,
+    /// JSON pointer to the changed value in the new spec (or in the old
+    /// spec if the value has been removed).
+    pub pointer: String,
+    /// Description of the change.
+    pub message: String,
+}
+
+impl Change {
+    /// Whether this change breaks the existing clients.
+    pub fn is_breaking(&self) -> bool {
+        self.compatibility == Compatibility::Breaking
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}[{}] {}: {}",
+            self.compatibility, self.kind, self.pointer, self.message
+        )
+    }
+}
+
+/// Compares the old spec with the new one and returns all the changes.
+///
+/// References to parameters, responses and definitions are followed by
+/// the differ, so the specs don't have to be resolved. Paths are matched
+/// regardless of their templating (i.e., `/pets/{id}` and `/pets/{petId}`
+/// are the same path).
+pub fn diff<S: Schema>(old: &ResolvableApi<S>, new: &ResolvableApi<S>) -> Vec<Change> {
+    let mut differ = Differ {
+        old,
+        new,
+        changes: vec![],
+        visited: HashSet::new(),
+    };
+
+    differ.diff_paths();
+    differ.changes
+}
+
+/// Whether a schema is sent by the clients or received by them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+enum Direction {
+    Request,
+    Response,
+}
+
+/// Locations of some value in the old and new specs.
+#[derive(Clone)]
+struct Location {
+    old: String,
+    new: String,
+}
+
+impl Location {
+    fn child(&self, key: &str) -> Self {
+        Location {
+            old: pointer_child(&self.old, key),
+            new: pointer_child(&self.new, key),
+        }
+    }
+}
+
+/// Compares the specs and collects the changes.
+struct Differ<'a, S> {
+    old: &'a ResolvableApi<S>,
+    new: &'a ResolvableApi<S>,
+    changes: Vec<Change>,
+    /// Pairs of (old and new) definitions which have already been compared.
+    visited: HashSet<(*const RwLock<S>, *const RwLock<S>, Direction)>,
+}
+
+impl<'a, S: Schema> Differ<'a, S> {
+    /// Adds a change found at the given location (unless it's been reported already,
+    /// which happens when a definition is used in both requests and responses).
+    fn report(&mut self, kind: ChangeKind, pointer: &str, message: String) {
+        self.report_as(kind, kind.compatibility(), pointer, message)
+    }
+
+    /// Same as `report`, but with the given compatibility.
+    fn report_as(
+        &mut self,
+        kind: ChangeKind,
+        compatibility: Compatibility,
+        pointer: &str,
+        message: String,
+    ) {
+        // Changes in schemas used by both requests and responses are reported
+        // once (as breaking if they break either of them).
+        let existing = self
+            .changes
+            .iter_mut()
+            .find(|c| c.kind == kind && c.pointer == pointer && c.message == message);
+        if let Some(c) = existing {
+            c.compatibility = c.compatibility.max(compatibility);
+            return;
+        }
+
+        self.changes.push(Change {
+            kind,
+            compatibility,
+            pointer: pointer.into(),
+            message,
+        });
+    }
+
+    /// Compares the paths and their operations.
+    fn diff_paths(&mut self) {
+        let normalized = |api: &'a ResolvableApi<S>| {
+            api.paths
+                .iter()
+                .map(|(path, item)| {
+                    let path_fmt = Api::<(), (), ()>::path_parameters_map(path, |_| ":".into());
+                    (path_fmt.into_owned(), (path.as_str(), item))
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        let (old_paths, new_paths) = (normalized(self.old), normalized(self.new));
+        for (path_fmt, &(path, old_item)) in &old_paths {
+            let old_pointer = pointer_child("/paths", path);
+            let (new_path, new_item) = match new_paths.get(path_fmt) {
+                Some(&p) => p,
+                None => {
+                    self.report(
+                        ChangeKind::PathRemoved,
+                        &old_pointer,
+                        format!("Path {:?} was removed", path),
+                    );
+                    continue;
+                }
+            };
+
+            let loc = Location {
+                old: old_pointer,
+                new: pointer_child("/paths", new_path),
+            };
+            // Path parameters could've been renamed along with the template.
+            let renames = template_params(path)
+                .into_iter()
+                .zip(template_params(new_path))
+                .collect::<HashMap<_, _>>();
+
+            for (method, old_op) in &old_item.methods {
+                let method_name = method.to_string().to_lowercase();
+                let loc = loc.child(&method_name);
+                if !new_item.methods.contains_key(method) {
+                    self.report(
+                        ChangeKind::OperationRemoved,
+                        &loc.old,
+                        format!(
+                            "Operation {} was removed from path {:?}",
+                            method_name.to_uppercase(),
+                            path
+                        ),
+                    );
+                    continue;
+                }
+
+                let new_op = &new_item.methods[method];
+                let old_params = parameters(self.old, old_item, &old_op.parameters, &loc.old);
+                let new_params = parameters(self.new, new_item, &new_op.parameters, &loc.new);
+                self.diff_parameters(old_params, new_params, &renames);
+
+                for (code, old_resp) in &old_op.responses {
+                    let new_resp = match new_op.responses.get(code) {
+                        Some(r) => r,
+                        None => {
+                            self.report(
+                                ChangeKind::ResponseRemoved,
+                                &loc.child("responses").child(code).old,
+                                format!(
+                                    "Response {:?} was removed from operation {} in path {:?}",
+                                    code,
+                                    method_name.to_uppercase(),
+                                    path
+                                ),
+                            );
+                            continue;
+                        }
+                    };
+
+                    let (old_resp, new_resp) =
+                        match (response(self.old, old_resp), response(self.new, new_resp)) {
+                            (Some(o), Some(n)) => (o.read(), n.read()),
+                            _ => continue,
+                        };
+
+                    if let (Some(o), Some(n)) = (old_resp.schema.as_ref(), new_resp.schema.as_ref())
+                    {
+                        let loc = loc.child("responses").child(code).child("schema");
+                        self.diff_schema(o, n, loc, Direction::Response);
+                    }
+                }
+            }
+
+            for method in new_item.methods.keys() {
+                if !old_item.methods.contains_key(method) {
+                    let method_name = method.to_string().to_lowercase();
+                    self.report(
+                        ChangeKind::OperationAdded,
+                        &loc.child(&method_name).new,
+                        format!(
+                            "Operation {} was added to path {:?}",
+                            method_name.to_uppercase(),
+                            new_path
+                        ),
+                    );
+                }
+            }
+        }
+
+        for (path_fmt, &(path, _)) in &new_paths {
+            if !old_paths.contains_key(path_fmt) {
+                self.report(
+                    ChangeKind::PathAdded,
+                    &pointer_child("/paths", path),
+                    format!("Path {:?} was added", path),
+                );
+            }
+        }
+    }
+
+    /// Compares the parameters of an operation.
+    fn diff_parameters(
+        &mut self,
+        old: Vec<(&'a ResolvableParameter<S>, String)>,
+        new: Vec<(&'a ResolvableParameter<S>, String)>,
+        renames: &HashMap<String, String>,
+    ) {
+        let same = |o: &Parameter<Resolvable<S>>, n: &Parameter<Resolvable<S>>| {
+            let name = match o.in_ {
+                ParameterIn::Path => renames.get(&o.name).unwrap_or(&o.name),
+                _ => &o.name,
+            };
+
+            o.in_ == n.in_ && *name == n.name
+        };
+
+        for (old_param, old_pointer) in &old {
+            let o = old_param.read();
+            let found = new.iter().find(|(p, _)| same(&o, &p.read()));
+
+            let (n, new_pointer) = match found {
+                Some((p, pointer)) => (p.read(), pointer),
+                None => {
+                    self.report(
+                        ChangeKind::ParameterRemoved,
+                        old_pointer,
+                        format!("Parameter {:?} ({:?}) was removed", o.name, o.in_),
+                    );
+                    continue;
+                }
+            };
+
+            if n.required && !o.required {
+                self.report(
+                    ChangeKind::RequiredParameterAdded,
+                    new_pointer,
+                    format!("Parameter {:?} ({:?}) is now required", n.name, n.in_),
+                );
+            }
+
+            if let (Some(os), Some(ns)) = (o.schema.as_ref(), n.schema.as_ref()) {
+                let loc = Location {
+                    old: pointer_child(old_pointer, "schema"),
+                    new: pointer_child(new_pointer, "schema"),
+                };
+                self.diff_schema(os, ns, loc, Direction::Request);
+                continue;
+            }
+
+            self.diff_parameter_type(&o, &n, new_pointer);
+            self.diff_enum(&o.enum_, &n.enum_, new_pointer, Direction::Request);
+        }
+
+        for (new_param, new_pointer) in &new {
+            let n = new_param.read();
+            if old.iter().any(|(p, _)| same(&p.read(), &n)) {
+                continue;
+            }
+
+            // Path parameters are always required.
+            if n.required || n.in_ == ParameterIn::Path {
+                self.report(
+                    ChangeKind::RequiredParameterAdded,
+                    new_pointer,
+                    format!("Required parameter {:?} ({:?}) was added", n.name, n.in_),
+                );
+            } else {
+                self.report(
+                    ChangeKind::OptionalParameterAdded,
+                    new_pointer,
+                    format!("Optional parameter {:?} ({:?}) was added", n.name, n.in_),
+                );
+            }
+        }
+    }
+
+    /// Compares the types of non-body parameters (including the array items).
+    fn diff_parameter_type(
+        &mut self,
+        old: &Parameter<Resolvable<S>>,
+        new: &Parameter<Resolvable<S>>,
+        pointer: &str,
+    ) {
+        let (mut o, mut n) = (
+            (old.data_type, old.format.as_ref(), old.items.as_ref()),
+            (new.data_type, new.format.as_ref(), new.items.as_ref()),
+        );
+        let mut pointer = pointer.to_owned();
+        loop {
+            if (o.0, o.1) != (n.0, n.1) {
+                self.report(
+                    ChangeKind::TypeChanged,
+                    &pointer,
+                    format!(
+                        "Type changed from {} to {}",
+                        type_name(o.0, o.1),
+                        type_name(n.0, n.1)
+                    ),
+                );
+                return;
+            }
+
+            match (o.2, n.2) {
+                (Some(oi), Some(ni)) if o.0 == Some(DataType::Array) => {
+                    pointer = pointer_child(&pointer, "items");
+                    o = item_type(oi);
+                    n = item_type(ni);
+                }
+                _ => return,
+            }
+        }
+    }
+
+    /// Compares the values of enums. Clients can't send the removed values
+    /// in requests, and they may not recognize the added values in responses.
+    fn diff_enum(
+        &mut self,
+        old: &[serde_json::Value],
+        new: &[serde_json::Value],
+        pointer: &str,
+        direction: Direction,
+    ) {
+        // Empty enum means that all values are allowed.
+        if old.is_empty() && new.is_empty() {
+            return;
+        }
+
+        let removed = if new.is_empty() {
+            vec![]
+        } else {
+            old.iter().filter(|v| !new.contains(v)).collect::<Vec<_>>()
+        };
+        let added = if old.is_empty() {
+            vec![]
+        } else {
+            new.iter().filter(|v| !old.contains(v)).collect::<Vec<_>>()
+        };
+
+        let compatibility = |breaking| {
+            if breaking {
+                Compatibility::Breaking
+            } else {
+                Compatibility::NonBreaking
+            }
+        };
+
+        if old.is_empty() || !removed.is_empty() {
+            self.report_as(
+                ChangeKind::EnumNarrowed,
+                compatibility(direction == Direction::Request),
+                pointer,
+                if old.is_empty() {
+                    format!("Values are now restricted to {}", values(new.iter()))
+                } else {
+                    format!("Value(s) {} were removed from enum", values(removed))
+                },
+            );
+        }
+
+        if new.is_empty() || !added.is_empty() {
+            self.report_as(
+                ChangeKind::EnumWidened,
+                compatibility(direction == Direction::Response),
+                pointer,
+                if new.is_empty() {
+                    "Values are no longer restricted".into()
+                } else {
+                    format!("Value(s) {} were added to enum", values(added))
+                },
+            );
+        }
+    }
+
+    /// Compares the given schemas (and their children).
+    fn diff_schema(
+        &mut self,
+        old: &Resolvable<S>,
+        new: &Resolvable<S>,
+        mut loc: Location,
+        direction: Direction,
+    ) {
+        let (old, new) = match (
+            definition(self.old, old, &mut loc.old),
+            definition(self.new, new, &mut loc.new),
+        ) {
+            (Some(o), Some(n)) => (o, n),
+            // References to other documents (or the missing ones) can't be compared.
+            _ => return,
+        };
+
+        if !self
+            .visited
+            .insert((Arc::as_ptr(&old), Arc::as_ptr(&new), direction))
+        {
+            return;
+        }
+
+        let (o, n) = (old.read(), new.read());
+        let (old_ty, new_ty) = (
+            (o.effective_data_type(), o.format()),
+            (n.effective_data_type(), n.format()),
+        );
+        if old_ty != new_ty {
+            self.report(
+                ChangeKind::TypeChanged,
+                &loc.new,
+                format!(
+                    "Type changed from {} to {}",
+                    type_name(old_ty.0, old_ty.1),
+                    type_name(new_ty.0, new_ty.1)
+                ),
+            );
+            return;
+        }
+
+        self.diff_enum(
+            o.enum_variants().unwrap_or_default(),
+            n.enum_variants().unwrap_or_default(),
+            &loc.new,
+            direction,
+        );
+
+        if let (Some(oi), Some(ni)) = (o.items(), n.items()) {
+            self.diff_schema(oi, ni, loc.child("items"), direction);
+        }
+
+        if let (Some(Either::Right(oe)), Some(Either::Right(ne))) =
+            (o.additional_properties(), n.additional_properties())
+        {
+            self.diff_schema(oe, ne, loc.child("additionalProperties"), direction);
+        }
+
+        let (old_props, new_props) = (
+            properties(self.old, &o, &loc.old),
+            properties(self.new, &n, &loc.new),
+        );
+        let (old_required, new_required) = (required(self.old, &o), required(self.new, &n));
+        for (name, (old_prop, old_pointer)) in &old_props {
+            let (new_prop, new_pointer) = match new_props.get(name) {
+                Some(p) => p,
+                None => {
+                    if direction == Direction::Response {
+                        self.report(
+                            ChangeKind::ResponseFieldRemoved,
+                            old_pointer,
+                            format!("Property {:?} was removed from response", name),
+                        );
+                    }
+
+                    continue;
+                }
+            };
+
+            if direction == Direction::Request
+                && new_required.contains(name)
+                && !old_required.contains(name)
+            {
+                self.report(
+                    ChangeKind::RequiredPropertyAdded,
+                    new_pointer,
+                    format!("Property {:?} is now required", name),
+                );
+            }
+
+            let loc = Location {
+                old: old_pointer.clone(),
+                new: new_pointer.clone(),
+            };
+            self.diff_schema(old_prop, new_prop, loc, direction);
+        }
+
+        for (name, (_, new_pointer)) in &new_props {
+            if old_props.contains_key(name) {
+                continue;
+            }
+
+            match direction {
+                Direction::Response => self.report(
+                    ChangeKind::ResponseFieldAdded,
+                    new_pointer,
+                    format!("Property {:?} was added to response", name),
+                ),
+                Direction::Request if new_required.contains(name) => self.report(
+                    ChangeKind::RequiredPropertyAdded,
+                    new_pointer,
+                    format!("Required property {:?} was added", name),
+                ),
+                Direction::Request => (),
+            }
+        }
+    }
+}
+
+/// Collects the parameters of an operation (including the ones shared
+/// by all operations in the path) along with their pointers.
+fn parameters<'a, S>(
+    api: &'a ResolvableApi<S>,
+    item: &'a ResolvablePathItem<S>,
+    op_params: &'a [Either<Reference, ResolvableParameter<S>>],
+    op_pointer: &str,
+) -> Vec<(&'a ResolvableParameter<S>, String)> {
+    let item_pointer = op_pointer
+        .rsplit_once('/')
+        .map(|(p, _)| p)
+        .unwrap_or_default();
+    let mut params: Vec<(&ResolvableParameter<S>, String)> = vec![];
+    for &(list, pointer) in &[
+        (&item.parameters[..], item_pointer),
+        (op_params, op_pointer),
+    ] {
+        for (i, p) in list.iter().enumerate() {
+            let p = match parameter(api, p) {
+                Some(p) => p,
+                None => continue,
+            };
+
+            // Operation parameters override the ones in path.
+            let (name, in_) = {
+                let p = p.read();
+                (p.name.clone(), p.in_)
+            };
+            params.retain(|(q, _)| {
+                let q = q.read();
+                q.name != name || q.in_ != in_
+            });
+
+            let pointer = pointer_child(&pointer_child(pointer, "parameters"), &i.to_string());
+            params.push((p, pointer));
+        }
+    }
+
+    params
+}
+
+/// Returns the names of parameters in the given templated path.
+fn template_params(path: &str) -> Vec<String> {
+    let mut names = vec![];
+    Api::<(), (), ()>::path_parameters_map(path, |p| {
+        names.push(p.to_owned());
+        ":".into()
+    });
+    names
+}
+
+/// Returns the parameter (following the reference if needed).
+fn parameter<'a, S>(
+    api: &'a ResolvableApi<S>,
+    param: &'a Either<Reference, ResolvableParameter<S>>,
+) -> Option<&'a ResolvableParameter<S>> {
+    match param {
+        Either::Left(r) => r
+            .reference
+            .strip_prefix(PARAM_REF_PREFIX)
+            .and_then(|n| api.parameters.get(n)),
+        Either::Right(p) => Some(p),
+    }
+}
+
+/// Returns the response (following the reference if needed).
+fn response<'a, S>(
+    api: &'a ResolvableApi<S>,
+    resp: &'a Either<Reference, ResolvableResponse<S>>,
+) -> Option<&'a ResolvableResponse<S>> {
+    match resp {
+        Either::Left(r) => r
+            .reference
+            .strip_prefix(RESP_REF_PREFIX)
+            .and_then(|n| api.responses.get(n)),
+        Either::Right(r) => Some(r),
+    }
+}
+
+/// Returns the actual schema (following the references if needed) and
+/// updates the pointer if it's in some definition.
+fn definition<S: Schema>(
+    api: &ResolvableApi<S>,
+    schema: &Resolvable<S>,
+    pointer: &mut String,
+) -> Option<Arc<RwLock<S>>> {
+    let mut schema = raw_schema(schema).clone();
+    // Guard against definitions referring to each other.
+    for _ in 0..=api.definitions.len() {
+        let def = {
+            let s = schema.read();
+            let name = match s.reference() {
+                Some(r) => r.strip_prefix(DEF_REF_PREFIX)?,
+                None => break,
+            };
+
+            *pointer = pointer_child("/definitions", name);
+            raw_schema(api.definitions.get(name)?).clone()
+        };
+
+        schema = def;
+    }
+
+    Some(schema).filter(|s| s.read().reference().is_none())
+}
+
+/// Collects the properties of the given schema (including the ones from `allOf`)
+/// along with their pointers.
+fn properties<S: Schema>(
+    api: &ResolvableApi<S>,
+    schema: &S,
+    pointer: &str,
+) -> BTreeMap<String, (Resolvable<S>, String)> {
+    let mut props = BTreeMap::new();
+    for (name, prop) in schema.properties().into_iter().flatten() {
+        let pointer = pointer_child(&pointer_child(pointer, "properties"), name);
+        props.insert(name.clone(), (prop.clone(), pointer));
+    }
+
+    for (i, base) in schema.all_of().into_iter().flatten().enumerate() {
+        let mut pointer = pointer_child(&pointer_child(pointer, "allOf"), &i.to_string());
+        let base = match definition(api, base, &mut pointer) {
+            Some(b) => b,
+            None => continue,
+        };
+
+        for (name, value) in properties(api, &*base.read(), &pointer) {
+            props.entry(name).or_insert(value);
+        }
+    }
+
+    props
+}
+
+/// Collects the required properties of the given schema (including the ones from `allOf`).
+fn required<S: Schema>(api: &ResolvableApi<S>, schema: &S) -> BTreeSet<String> {
+    let mut names = schema.required_properties().cloned().unwrap_or_default();
+    for base in schema.all_of().into_iter().flatten() {
+        if let Some(b) = definition(api, base, &mut String::new()) {
+            names.extend(required(api, &*b.read()));
+        }
+    }
+
+    names
+}
+
+/// Returns the type information of the given items.
+fn item_type(items: &Items) -> (Option<DataType>, Option<&DataTypeFormat>, Option<&Items>) {
+    (
+        items.data_type,
+        items.format.as_ref(),
+        items.items.as_deref(),
+    )
+}
+
+/// Returns the name of the given type (for messages).
+fn type_name(ty: Option<DataType>, format: Option<&DataTypeFormat>) -> String {
+    match (ty, format) {
+        (Some(t), Some(f)) => format!("{:?} ({:?})", t, f),
+        (Some(t), None) => format!("{:?}", t),
+        (None, _) => "Any".into(),
+    }
+}
+
+/// Formats the given enum values (for messages).
+fn values<'v>(values: impl IntoIterator<Item = &'v serde_json::Value>) -> String {
+    values
+        .into_iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,299 @@
+//! Filtering the operations in OpenAPI v2 specs.
+//!
+//! The [`Filter`](struct.Filter.html) selects operations based on their tags,
+//! paths and operation IDs, and prunes the rest of the spec (along with the
+//! definitions, parameters and responses that are no longer referred).
+//!
+//! ```rust,no_run
+//! use paperclip::v2::{self, filter::Filter, DefaultSchema, ResolvableApi};
+//!
+//! use std::fs::File;
+//!
+//! let mut fd = File::open("my_spec.yaml").unwrap(); // yaml or json
+//! let api: ResolvableApi<DefaultSchema> = v2::from_reader(&mut fd).unwrap();
+//! let mut filter = Filter::default();
+//! filter.include_paths.push("/api/v1/namespaces/{namespace}/pods/**".into());
+//! filter.exclude_tags.push("admin".into());
+//! let api = filter.apply(&api);
+//! ```
+
+use super::{
+    models::{
+        Either, HttpMethod, Operation, Reference, Resolvable, ResolvableApi, ResolvableParameter,
+        ResolvableResponse,
+    },
+    raw_schema, Schema, DEF_REF_PREFIX, PARAM_REF_PREFIX, RESP_REF_PREFIX,
+};
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Filter for selecting the operations in a spec.
+///
+/// An operation is selected if it matches any of the `include_*` filters
+/// (or if there aren't any) and none of the `exclude_*` filters.
+#[derive(Clone, Debug, Default)]
+pub struct Filter {
+    /// Tags of operations to be included.
+    pub include_tags: Vec<String>,
+    /// Tags of operations to be excluded.
+    pub exclude_tags: Vec<String>,
+    /// Globs for paths of operations to be included. `*` matches anything
+    /// within a path segment and `**` matches across segments (`/foo/**`
+    /// also matches `/foo`).
+    pub include_paths: Vec<String>,
+    /// Globs for paths of operations to be excluded.
+    pub exclude_paths: Vec<String>,
+    /// IDs of operations to be included.
+    pub include_operations: Vec<String>,
+    /// IDs of operations to be excluded.
+    pub exclude_operations: Vec<String>,
+}
+
+impl Filter {
+    /// Checks whether this filter doesn't filter anything.
+    pub fn is_empty(&self) -> bool {
+        !self.has_includes()
+            && self.exclude_tags.is_empty()
+            && self.exclude_paths.is_empty()
+            && self.exclude_operations.is_empty()
+    }
+
+    /// Checks whether the given operation is selected by this filter.
+    pub fn selects<P, R>(&self, path: &str, op: &Operation<P, R>) -> bool {
+        let is_included = !self.has_includes()
+            || matches_any(
+                path,
+                op,
+                &self.include_tags,
+                &self.include_paths,
+                &self.include_operations,
+            );
+
+        is_included
+            && !matches_any(
+                path,
+                op,
+                &self.exclude_tags,
+                &self.exclude_paths,
+                &self.exclude_operations,
+            )
+    }
+
+    /// Returns the spec with only the operations selected by this filter,
+    /// along with the definitions, parameters and responses referred by them.
+    /// This works for both raw and resolved specs.
+    pub fn apply<S: Schema>(&self, api: &ResolvableApi<S>) -> ResolvableApi<S> {
+        let mut paths = BTreeMap::new();
+        for (path, map) in &api.paths {
+            let methods = map
+                .methods
+                .iter()
+                .filter(|(_, op)| self.selects(path, op))
+                .map(|(&m, op)| (m, op.clone()))
+                .collect::<BTreeMap<HttpMethod, _>>();
+            if methods.is_empty() {
+                continue;
+            }
+
+            let mut map = map.clone();
+            map.methods = methods;
+            paths.insert(path.clone(), map);
+        }
+
+        let mut refs = References::new(api);
+        for map in paths.values() {
+            let params = map
+                .methods
+                .values()
+                .flat_map(|op| op.parameters.iter())
+                .chain(map.parameters.iter());
+            for param in params {
+                refs.parameter(param);
+            }
+
+            for resp in map.methods.values().flat_map(|op| op.responses.values()) {
+                refs.response(resp);
+            }
+        }
+
+        let References {
+            defs, params, resp, ..
+        } = refs;
+        ResolvableApi {
+            paths,
+            definitions: retain(&api.definitions, &defs),
+            parameters: retain(&api.parameters, &params),
+            responses: retain(&api.responses, &resp),
+            ..api.clone()
+        }
+    }
+
+    fn has_includes(&self) -> bool {
+        !self.include_tags.is_empty()
+            || !self.include_paths.is_empty()
+            || !self.include_operations.is_empty()
+    }
+}
+
+/// Checks whether the given operation has any of the given tags, IDs
+/// or its path matches any of the given globs.
+fn matches_any<P, R>(
+    path: &str,
+    op: &Operation<P, R>,
+    tags: &[String],
+    globs: &[String],
+    ids: &[String],
+) -> bool {
+    op.tags.iter().any(|t| tags.contains(t))
+        || op.operation_id.as_ref().map(|id| ids.contains(id)) == Some(true)
+        || globs.iter().any(|g| path_matches(g, path))
+}
+
+/// Checks whether the given path matches the given glob.
+fn path_matches(glob: &str, path: &str) -> bool {
+    if glob_matches(glob.as_bytes(), path.as_bytes()) {
+        return true;
+    }
+
+    matches!(glob.strip_suffix("/**"), Some(g) if glob_matches(g.as_bytes(), path.as_bytes()))
+}
+
+fn glob_matches(glob: &[u8], s: &[u8]) -> bool {
+    match glob {
+        [] => s.is_empty(),
+        [b'*', b'*', rest @ ..] => (0..=s.len()).any(|i| glob_matches(rest, &s[i..])),
+        [b'*', rest @ ..] => (0..=s.len())
+            .take_while(|&i| i == 0 || s[i - 1] != b'/')
+            .any(|i| glob_matches(rest, &s[i..])),
+        [c, rest @ ..] => s.first() == Some(c) && glob_matches(rest, &s[1..]),
+    }
+}
+
+/// Returns the entries in the given map with the given names.
+fn retain<T: Clone>(map: &BTreeMap<String, T>, names: &BTreeSet<String>) -> BTreeMap<String, T> {
+    map.iter()
+        .filter(|(k, _)| names.contains(*k))
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+/// Collects the names of definitions, parameters and responses referred
+/// (transitively) from some operations.
+struct References<'a, S> {
+    api: &'a ResolvableApi<S>,
+    /// Subtypes of definitions (i.e., definitions extending them through `allOf`).
+    subtypes: BTreeMap<String, Vec<String>>,
+    defs: BTreeSet<String>,
+    params: BTreeSet<String>,
+    resp: BTreeSet<String>,
+}
+
+impl<'a, S: Schema> References<'a, S> {
+    fn new(api: &'a ResolvableApi<S>) -> Self {
+        let mut subtypes = BTreeMap::new();
+        for (name, schema) in &api.definitions {
+            let s = raw_schema(schema).read();
+            for base in s.all_of().into_iter().flatten() {
+                if let Some(b) = ref_name(base, DEF_REF_PREFIX) {
+                    subtypes
+                        .entry(b)
+                        .or_insert_with(Vec::new)
+                        .push(name.clone());
+                }
+            }
+        }
+
+        References {
+            api,
+            subtypes,
+            defs: BTreeSet::new(),
+            params: BTreeSet::new(),
+            resp: BTreeSet::new(),
+        }
+    }
+
+    fn parameter(&mut self, param: &Either<Reference, ResolvableParameter<S>>) {
+        let param = match param {
+            Either::Left(r) => match r.reference.strip_prefix(PARAM_REF_PREFIX) {
+                Some(name) if self.params.insert(name.into()) => {
+                    match self.api.parameters.get(name) {
+                        Some(p) => p,
+                        None => return,
+                    }
+                }
+                _ => return,
+            },
+            Either::Right(p) => p,
+        };
+
+        if let Some(s) = param.read().schema.as_ref() {
+            self.schema(s);
+        }
+    }
+
+    fn response(&mut self, resp: &Either<Reference, ResolvableResponse<S>>) {
+        let resp = match resp {
+            Either::Left(r) => match r.reference.strip_prefix(RESP_REF_PREFIX) {
+                Some(name) if self.resp.insert(name.into()) => match self.api.responses.get(name) {
+                    Some(r) => r,
+                    None => return,
+                },
+                _ => return,
+            },
+            Either::Right(r) => r,
+        };
+
+        if let Some(s) = resp.read().schema.as_ref() {
+            self.schema(s);
+        }
+    }
+
+    fn schema(&mut self, schema: &Resolvable<S>) {
+        if let Some(name) = ref_name(schema, DEF_REF_PREFIX) {
+            return self.definition(name);
+        }
+
+        let s = raw_schema(schema).read();
+        for prop in s.properties().into_iter().flat_map(|p| p.values()) {
+            self.schema(prop);
+        }
+
+        if let Some(s) = s.items() {
+            self.schema(s);
+        }
+
+        if let Some(Either::Right(s)) = s.additional_properties() {
+            self.schema(s);
+        }
+
+        for s in s.all_of().into_iter().flatten() {
+            self.schema(s);
+        }
+    }
+
+    fn definition(&mut self, name: String) {
+        if !self.defs.insert(name.clone()) {
+            return;
+        }
+
+        let api = self.api;
+        if let Some(schema) = api.definitions.get(&name) {
+            // Polymorphic definitions need their subtypes.
+            if raw_schema(schema).read().discriminator().is_some() {
+                for sub in self.subtypes.get(&name).cloned().unwrap_or_default() {
+                    self.definition(sub);
+                }
+            }
+
+            self.schema(schema);
+        }
+    }
+}
+
+/// Returns the name referred by the given schema (if it's a reference).
+fn ref_name<S: Schema>(schema: &Resolvable<S>, prefix: &str) -> Option<String> {
+    let s = raw_schema(schema).read();
+    s.reference()
+        .and_then(|r| r.strip_prefix(prefix))
+        .map(String::from)
+}
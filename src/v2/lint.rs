@@ -0,0 +1,677 @@
+//! Linting OpenAPI v2 specs.
+//!
+//! The linter runs a set of [rules](enum.Rule.html) over the (unresolved) spec
+//! and reports all the findings (along with the JSON pointer to the offending
+//! value) instead of stopping at the first error.
+//!
+//! ```rust,no_run
+//! use paperclip::v2::{self, lint::{self, LintConfig}, DefaultSchema, ResolvableApi};
+//!
+//! use std::fs::File;
+//!
+//! let mut fd = File::open("my_spec.yaml").unwrap(); // yaml or json
+//! let api: ResolvableApi<DefaultSchema> = v2::from_reader(&mut fd).unwrap();
+//! for finding in lint::lint(&api, &LintConfig::default()) {
+//!     println!("{}", finding);
+//! }
+//! ```
+//!
+//! Rules can be disabled (or their severities changed) through [`LintConfig`](struct.LintConfig.html),
+//! which can be deserialized from a YAML (or JSON) document like so:
+//!
+//! ```yaml
+//! rules:
+//!   missing-description: off
+//!   property-case: error
+//! ```
+
+use super::{
+    models::{
+        Api, CollectionFormat, DataType, Either, Parameter, ParameterIn, Reference, Resolvable,
+        ResolvableApi, ResolvableParameter, ResolvableResponse,
+    },
+    pointer_child, raw_schema, Schema, DEF_REF_PREFIX, PARAM_REF_PREFIX, RESP_REF_PREFIX,
+};
+use paperclip_core::ValidationError;
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+/// Rules supported by the linter.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    /// Parameter is invalid (body without schema, objects in non-body
+    /// parameters, special headers, etc.).
+    InvalidParameter,
+    /// Collection format of an array parameter isn't allowed in its location.
+    CollectionFormat,
+    /// Path is the same as another path (regardless of its templating).
+    DuplicatePath,
+    /// Parameters in the path template aren't defined (or the defined
+    /// path parameters aren't in the template).
+    PathParameters,
+    /// `$ref` points to something that doesn't exist in the spec.
+    MissingReference,
+    /// Operation doesn't have an `operationId`.
+    MissingOperationId,
+    /// `operationId` is shared by multiple operations.
+    DuplicateOperationId,
+    /// Definition isn't referred anywhere in the spec.
+    UnusedDefinition,
+    /// Operation doesn't have any responses, or a response doesn't have a description.
+    UndocumentedResponses,
+    /// Operation, parameter or definition doesn't have a description.
+    MissingDescription,
+    /// Property name isn't in camelCase.
+    PropertyCase,
+}
+
+impl Rule {
+    /// All the rules supported by the linter.
+    pub const ALL: &'static [Rule] = &[
+        Rule::InvalidParameter,
+        Rule::CollectionFormat,
+        Rule::DuplicatePath,
+        Rule::PathParameters,
+        Rule::MissingReference,
+        Rule::MissingOperationId,
+        Rule::DuplicateOperationId,
+        Rule::UnusedDefinition,
+        Rule::UndocumentedResponses,
+        Rule::MissingDescription,
+        Rule::PropertyCase,
+    ];
+
+    /// Name of this rule (as used in config).
+    pub fn name(self) -> &'static str {
+        match self {
+            Rule::InvalidParameter => "invalid-parameter",
+            Rule::CollectionFormat => "collection-format",
+            Rule::DuplicatePath => "duplicate-path",
+            Rule::PathParameters => "path-parameters",
+            Rule::MissingReference => "missing-reference",
+            Rule::MissingOperationId => "missing-operation-id",
+            Rule::DuplicateOperationId => "duplicate-operation-id",
+            Rule::UnusedDefinition => "unused-definition",
+            Rule::UndocumentedResponses => "undocumented-responses",
+            Rule::MissingDescription => "missing-description",
+            Rule::PropertyCase => "property-case",
+        }
+    }
+
+    /// Severity of this rule's findings (unless overridden in config).
+    ///
+    /// Rules which would fail resolution or codegen are errors,
+    /// while the rest are warnings.
+    pub fn default_severity(self) -> Severity {
+        match self {
+            Rule::InvalidParameter
+            | Rule::DuplicatePath
+            | Rule::PathParameters
+            | Rule::MissingReference
+            | Rule::DuplicateOperationId => Severity::Error,
+            _ => Severity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Severity of a finding.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        })
+    }
+}
+
+/// Level of a rule in config.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    /// Rule is disabled.
+    Off,
+    /// Rule is reported as a warning.
+    Warning,
+    /// Rule is reported as an error.
+    Error,
+}
+
+/// Linter configuration.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LintConfig {
+    /// Levels of rules (overriding their default severities).
+    #[serde(default)]
+    pub rules: BTreeMap<Rule, RuleLevel>,
+}
+
+impl LintConfig {
+    /// Returns the severity of the given rule, or `None` if it's disabled.
+    pub fn severity(&self, rule: Rule) -> Option<Severity> {
+        match self.rules.get(&rule) {
+            Some(RuleLevel::Off) => None,
+            Some(RuleLevel::Warning) => Some(Severity::Warning),
+            Some(RuleLevel::Error) => Some(Severity::Error),
+            None => Some(rule.default_severity()),
+        }
+    }
+}
+
+/// Problem found by the linter.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Finding {
+    /// Rule which reported this finding.
+    pub rule: Rule,
+    /// Severity of this finding.
+    pub severity: Severity,
+    /// JSON pointer to the offending value in the spec.
+    pub pointer: String,
+    /// Description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}[{}] {}: {}",
+            self.severity, self.rule, self.pointer, self.message
+        )
+    }
+}
+
+/// Lints the given spec using the given config and returns all the findings.
+///
+/// This is meant for specs which haven't been resolved yet, because we need
+/// the `$ref` values for checking references.
+pub fn lint<S: Schema>(api: &ResolvableApi<S>, config: &LintConfig) -> Vec<Finding> {
+    let mut linter = Linter {
+        api,
+        config,
+        findings: vec![],
+        refs: vec![],
+    };
+
+    linter.lint_paths();
+    for (name, param) in &api.parameters {
+        let pointer = pointer_child(&pointer_child("", "parameters"), name);
+        // These aren't bound to any path (yet).
+        linter.lint_parameter(&param.read(), &pointer, None);
+    }
+
+    for (name, resp) in &api.responses {
+        let pointer = pointer_child(&pointer_child("", "responses"), name);
+        linter.lint_response(name, resp, &pointer);
+    }
+
+    for (name, schema) in &api.definitions {
+        let pointer = pointer_child(&pointer_child("", "definitions"), name);
+        let has_description = raw_schema(schema)
+            .read()
+            .description()
+            .filter(|d| !d.is_empty())
+            .is_some();
+        if !has_description {
+            linter.report(
+                Rule::MissingDescription,
+                &pointer,
+                format!("Definition {:?} doesn't have a description", name),
+            );
+        }
+
+        linter.lint_schema(schema, &pointer, Some(name));
+    }
+
+    linter.lint_references();
+    linter.findings
+}
+
+/// Lints the spec and collects the findings.
+struct Linter<'a, S> {
+    api: &'a ResolvableApi<S>,
+    config: &'a LintConfig,
+    findings: Vec<Finding>,
+    /// `$ref` values found in schemas, along with their pointers
+    /// and the definitions in which they were found.
+    refs: Vec<SchemaRef<'a>>,
+}
+
+/// `$ref` value found in some schema.
+struct SchemaRef<'a> {
+    pointer: String,
+    reference: String,
+    definition: Option<&'a str>,
+}
+
+impl<'a, S: Schema> Linter<'a, S> {
+    /// Adds a finding for the given rule (if it's enabled).
+    fn report(&mut self, rule: Rule, pointer: &str, message: String) {
+        if let Some(severity) = self.config.severity(rule) {
+            self.findings.push(Finding {
+                rule,
+                severity,
+                pointer: pointer.into(),
+                message,
+            });
+        }
+    }
+
+    /// Lints the paths and their operations.
+    fn lint_paths(&mut self) {
+        let mut paths = HashMap::new();
+        let mut operation_ids = HashMap::new();
+        for (path, item) in &self.api.paths {
+            let item_pointer = pointer_child(&pointer_child("", "paths"), path);
+            let (path_fmt, template_params) = Api::<(), (), ()>::path_template(path);
+            let template_params = template_params.into_iter().collect::<BTreeSet<_>>();
+            if let Some(other) = paths.insert(path_fmt, path) {
+                self.report(
+                    Rule::DuplicatePath,
+                    &item_pointer,
+                    format!(
+                        "Path {:?} is the same as {:?} (regardless of templating)",
+                        path, other
+                    ),
+                );
+            }
+
+            let mut path_params = BTreeSet::new();
+            for (i, param) in item.parameters.iter().enumerate() {
+                let pointer =
+                    pointer_child(&pointer_child(&item_pointer, "parameters"), &i.to_string());
+                if let Some(p) = self.lint_parameter_ref(param, &pointer, path) {
+                    path_params.insert(p);
+                }
+            }
+
+            for (method, op) in &item.methods {
+                let op_pointer = pointer_child(&item_pointer, &method.to_string().to_lowercase());
+                match op.operation_id.as_ref() {
+                    Some(id) => {
+                        if let Some(other) = operation_ids.insert(id, op_pointer.clone()) {
+                            self.report(
+                                Rule::DuplicateOperationId,
+                                &pointer_child(&op_pointer, "operationId"),
+                                format!("Operation ID {:?} is already used by {:?}", id, other),
+                            );
+                        }
+                    }
+                    None => self.report(
+                        Rule::MissingOperationId,
+                        &op_pointer,
+                        "Operation doesn't have an operation ID".into(),
+                    ),
+                }
+
+                if op.description.as_ref().filter(|d| !d.is_empty()).is_none() {
+                    self.report(
+                        Rule::MissingDescription,
+                        &op_pointer,
+                        "Operation doesn't have a description".into(),
+                    );
+                }
+
+                let mut params = path_params.clone();
+                for (i, param) in op.parameters.iter().enumerate() {
+                    let pointer =
+                        pointer_child(&pointer_child(&op_pointer, "parameters"), &i.to_string());
+                    if let Some(p) = self.lint_parameter_ref(param, &pointer, path) {
+                        params.insert(p);
+                    }
+                }
+
+                let missing = &template_params - &params;
+                if !missing.is_empty() {
+                    self.report(
+                        Rule::PathParameters,
+                        &op_pointer,
+                        format!(
+                            "Parameter(s) {:?} aren't defined for templated path {:?}",
+                            missing, path
+                        ),
+                    );
+                }
+
+                for name in &params - &template_params {
+                    self.report(
+                        Rule::PathParameters,
+                        &op_pointer,
+                        format!(
+                            "Path parameter {:?} doesn't exist in templated path {:?}",
+                            name, path
+                        ),
+                    );
+                }
+
+                let responses_pointer = pointer_child(&op_pointer, "responses");
+                if op.responses.is_empty() {
+                    self.report(
+                        Rule::UndocumentedResponses,
+                        &responses_pointer,
+                        "Operation doesn't have any responses".into(),
+                    );
+                }
+
+                for (code, resp) in &op.responses {
+                    let pointer = pointer_child(&responses_pointer, code);
+                    match resp {
+                        Either::Left(r) => {
+                            let api = self.api;
+                            let exists = |n: &str| api.responses.contains_key(n);
+                            self.lint_reference(r, RESP_REF_PREFIX, exists, &pointer);
+                        }
+                        Either::Right(r) => self.lint_response(code, r, &pointer),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Lints the (possibly referenced) parameter and returns its name
+    /// if it's a path parameter.
+    fn lint_parameter_ref(
+        &mut self,
+        param: &'a Either<Reference, ResolvableParameter<S>>,
+        pointer: &str,
+        path: &str,
+    ) -> Option<String> {
+        let api = self.api;
+        let p = match param {
+            Either::Left(r) => {
+                let exists = |n: &str| api.parameters.contains_key(n);
+                let name = self.lint_reference(r, PARAM_REF_PREFIX, exists, pointer)?;
+                api.parameters[name].read()
+            }
+            Either::Right(p) => {
+                let p = p.read();
+                self.lint_parameter(&p, pointer, Some(path));
+                p
+            }
+        };
+
+        if p.in_ == ParameterIn::Path {
+            Some(p.name.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Lints the given parameter (along with the path it's associated with,
+    /// if any).
+    fn lint_parameter(
+        &mut self,
+        param: &Parameter<Resolvable<S>>,
+        pointer: &str,
+        path: Option<&str>,
+    ) {
+        if let Err(e) = param.check(path.unwrap_or_default()) {
+            let message = match path {
+                Some(_) => e.to_string(),
+                None => global_parameter_message(&e),
+            };
+
+            self.report(Rule::InvalidParameter, pointer, message);
+        }
+
+        if param.data_type == Some(DataType::Array) {
+            let mut formats = vec![param.collection_format.unwrap_or_default()];
+            let mut items = param.items.as_ref();
+            while let Some(i) = items.filter(|i| i.data_type == Some(DataType::Array)) {
+                formats.push(i.collection_format.unwrap_or_default());
+                items = i.items.as_deref();
+            }
+
+            if !CollectionFormat::is_valid_nesting(param.in_, &formats) {
+                self.report(
+                    Rule::CollectionFormat,
+                    pointer,
+                    format!(
+                        "Parameter {:?} in {:?} allows multiple instances only for \
+                         the root array in query or form data",
+                        param.name, param.in_
+                    ),
+                );
+            }
+        }
+
+        if param
+            .description
+            .as_ref()
+            .filter(|d| !d.is_empty())
+            .is_none()
+        {
+            self.report(
+                Rule::MissingDescription,
+                pointer,
+                format!("Parameter {:?} doesn't have a description", param.name),
+            );
+        }
+
+        if let Some(schema) = param.schema.as_ref() {
+            self.lint_schema(schema, &pointer_child(pointer, "schema"), None);
+        }
+    }
+
+    /// Lints the given response.
+    fn lint_response(&mut self, code: &str, resp: &'a ResolvableResponse<S>, pointer: &str) {
+        let r = resp.read();
+        if r.description.as_ref().filter(|d| !d.is_empty()).is_none() {
+            self.report(
+                Rule::UndocumentedResponses,
+                pointer,
+                format!("Response {:?} doesn't have a description", code),
+            );
+        }
+
+        if let Some(schema) = r.schema.as_ref() {
+            self.lint_schema(schema, &pointer_child(pointer, "schema"), None);
+        }
+    }
+
+    /// Lints the given schema (and its children) in some definition.
+    fn lint_schema(&mut self, schema: &Resolvable<S>, pointer: &str, definition: Option<&'a str>) {
+        let s = raw_schema(schema).read();
+        if let Some(r) = s.reference() {
+            self.refs.push(SchemaRef {
+                pointer: pointer.into(),
+                reference: r.into(),
+                definition,
+            });
+            return;
+        }
+
+        if let Some(props) = s.properties() {
+            for (name, prop) in props {
+                let pointer = pointer_child(&pointer_child(pointer, "properties"), name);
+                if !is_camel_case(name) {
+                    self.report(
+                        Rule::PropertyCase,
+                        &pointer,
+                        format!("Property {:?} isn't in camelCase", name),
+                    );
+                }
+
+                self.lint_schema(prop, &pointer, definition);
+            }
+        }
+
+        if let Some(items) = s.items() {
+            self.lint_schema(items, &pointer_child(pointer, "items"), definition);
+        }
+
+        if let Some(Either::Right(extra)) = s.additional_properties() {
+            self.lint_schema(
+                extra,
+                &pointer_child(pointer, "additionalProperties"),
+                definition,
+            );
+        }
+
+        if let Some(all_of) = s.all_of() {
+            for (i, schema) in all_of.iter().enumerate() {
+                let pointer = pointer_child(&pointer_child(pointer, "allOf"), &i.to_string());
+                self.lint_schema(schema, &pointer, definition);
+            }
+        }
+    }
+
+    /// Checks whether the given reference (to a parameter or a response) exists
+    /// in the spec, and returns the name of the referred object.
+    fn lint_reference<'r>(
+        &mut self,
+        r: &'r Reference,
+        prefix: &str,
+        exists: impl Fn(&str) -> bool,
+        pointer: &str,
+    ) -> Option<&'r str> {
+        let name = r.reference.strip_prefix(prefix);
+        match name {
+            Some(n) if exists(n) => return name,
+            Some(_) => {
+                let err = ValidationError::MissingReference(r.reference.clone());
+                self.report(Rule::MissingReference, pointer, err.to_string());
+            }
+            None => {
+                let err = ValidationError::InvalidRefURI(r.reference.clone());
+                self.report(Rule::MissingReference, pointer, err.to_string());
+            }
+        }
+
+        None
+    }
+
+    /// Checks the `$ref` values collected from schemas for missing
+    /// and unused definitions.
+    fn lint_references(&mut self) {
+        let defs = &self.api.definitions;
+        let refs = std::mem::take(&mut self.refs);
+        // Definitions used outside definitions and the ones used by each definition.
+        let mut used = HashSet::new();
+        let mut deps = HashMap::<_, Vec<_>>::new();
+        for r in &refs {
+            // References to other documents are handled by the resolver.
+            if !r.reference.starts_with('#') {
+                continue;
+            }
+
+            let def = r
+                .reference
+                .strip_prefix(DEF_REF_PREFIX)
+                .map(|n| defs.get_key_value(n));
+            let name = match def {
+                Some(Some((name, _))) => name.as_str(),
+                Some(None) => {
+                    let err = ValidationError::MissingReference(r.reference.clone());
+                    self.report(Rule::MissingReference, &r.pointer, err.to_string());
+                    continue;
+                }
+                None => {
+                    let err = ValidationError::InvalidRefURI(r.reference.clone());
+                    self.report(Rule::MissingReference, &r.pointer, err.to_string());
+                    continue;
+                }
+            };
+
+            match r.definition {
+                Some(d) if d != name => deps.entry(d).or_default().push(name),
+                Some(_) => (),
+                None => {
+                    used.insert(name);
+                }
+            }
+        }
+
+        // Walk through the dependencies of the used definitions (and the subtypes
+        // of used polymorphic definitions) until there's nothing more to add.
+        let mut queue = used.iter().cloned().collect::<Vec<_>>();
+        loop {
+            while let Some(name) = queue.pop() {
+                for &dep in deps.get(name).into_iter().flatten() {
+                    if used.insert(dep) {
+                        queue.push(dep);
+                    }
+                }
+            }
+
+            for (name, schema) in defs {
+                if used.contains(name.as_str()) {
+                    continue;
+                }
+
+                let s = raw_schema(schema).read();
+                let extends_used = s.all_of().into_iter().flatten().any(|p| {
+                    let p = raw_schema(p).read();
+                    p.reference()
+                        .and_then(|r| r.strip_prefix(DEF_REF_PREFIX))
+                        .filter(|&r| used.contains(r))
+                        .and_then(|r| defs.get(r))
+                        .filter(|d| raw_schema(d).read().discriminator().is_some())
+                        .is_some()
+                });
+
+                if extends_used {
+                    used.insert(name.as_str());
+                    queue.push(name.as_str());
+                }
+            }
+
+            if queue.is_empty() {
+                break;
+            }
+        }
+
+        for name in defs.keys() {
+            if !used.contains(name.as_str()) {
+                let pointer = pointer_child(&pointer_child("", "definitions"), name);
+                self.report(
+                    Rule::UnusedDefinition,
+                    &pointer,
+                    format!("Definition {:?} isn't used anywhere", name),
+                );
+            }
+        }
+    }
+}
+
+/// Checks whether the given name is in camelCase.
+fn is_camel_case(name: &str) -> bool {
+    name.chars()
+        .next()
+        .filter(|c| c.is_ascii_lowercase())
+        .is_some()
+        && name.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Returns the message for the given error from checking a global parameter
+/// (which isn't associated with any path).
+fn global_parameter_message(err: &ValidationError) -> String {
+    match err {
+        ValidationError::MissingSchemaForBodyParameter(name, _) => {
+            format!("Parameter {:?} is a body but the schema is missing", name)
+        }
+        ValidationError::InvalidHeader(name, _) => {
+            format!("Header parameter {:?} is not allowed", name)
+        }
+        ValidationError::InvalidParameterType(name, _, ty, in_) => format!(
+            "Parameter {:?} has specified {:?} type, but it's invalid for {:?} parameters",
+            name, ty, in_
+        ),
+        e => e.to_string(),
+    }
+}
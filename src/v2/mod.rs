@@ -83,45 +83,95 @@
 //! emitter.generate(&api).unwrap(); // generate code!
 //! ```
 
+#[cfg(feature = "codegen")]
+pub mod bundle;
 #[cfg(feature = "codegen")]
 pub mod codegen;
+pub mod diff;
+pub mod filter;
+pub mod lint;
 
-use crate::error::PaperClipError;
-use paperclip_core::v2::models::SpecFormat;
+use crate::error::{PaperClipError, SpecError};
+use paperclip_core::v2::models::{Resolvable, SpecFormat, UTF8_BOM};
+use parking_lot::RwLock;
 use serde::Deserialize;
 
-use std::io::{Read, Seek, SeekFrom};
+use std::io::Read;
+use std::sync::Arc;
 
 #[cfg(feature = "codegen")]
 pub use self::codegen::{DefaultEmitter, Emitter, EmitterState};
 pub use paperclip_core::im;
+#[cfg(feature = "codegen")]
+pub use paperclip_core::v2::loader;
 pub use paperclip_core::v2::models::{self, DefaultSchema, ResolvableApi};
 pub use paperclip_core::v2::schema::{self, Schema};
 
 /// Deserialize the schema from the given reader. Currently, this only supports
-/// JSON and YAML formats.
-pub fn from_reader<R, S>(mut reader: R) -> Result<ResolvableApi<S>, PaperClipError>
+/// JSON and YAML formats, which are detected from the contents
+/// (see [`SpecFormat::sniff`](models/enum.SpecFormat.html#method.sniff)).
+pub fn from_reader<R, S>(reader: R) -> Result<ResolvableApi<S>, PaperClipError>
 where
-    R: Read + Seek,
+    R: Read,
     for<'de> S: Deserialize<'de> + Schema,
 {
-    let mut buf = [0; 1];
-    reader.read_exact(&mut buf)?;
-    reader.seek(SeekFrom::Start(0))?;
-
-    // FIXME: Support whitespaces
-    let (mut api, fmt) = if buf[0] == b'{' {
-        (
-            serde_json::from_reader::<_, ResolvableApi<S>>(reader)?,
-            SpecFormat::Json,
-        )
-    } else {
-        (
-            serde_yaml::from_reader::<_, ResolvableApi<S>>(reader)?,
-            SpecFormat::Yaml,
-        )
-    };
+    let (mut api, fmt) = deserialize_spec::<_, ResolvableApi<S>>(reader, None)?;
+    api.spec_format = fmt;
+    Ok(api)
+}
 
+/// Deserialize the schema in the given format from the given reader.
+pub fn from_reader_with_format<R, S>(
+    reader: R,
+    format: SpecFormat,
+) -> Result<ResolvableApi<S>, PaperClipError>
+where
+    R: Read,
+    for<'de> S: Deserialize<'de> + Schema,
+{
+    let (mut api, fmt) = deserialize_spec::<_, ResolvableApi<S>>(reader, Some(format))?;
     api.spec_format = fmt;
     Ok(api)
 }
+
+/// Deserializes the spec in the given reader into the given type. If the format
+/// isn't specified, then it's sniffed from the contents. This is shared by all
+/// supported OpenAPI versions.
+pub(crate) fn deserialize_spec<R, T>(
+    mut reader: R,
+    format: Option<SpecFormat>,
+) -> Result<(T, SpecFormat), PaperClipError>
+where
+    R: Read,
+    for<'de> T: Deserialize<'de>,
+{
+    let mut contents = vec![];
+    reader.read_to_end(&mut contents)?;
+    let contents = contents.strip_prefix(UTF8_BOM).unwrap_or(&contents);
+    let format = format.unwrap_or_else(|| SpecFormat::sniff(contents));
+
+    let value = match format {
+        SpecFormat::Json => {
+            let mut de = serde_json::Deserializer::from_slice(contents);
+            let value = serde_path_to_error::deserialize(&mut de).map_err(SpecError::from_json)?;
+            de.end().map_err(SpecError::from_json_end)?;
+            value
+        }
+        SpecFormat::Yaml => {
+            let de = serde_yaml::Deserializer::from_slice(contents);
+            serde_path_to_error::deserialize(de).map_err(SpecError::from_yaml)?
+        }
+    };
+
+    Ok((value, format))
+}
+
+pub(crate) use paperclip_core::{pointer_child, DEF_REF_PREFIX, PARAM_REF_PREFIX, RESP_REF_PREFIX};
+
+/// Returns the schema as it was in the spec (i.e., before resolution).
+pub(crate) fn raw_schema<S>(schema: &Resolvable<S>) -> &Arc<RwLock<S>> {
+    match schema {
+        Resolvable::Raw(s) => s,
+        Resolvable::Resolved { old, .. } => old,
+    }
+}
@@ -0,0 +1,42 @@
+//! Utilities related to the [OpenAPI v3 specification](https://github.com/OAI/OpenAPI-Specification/blob/master/versions/3.0.3.md).
+//!
+//! Currently, this only supports parsing the spec into Rust structs,
+//! which can then be inspected (code generation is limited to v2).
+//!
+//! ```rust,no_run
+//! use paperclip::v3::{self, models::Version};
+//!
+//! use std::fs::File;
+//!
+//! let mut fd = File::open("my_spec.yaml").unwrap(); // yaml or json
+//! let api = v3::from_reader(&mut fd).unwrap();
+//! assert_eq!(api.openapi, Version::V3_0_3);
+//! ```
+
+use crate::error::PaperClipError;
+use paperclip_core::v2::models::SpecFormat;
+
+use std::io::Read;
+
+pub use paperclip_core::v3::models::{self, Api, Schema};
+
+/// Deserialize the v3 spec from the given reader. Like v2, this only
+/// supports JSON and YAML formats, which are detected from the contents.
+pub fn from_reader<R>(reader: R) -> Result<Api, PaperClipError>
+where
+    R: Read,
+{
+    let (mut api, fmt) = crate::v2::deserialize_spec::<_, Api>(reader, None)?;
+    api.spec_format = fmt;
+    Ok(api)
+}
+
+/// Deserialize the v3 spec in the given format from the given reader.
+pub fn from_reader_with_format<R>(reader: R, format: SpecFormat) -> Result<Api, PaperClipError>
+where
+    R: Read,
+{
+    let (mut api, fmt) = crate::v2::deserialize_spec::<_, Api>(reader, Some(format))?;
+    api.spec_format = fmt;
+    Ok(api)
+}
@@ -0,0 +1,44 @@
+//! Helpers shared by the codegen tests.
+#![allow(dead_code)]
+
+use paperclip::v2::{
+    self,
+    codegen::{CrateMeta, DefaultEmitter, EmitMode, Emitter, EmitterState},
+    models::{DefaultSchema, ResolvableApi},
+};
+
+use std::io::Cursor;
+use std::path::PathBuf;
+
+/// Returns a temporary directory (unique to this process) for generating code.
+pub fn temp_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("paperclip-{}-{}", name, std::process::id()))
+}
+
+/// Returns the metadata for a crate named "store" in the given mode.
+pub fn store_meta(mode: EmitMode) -> CrateMeta {
+    let mut meta = CrateMeta::default();
+    meta.name = Some("store".into());
+    meta.authors = Some(vec!["Me <me@example.com>".into()]);
+    meta.mode = mode;
+    meta
+}
+
+/// Deserializes the given spec (without resolving it).
+pub fn raw_spec(spec: &[u8]) -> ResolvableApi<DefaultSchema> {
+    v2::from_reader(Cursor::new(spec)).expect("deserializing spec")
+}
+
+/// Resolves the given spec and generates code for it in the given directory
+/// after updating the emitter state with the given function.
+pub fn generate<F>(spec: &[u8], dir: PathBuf, configure: F) -> PathBuf
+where
+    F: FnOnce(&mut EmitterState),
+{
+    let api = raw_spec(spec).resolve().expect("resolution");
+    let mut state = EmitterState::default();
+    state.working_dir = dir.clone();
+    configure(&mut state);
+    DefaultEmitter::from(state).generate(&api).expect("codegen");
+    dir
+}
@@ -1304,6 +1304,329 @@ fn test_security_app() {
     );
 }
 
+#[test]
+fn test_v3_spec_app() {
+    #[derive(Apiv2Security, Deserialize)]
+    #[openapi(
+        apiKey,
+        alias = "JWT",
+        in = "header",
+        name = "Authorization",
+        description = "Use format 'Bearer TOKEN'"
+    )]
+    struct AccessToken;
+
+    impl FromRequest for AccessToken {
+        type Future = Ready<Result<Self, Self::Error>>;
+        type Error = Error;
+        type Config = ();
+
+        fn from_request(_: &HttpRequest, _payload: &mut actix_web::dev::Payload) -> Self::Future {
+            ready(Ok(Self {}))
+        }
+    }
+
+    #[api_v2_operation]
+    async fn echo_pet(_: AccessToken, body: web::Json<Pet>) -> web::Json<Pet> {
+        body
+    }
+
+    #[api_v2_operation]
+    async fn get_pet(_id: web::Path<u64>) -> web::Json<Pet> {
+        web::Json(Pet::default())
+    }
+
+    run_and_check_app(
+        move || {
+            App::new()
+                .wrap_api()
+                .with_json_spec_v3_at("/api/spec")
+                .service(web::resource("/api/echo").route(web::post().to(echo_pet)))
+                .service(web::resource("/api/pets/{id}").route(web::get().to(get_pet)))
+                .build()
+        },
+        |addr| {
+            let resp = CLIENT
+                .get(&format!("http://{}/api/spec", addr))
+                .send()
+                .expect("request failed?");
+
+            check_json(
+                resp,
+                json!({
+                  "openapi": "3.0.3",
+                  "info": {"title": "", "version": ""},
+                  "components": {
+                    "schemas": {
+                      "Pet": {
+                        "properties": {
+                          "class": {
+                            "enum": ["dog", "cat", "other"],
+                            "type": "string"
+                          },
+                          "id": {
+                            "format": "int64",
+                            "type": "integer"
+                          },
+                          "name": {
+                            "description": "Pick a good one.",
+                            "type": "string"
+                          },
+                          "updatedOn": {
+                            "format": "date-time",
+                            "type": "string"
+                          },
+                          "uuid": {
+                            "format": "uuid",
+                            "type": "string"
+                          }
+                        },
+                        "required": ["class", "name"]
+                      }
+                    },
+                    "securitySchemes": {
+                      "JWT": {
+                        "description": "Use format 'Bearer TOKEN'",
+                        "in": "header",
+                        "name": "Authorization",
+                        "type": "apiKey"
+                      }
+                    }
+                  },
+                  "paths": {
+                    "/api/echo": {
+                      "post": {
+                        "requestBody": {
+                          "content": {
+                            "application/json": {
+                              "schema": {"$ref": "#/components/schemas/Pet"}
+                            }
+                          },
+                          "required": true
+                        },
+                        "responses": {
+                          "200": {
+                            "content": {
+                              "application/json": {
+                                "schema": {"$ref": "#/components/schemas/Pet"}
+                              }
+                            },
+                            "description": "OK"
+                          }
+                        },
+                        "security": [{"JWT": []}]
+                      }
+                    },
+                    "/api/pets/{id}": {
+                      "get": {
+                        "responses": {
+                          "200": {
+                            "content": {
+                              "application/json": {
+                                "schema": {"$ref": "#/components/schemas/Pet"}
+                              }
+                            },
+                            "description": "OK"
+                          }
+                        }
+                      },
+                      "parameters": [{
+                        "in": "path",
+                        "name": "id",
+                        "required": true,
+                        "schema": {"format": "int64", "type": "integer"}
+                      }]
+                    }
+                  }
+                }),
+            );
+        },
+    );
+}
+
+#[test]
+fn test_request_validation() {
+    #[api_v2_operation]
+    async fn echo_pet(body: web::Json<Pet>) -> web::Json<Pet> {
+        body
+    }
+
+    #[api_v2_operation]
+    async fn get_pet(_id: web::Path<u64>) -> web::Json<Pet> {
+        web::Json(Pet::default())
+    }
+
+    run_and_check_app(
+        move || {
+            let app = App::new().wrap_api();
+            let validator = app.request_validator();
+            app.wrap(validator)
+                .service(web::resource("/api/echo").route(web::post().to(echo_pet)))
+                .service(web::resource("/api/pets/{id}").route(web::get().to(get_pet)))
+                .build()
+        },
+        |addr| {
+            let resp = CLIENT
+                .post(&format!("http://{}/api/echo", addr))
+                .json(&json!({"name": "Milo", "class": "dog", "id": 5, "uuid": null}))
+                .send()
+                .expect("request failed?");
+            check_json(
+                resp,
+                json!({"name": "Milo", "class": "dog", "id": 5, "updatedOn": null, "uuid": null}),
+            );
+
+            let resp = CLIENT
+                .post(&format!("http://{}/api/echo", addr))
+                .json(&json!({"class": "fish", "id": "5"}))
+                .send()
+                .expect("request failed?");
+            assert_eq!(resp.status().as_u16(), 400);
+            assert_eq!(
+                resp.json::<serde_json::Value>().expect("json error"),
+                json!({
+                    "errors": [{
+                        "pointer": "/body/name",
+                        "message": "missing required property"
+                    }, {
+                        "pointer": "/body/class",
+                        "message": "value is not one of the allowed values"
+                    }, {
+                        "pointer": "/body/id",
+                        "message": "expected integer"
+                    }]
+                })
+            );
+
+            let resp = CLIENT
+                .get(&format!("http://{}/api/pets/milo", addr))
+                .send()
+                .expect("request failed?");
+            assert_eq!(resp.status().as_u16(), 400);
+            assert_eq!(
+                resp.json::<serde_json::Value>().expect("json error"),
+                json!({
+                    "errors": [{
+                        "pointer": "/path/id",
+                        "message": "expected integer"
+                    }]
+                })
+            );
+
+            let resp = CLIENT
+                .get(&format!("http://{}/api/pets/25", addr))
+                .send()
+                .expect("request failed?");
+            assert_eq!(resp.status().as_u16(), 200);
+        },
+    );
+}
+
+#[test]
+fn test_request_validation_body_limit() {
+    #[api_v2_operation]
+    async fn echo_pet(body: web::Json<Pet>) -> web::Json<Pet> {
+        body
+    }
+
+    run_and_check_app(
+        move || {
+            let app = App::new().wrap_api();
+            let validator = app.request_validator().limit(64);
+            app.wrap(validator)
+                .app_data(web::JsonConfig::default().content_type(|_| true))
+                .service(web::resource("/api/echo").route(web::post().to(echo_pet)))
+                .build()
+        },
+        |addr| {
+            let pet = json!({"name": "x".repeat(100), "class": "dog"});
+            let resp = CLIENT
+                .post(&format!("http://{}/api/echo", addr))
+                .json(&pet)
+                .send()
+                .expect("request failed?");
+            assert_eq!(resp.status().as_u16(), 413);
+
+            // Non-JSON bodies aren't buffered (or validated).
+            let resp = CLIENT
+                .post(&format!("http://{}/api/echo", addr))
+                .header("content-type", "text/plain")
+                .body(pet.to_string())
+                .send()
+                .expect("request failed?");
+            check_json(
+                resp,
+                json!({"name": "x".repeat(100), "class": "dog", "id": null, "updatedOn": null, "uuid": null}),
+            );
+        },
+    );
+}
+
+#[test]
+fn test_response_validation() {
+    #[derive(Apiv2Schema)]
+    struct Drifting {
+        name: String,
+    }
+
+    impl serde::Serialize for Drifting {
+        fn serialize<S: serde::Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+            json!({ "name": self.name.len() }).serialize(ser)
+        }
+    }
+
+    #[api_v2_operation]
+    async fn get_pet() -> web::Json<Pet> {
+        web::Json(Pet::default())
+    }
+
+    #[api_v2_operation]
+    async fn get_drifting() -> web::Json<Drifting> {
+        web::Json(Drifting {
+            name: "Milo".into(),
+        })
+    }
+
+    run_and_check_app(
+        move || {
+            let app = App::new().wrap_api();
+            let validator = app.response_validator().fail_on_mismatch(true);
+            app.wrap(validator)
+                .service(web::resource("/api/pet").route(web::get().to(get_pet)))
+                .service(web::resource("/api/drifting").route(web::get().to(get_drifting)))
+                .build()
+        },
+        |addr| {
+            let resp = CLIENT
+                .get(&format!("http://{}/api/pet", addr))
+                .send()
+                .expect("request failed?");
+            check_json(
+                resp,
+                json!({"name": "", "class": "other", "id": null, "updatedOn": null, "uuid": null}),
+            );
+
+            let resp = CLIENT
+                .get(&format!("http://{}/api/drifting", addr))
+                .send()
+                .expect("request failed?");
+            assert_eq!(resp.status().as_u16(), 500);
+            assert_eq!(
+                resp.json::<serde_json::Value>().expect("json error"),
+                json!({
+                    "method": "GET",
+                    "path": "/api/drifting",
+                    "status": 200,
+                    "errors": [{
+                        "pointer": "/body/name",
+                        "message": "expected string"
+                    }]
+                })
+            );
+        },
+    );
+}
+
 fn run_and_check_app<F, G, T, B, U>(factory: F, check: G) -> U
 where
     F: Fn() -> App<T, B> + Clone + Send + Sync + 'static,
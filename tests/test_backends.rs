@@ -0,0 +1,140 @@
+mod common;
+
+use paperclip::v2::codegen::{EmitMode, HttpBackends};
+
+use std::fs;
+use std::path::PathBuf;
+
+const SPEC: &[u8] = b"
+swagger: \"2.0\"
+info:
+  title: Store
+  version: \"1.0.0\"
+definitions:
+  Order:
+    type: object
+    properties:
+      id:
+        type: integer
+        format: int64
+    required: [id]
+paths:
+  /orders:
+    get:
+      operationId: listOrders
+      responses:
+        \"200\":
+          description: Orders.
+          schema:
+            type: array
+            items:
+              $ref: \"#/definitions/Order\"
+";
+
+fn generate(name: &str, backends: HttpBackends, blocking: bool) -> PathBuf {
+    common::generate(SPEC, common::temp_dir(name), |state| {
+        state.http_backends = backends;
+        let mut meta = common::store_meta(EmitMode::Crate);
+        meta.blocking = blocking;
+        state.set_meta(meta);
+    })
+}
+
+#[test]
+fn test_backends() {
+    let backends = HttpBackends {
+        hyper: true,
+        awc: true,
+    };
+    let dir = generate("backends", backends, false);
+    let lib = fs::read_to_string(dir.join("lib.rs")).unwrap();
+    assert!(lib.contains(
+        "
+    #[cfg(feature = \"reqwest\")]
+    #[async_trait::async_trait]
+    impl ApiClient for reqwest::Client {"
+    ));
+    assert!(lib.contains(
+        "
+        #[cfg(feature = \"reqwest\")]
+        #[error(\"An error has occurred while performing the API request: {}\", _0)]
+        Reqwest(reqwest::Error),"
+    ));
+    assert!(lib.contains("impl Request for HttpRequest {"));
+    assert!(lib.contains("impl Form for MultipartForm {"));
+    assert!(lib.contains(
+        "
+    #[cfg(feature = \"hyper\")]
+    #[async_trait::async_trait]
+    impl<C> ApiClient for hyper::Client<C>"
+    ));
+    assert!(lib.contains(
+        "
+    #[cfg(feature = \"awc\")]
+    #[async_trait::async_trait]
+    impl ApiClient for AwcClient {"
+    ));
+
+    let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("serde_urlencoded = \"0.7\""));
+    assert!(manifest
+        .contains("tokio = { version = \"0.2\", features = [\"fs\", \"io-util\", \"rt-util\"] }"));
+    assert!(manifest.contains(
+        "
+reqwest = { version = \"0.10\", features = [\"stream\", \"json\"], optional = true }
+hyper = { version = \"0.13\", optional = true }
+awc = { version = \"1.0\", optional = true }
+
+[features]
+default = [\"reqwest\"]
+
+[workspace]"
+    ));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_hyper_backend_only() {
+    let backends = HttpBackends {
+        hyper: true,
+        ..HttpBackends::default()
+    };
+    let dir = generate("hyper-backend", backends, false);
+    let lib = fs::read_to_string(dir.join("lib.rs")).unwrap();
+    assert!(lib.contains("impl<C> ApiClient for hyper::Client<C>"));
+    assert!(!lib.contains("AwcClient"));
+    assert!(!lib.contains("Awc(String)"));
+
+    let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("hyper = "));
+    assert!(!manifest.contains("awc"));
+    assert!(!manifest.contains("rt-util"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_no_backends() {
+    let dir = generate("no-backends", HttpBackends::default(), false);
+    let lib = fs::read_to_string(dir.join("lib.rs")).unwrap();
+    assert!(!lib.contains("#[cfg(feature"));
+    assert!(!lib.contains("HttpRequest"));
+    let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+    assert!(!manifest.contains("optional"));
+    assert!(!manifest.contains("[features]"));
+    let _ = fs::remove_dir_all(&dir);
+
+    // Backends are ignored for blocking clients.
+    let backends = HttpBackends {
+        hyper: true,
+        awc: true,
+    };
+    let dir = generate("blocking-backends", backends, true);
+    let lib = fs::read_to_string(dir.join("lib.rs")).unwrap();
+    assert!(!lib.contains("hyper"));
+    assert!(!lib.contains("awc"));
+    let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+    assert!(!manifest.contains("[features]"));
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use paperclip::v2::codegen::{EmitMode, FormatTypes};
+
+use std::fs;
+use std::path::PathBuf;
+
+const SPEC: &[u8] = b"
+swagger: \"2.0\"
+info:
+  title: Store
+  version: \"1.0.0\"
+definitions:
+  Order:
+    type: object
+    properties:
+      id:
+        type: integer
+        format: int64
+      item:
+        type: string
+    required: [id]
+paths:
+  /orders:
+    get:
+      operationId: listOrders
+      responses:
+        \"200\":
+          description: Orders.
+          schema:
+            type: array
+            items:
+              $ref: \"#/definitions/Order\"
+    post:
+      operationId: placeOrder
+      parameters:
+        - name: body
+          in: body
+          schema:
+            $ref: \"#/definitions/Order\"
+      responses:
+        \"200\":
+          description: Placed.
+  /orders/{id}/receipt:
+    get:
+      operationId: getReceipt
+      produces: [application/octet-stream]
+      parameters:
+        - name: id
+          in: path
+          type: integer
+          format: int64
+          required: true
+      responses:
+        \"200\":
+          description: Receipt.
+          schema:
+            type: string
+            format: binary
+";
+
+fn generate(name: &str, mode: EmitMode, blocking: bool) -> PathBuf {
+    common::generate(SPEC, common::temp_dir(name), |state| {
+        state.format_types = FormatTypes {
+            binary: true,
+            ..FormatTypes::default()
+        };
+        let mut meta = common::store_meta(mode);
+        meta.blocking = blocking;
+        state.set_meta(meta);
+    })
+}
+
+#[test]
+fn test_blocking_crate() {
+    let dir = generate("blocking-crate", EmitMode::Crate, true);
+    let lib = fs::read_to_string(dir.join("lib.rs")).unwrap();
+    assert!(!lib.contains("async"));
+    assert!(!lib.contains(".await"));
+    assert!(!lib.contains("futures::"));
+    assert!(lib.contains("impl ApiClient for reqwest::blocking::Client {"));
+    assert!(lib.contains("impl Response for reqwest::blocking::Response {"));
+    assert!(lib.contains(
+        "fn send(&self, client: &Client) -> Result<ResponseWrapper<Self::Output, Self>, ApiError<Client::Response>> {"
+    ));
+
+    let misc = fs::read_to_string(dir.join("miscellaneous.rs")).unwrap();
+    assert!(!misc.contains("async"));
+    assert!(misc.contains("type Output = crate::util::ResponseStream<"));
+
+    let util = fs::read_to_string(dir.join("util.rs")).unwrap();
+    assert!(!util.contains("tokio"));
+    assert!(!util.contains("futures"));
+    assert!(util.contains("pub fn to_writer<W>(self, writer: &mut W) -> io::Result<()>"));
+
+    let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+    assert!(
+        manifest.contains("reqwest = { version = \"0.10\", features = [\"blocking\", \"json\"] }")
+    );
+    for krate in &["async-trait", "bytes", "futures", "tokio", "tokio-util"] {
+        assert!(!manifest.contains(&format!("\n{} = ", krate)));
+    }
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_blocking_app() {
+    let dir = generate("blocking-app", EmitMode::App, true);
+    let main = fs::read_to_string(dir.join("main.rs")).unwrap();
+    assert!(!main.contains("async"));
+    assert!(!main.contains("tokio"));
+    assert!(main.contains("inner: reqwest::blocking::Client,"));
+    assert!(main.contains("\nfn main() {"));
+
+    let cli = fs::read_to_string(dir.join("cli.rs")).unwrap();
+    assert!(!cli.contains("async"));
+    assert!(!cli.contains(".await"));
+
+    let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains(
+        "reqwest = { version = \"0.10\", features = [\"blocking\", \"json\", \"native-tls\"] }"
+    ));
+    assert!(!manifest.contains("tokio"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_async_by_default() {
+    let dir = generate("async-crate", EmitMode::Crate, false);
+    let lib = fs::read_to_string(dir.join("lib.rs")).unwrap();
+    assert!(lib.contains("impl ApiClient for reqwest::Client {"));
+    assert!(!lib.contains("reqwest::blocking"));
+
+    let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("async-trait = "));
+    assert!(manifest.contains("tokio = "));
+
+    let _ = fs::remove_dir_all(&dir);
+}
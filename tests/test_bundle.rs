@@ -0,0 +1,163 @@
+use paperclip::v2::{
+    self, bundle,
+    loader::{LoaderError, Url},
+    models::{DefaultSchema, ResolvableApi},
+};
+
+use std::io::Cursor;
+
+const SPEC: &[u8] = b"
+swagger: \"2.0\"
+info:
+  title: Store
+  version: \"1.0.0\"
+definitions:
+  Order:
+    type: object
+    properties:
+      item:
+        $ref: \"common.yaml#/definitions/Item\"
+      shipping:
+        type: object
+        properties:
+          address:
+            type: string
+      notes:
+        type: array
+        items:
+          type: object
+          properties:
+            text:
+              type: string
+  OrderShipping:
+    type: string
+paths:
+  /orders:
+    post:
+      parameters:
+        - name: body
+          in: body
+          schema:
+            type: object
+            properties:
+              order:
+                $ref: \"#/definitions/Order\"
+      responses:
+        \"200\":
+          description: Created.
+          schema:
+            type: object
+            properties:
+              id:
+                type: integer
+";
+
+const COMMON: &str = "
+definitions:
+  Item:
+    type: object
+    properties:
+      name:
+        type: string
+";
+
+fn bundled_value(spec: &[u8]) -> serde_json::Value {
+    let api: ResolvableApi<DefaultSchema> =
+        v2::from_reader(Cursor::new(spec)).expect("deserializing spec");
+    let base = Url::parse("https://example.com/specs/api.yaml").unwrap();
+    let loader = |uri: &Url| -> Result<String, LoaderError> {
+        assert_eq!(uri.as_str(), "https://example.com/specs/common.yaml");
+        Ok(COMMON.into())
+    };
+
+    let api = bundle::bundle(api, &base, &loader).expect("bundling");
+    bundle::canonical_value(&api).unwrap()
+}
+
+#[test]
+fn test_bundle_hoists_anonymous_schemas() {
+    let value = bundled_value(SPEC);
+    assert_eq!(
+        value,
+        serde_json::json!({
+            "swagger": "2.0",
+            "info": {
+                "title": "Store",
+                "version": "1.0.0",
+            },
+            "definitions": {
+                "Item": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                    },
+                },
+                "Order": {
+                    "type": "object",
+                    "properties": {
+                        "item": { "$ref": "#/definitions/Item" },
+                        "notes": {
+                            "type": "array",
+                            "items": { "$ref": "#/definitions/OrderNotesItem" },
+                        },
+                        "shipping": { "$ref": "#/definitions/OrderShipping1" },
+                    },
+                },
+                "OrderNotesItem": {
+                    "type": "object",
+                    "properties": {
+                        "text": { "type": "string" },
+                    },
+                },
+                "OrderShipping": { "type": "string" },
+                "OrderShipping1": {
+                    "type": "object",
+                    "properties": {
+                        "address": { "type": "string" },
+                    },
+                },
+                "PostOrdersBody": {
+                    "type": "object",
+                    "properties": {
+                        "order": { "$ref": "#/definitions/Order" },
+                    },
+                },
+                "PostOrdersResponse": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "integer" },
+                    },
+                },
+            },
+            "paths": {
+                "/orders": {
+                    "post": {
+                        "parameters": [{
+                            "in": "body",
+                            "name": "body",
+                            "schema": { "$ref": "#/definitions/PostOrdersBody" },
+                        }],
+                        "responses": {
+                            "200": {
+                                "description": "Created.",
+                                "schema": { "$ref": "#/definitions/PostOrdersResponse" },
+                            },
+                        },
+                    },
+                },
+            },
+        })
+    );
+
+    // Keys are sorted, so the output is the same for the same spec.
+    let json = serde_json::to_string(&value).unwrap();
+    assert!(json.starts_with("{\"definitions\":{\"Item\":"));
+    assert_eq!(json, serde_json::to_string(&bundled_value(SPEC)).unwrap());
+}
+
+#[test]
+fn test_bundle_is_idempotent() {
+    let value = bundled_value(SPEC);
+    let yaml = serde_yaml::to_string(&value).unwrap();
+    assert_eq!(bundled_value(yaml.as_bytes()), value);
+}
@@ -5,6 +5,7 @@ use paperclip::v2::{
     self,
     codegen::{CrateMeta, DefaultEmitter, EmitMode, Emitter, EmitterState},
     models::{DefaultSchema, ResolvableApi},
+    Schema,
 };
 
 use std::fs::File;
@@ -74,6 +75,14 @@ pub mod category {
     include!(\"./category.rs\");
 }
 
+pub mod dog {
+    include!(\"./dog.rs\");
+}
+
+pub mod event {
+    include!(\"./event.rs\");
+}
+
 pub mod get_shipments_id_response {
     include!(\"./get_shipments_id_response.rs\");
 }
@@ -86,6 +95,14 @@ pub mod order {
     include!(\"./order.rs\");
 }
 
+pub mod payment_created {
+    include!(\"./payment_created.rs\");
+}
+
+pub mod payment_refunded {
+    include!(\"./payment_refunded.rs\");
+}
+
 pub mod pet {
     include!(\"./pet.rs\");
 }
@@ -148,6 +165,7 @@ log = \"0.4\"
 mime = { git = \"https://github.com/hyperium/mime\" }
 mime_guess = \"2.0\"
 parking_lot = \"0.8\"
+regex = \"1.3\"
 serde = { version = \"1.0\", features = [\"derive\"] }
 serde_json = \"1.0\"
 serde_yaml = \"0.8\"
@@ -187,7 +205,7 @@ fn test_overridden_path() {
         }
     }
 ",
-        Some(7333),
+        Some(7559),
     );
 }
 
@@ -438,6 +456,193 @@ impl<Any> RecursiveObjectBuilder<Any> {
     );
 }
 
+#[test]
+fn test_all_of_composition() {
+    assert_file_contains_content_at(
+        &(ROOT.clone() + "/tests/test_pet/dog.rs"),
+        "#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Dog {
+    pub breed: String,
+    pub id: Option<i64>,
+    pub name: Option<String>,
+}
+",
+        Some(0),
+    );
+}
+
+#[test]
+fn test_discriminator_tagged_enum() {
+    let event = SCHEMA.definitions["Event"].read();
+    let subtypes = event.subtypes();
+    let names = subtypes
+        .iter()
+        .map(|s| s.read().name().map(String::from))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            Some("PaymentCreated".to_owned()),
+            Some("PaymentRefunded".to_owned())
+        ]
+    );
+
+    assert_file_contains_content_at(
+        &(ROOT.clone() + "/tests/test_pet/event.rs"),
+        "#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = \"eventType\")]
+pub enum Event {
+    #[serde(rename = \"Event\")]
+    Event(crate::event::EventBase),
+    #[serde(rename = \"PaymentCreated\")]
+    PaymentCreated(crate::payment_created::PaymentCreated),
+    #[serde(rename = \"PaymentRefunded\")]
+    PaymentRefunded(crate::payment_refunded::PaymentRefunded),
+}
+
+impl Default for Event {
+    fn default() -> Self {
+        Event::Event(Default::default())
+    }
+}
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct EventBase {
+    pub timestamp: Option<String>,
+}
+",
+        Some(0),
+    );
+
+    // Discriminator is not a part of the subtype.
+    assert_file_contains_content_at(
+        &(ROOT.clone() + "/tests/test_pet/payment_created.rs"),
+        "#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct PaymentCreated {
+    pub amount: i64,
+    pub timestamp: Option<String>,
+}
+",
+        Some(0),
+    );
+}
+
+#[test]
+fn test_schema_validation_keywords() {
+    let pet = SCHEMA.definitions["Pet"].read();
+    let props = pet.properties().expect("pet properties");
+
+    let id = props["id"].read();
+    assert_eq!(id.minimum(), Some(1.0));
+    assert!(!id.exclusive_minimum());
+    assert_eq!(id.maximum(), None);
+    assert!(id.is_read_only());
+
+    let tags = props["tags"].read();
+    assert_eq!(tags.max_items(), Some(10));
+    assert_eq!(tags.min_items(), None);
+    assert!(tags.unique_items());
+
+    let name = props["name"].read();
+    assert_eq!(name.min_length(), Some(1));
+    assert_eq!(name.max_length(), Some(64));
+    assert_eq!(name.pattern(), Some("^[A-Za-z ]+$"));
+    assert_eq!(name.example(), Some(&serde_json::json!("Doggie")));
+    assert_eq!(name.default_value(), None);
+    assert!(!name.is_read_only());
+}
+
+#[test]
+fn test_validation_methods() {
+    assert_file_contains_content_at(
+        &(ROOT.clone() + "/tests/test_pet/pet.rs"),
+        "
+impl<Any> Pet<Any> {
+    /// Validates this object against the constraints in the spec.
+    pub fn validate(&self) -> Result<(), crate::util::ValidationErrors> {
+        let mut errors = crate::util::ValidationErrors::default();
+        self.validate_at(\"\", &mut errors);
+        errors.into_result()
+    }
+
+    #[doc(hidden)]
+    pub fn validate_at(&self, path: &str, errors: &mut crate::util::ValidationErrors) {
+        if let Some(value) = self.category.as_ref() {
+            let path = crate::util::json_pointer(path, \"category\");
+            value.validate_at(&path, errors);
+        }
+        {
+            let value = &self.id;
+            let path = crate::util::json_pointer(path, \"id\");
+            errors.check_range(&path, *value as f64, Some((1.0, false)), None);
+        }
+        {
+            let value = &self.name;
+            let path = crate::util::json_pointer(path, \"name\");
+            errors.check_length(&path, value.chars().count(), Some(1), Some(64));
+            errors.check_pattern(&path, value, \"^[A-Za-z ]+$\");
+        }
+        if let Some(value) = self.tags.as_ref() {
+            let path = crate::util::json_pointer(path, \"tags\");
+            errors.check_items(&path, value.len(), None, Some(10));
+            for (i, value) in value.iter().enumerate() {
+                let path = crate::util::json_pointer(&path, &i.to_string());
+                value.validate_at(&path, errors);
+            }
+        }
+    }
+}
+
+impl<Id, Name, Any> PetBuilder<Id, Name, Any>
+where
+    Id: 'static,
+    Name: 'static,
+{
+    /// Validates the object built so far against the constraints in the spec
+    /// (including the required fields which haven't been set yet).
+    pub fn validate(&self) -> Result<(), crate::util::ValidationErrors> {
+        let mut errors = crate::util::ValidationErrors::default();
+        errors.check_required::<Id, crate::generics::MissingId>(\"/id\");
+        errors.check_required::<Name, crate::generics::MissingName>(\"/name\");
+        self.body.validate_at(\"\", &mut errors);
+        errors.into_result()
+    }
+}
+
+impl<XAuth, Id, Name, Any> PetPostBuilder<XAuth, Id, Name, Any>
+where
+    Id: 'static,
+    Name: 'static,
+{
+    /// Validates the object built so far against the constraints in the spec
+    /// (including the required fields which haven't been set yet).
+    pub fn validate(&self) -> Result<(), crate::util::ValidationErrors> {
+        let mut errors = crate::util::ValidationErrors::default();
+        errors.check_required::<Id, crate::generics::MissingId>(\"/id\");
+        errors.check_required::<Name, crate::generics::MissingName>(\"/name\");
+        self.inner.body.validate_at(\"\", &mut errors);
+        errors.into_result()
+    }
+}
+",
+        None,
+    );
+
+    assert_file_contains_content_at(
+        &(ROOT.clone() + "/tests/test_pet/event.rs"),
+        "
+    #[doc(hidden)]
+    pub fn validate_at(&self, path: &str, errors: &mut crate::util::ValidationErrors) {
+        match self {
+            Event::Event(value) => value.validate_at(path, errors),
+            Event::PaymentCreated(value) => value.validate_at(path, errors),
+            Event::PaymentRefunded(value) => value.validate_at(path, errors),
+        }
+    }
+",
+        None,
+    );
+}
+
 #[test]
 fn test_anonymous_object_definition_in_schema() {
     let _ = &*CLI_CODEGEN;
@@ -0,0 +1,216 @@
+mod common;
+
+use paperclip::v2::{
+    self,
+    diff::{self, Change, ChangeKind},
+    models::{DefaultSchema, ResolvableApi},
+};
+
+use std::fs::{self, File};
+
+const OLD_SPEC: &[u8] = include_bytes!("diff-old-v2.yaml");
+const NEW_SPEC: &[u8] = include_bytes!("diff-new-v2.yaml");
+
+fn summary(changes: &[Change]) -> Vec<(ChangeKind, &str)> {
+    changes
+        .iter()
+        .map(|c| (c.kind, c.pointer.as_str()))
+        .collect()
+}
+
+#[test]
+fn test_diff_reports_all_changes() {
+    let (old, new) = (common::raw_spec(OLD_SPEC), common::raw_spec(NEW_SPEC));
+    let changes = diff::diff(&old, &new);
+    assert_eq!(
+        summary(&changes),
+        &[
+            (
+                ChangeKind::RequiredParameterAdded,
+                "/paths/~1pets/get/parameters/0"
+            ),
+            (ChangeKind::EnumNarrowed, "/paths/~1pets/get/parameters/1"),
+            (
+                ChangeKind::ParameterRemoved,
+                "/paths/~1pets/get/parameters/2"
+            ),
+            (
+                ChangeKind::OptionalParameterAdded,
+                "/paths/~1pets/get/parameters/2"
+            ),
+            (ChangeKind::TypeChanged, "/definitions/Pet/properties/id"),
+            (
+                ChangeKind::EnumNarrowed,
+                "/definitions/Pet/properties/status"
+            ),
+            (
+                ChangeKind::EnumWidened,
+                "/definitions/Pet/properties/status"
+            ),
+            (
+                ChangeKind::ResponseFieldRemoved,
+                "/definitions/Pet/properties/tag"
+            ),
+            (
+                ChangeKind::ResponseFieldAdded,
+                "/definitions/Pet/properties/owner"
+            ),
+            (
+                ChangeKind::RequiredPropertyAdded,
+                "/definitions/NewPet/properties/name"
+            ),
+            (
+                ChangeKind::RequiredPropertyAdded,
+                "/definitions/NewPet/properties/kind"
+            ),
+            (
+                ChangeKind::ResponseRemoved,
+                "/paths/~1pets~1{id}/get/responses/404"
+            ),
+            (ChangeKind::OperationRemoved, "/paths/~1pets~1{id}/delete"),
+            (ChangeKind::OperationAdded, "/paths/~1pets~1{petId}/put"),
+            (ChangeKind::PathRemoved, "/paths/~1stores"),
+            (ChangeKind::PathAdded, "/paths/~1owners"),
+        ][..]
+    );
+
+    let breaking = changes
+        .iter()
+        .filter(|c| c.is_breaking())
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        breaking,
+        &[
+            "breaking[required-parameter-added] /paths/~1pets/get/parameters/0: Parameter \"limit\" (Query) is now required",
+            "breaking[enum-narrowed] /paths/~1pets/get/parameters/1: Value(s) \"desc\" were removed from enum",
+            "breaking[type-changed] /definitions/Pet/properties/id: Type changed from Integer (Int64) to String",
+            "breaking[enum-widened] /definitions/Pet/properties/status: Value(s) \"adopted\" were added to enum",
+            "breaking[response-field-removed] /definitions/Pet/properties/tag: Property \"tag\" was removed from response",
+            "breaking[required-property-added] /definitions/NewPet/properties/name: Property \"name\" is now required",
+            "breaking[required-property-added] /definitions/NewPet/properties/kind: Required property \"kind\" was added",
+            "breaking[response-removed] /paths/~1pets~1{id}/get/responses/404: Response \"404\" was removed from operation GET in path \"/pets/{id}\"",
+            "breaking[operation-removed] /paths/~1pets~1{id}/delete: Operation DELETE was removed from path \"/pets/{id}\"",
+            "breaking[path-removed] /paths/~1stores: Path \"/stores\" was removed",
+        ]
+    );
+
+    let json = serde_json::to_value(&changes[2]).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "kind": "parameter-removed",
+            "compatibility": "non-breaking",
+            "pointer": "/paths/~1pets/get/parameters/2",
+            "message": "Parameter \"verbose\" (Query) was removed",
+        })
+    );
+}
+
+#[test]
+fn test_diff_enums_by_direction() {
+    let spec = |kind: &str, status: &str, color: &str| {
+        format!(
+            "
+swagger: \"2.0\"
+info:
+  title: Petstore
+  version: \"1.0.0\"
+definitions:
+  Color:
+    type: string
+    enum: [{}]
+  NewPet:
+    type: object
+    properties:
+      kind:
+        type: string
+        enum: [{}]
+      color:
+        $ref: \"#/definitions/Color\"
+  Pet:
+    type: object
+    properties:
+      status:
+        type: string
+        enum: [{}]
+      color:
+        $ref: \"#/definitions/Color\"
+paths:
+  /pets:
+    post:
+      parameters:
+        - name: pet
+          in: body
+          schema:
+            $ref: \"#/definitions/NewPet\"
+      responses:
+        \"200\":
+          description: Created.
+          schema:
+            $ref: \"#/definitions/Pet\"
+",
+            color, kind, status
+        )
+    };
+
+    let old = common::raw_spec(spec("cat, dog", "available, sold", "black, white").as_bytes());
+    let new = common::raw_spec(spec("cat, dog, bird", "available, pending", "black").as_bytes());
+    let changes = diff::diff(&old, &new)
+        .into_iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>();
+    // Clients can send fewer values, but they may not recognize the new ones
+    // in responses. `Color` is used in both requests and responses.
+    assert_eq!(
+        changes,
+        &[
+            "breaking[enum-narrowed] /definitions/Color: Value(s) \"white\" were removed from enum",
+            "non-breaking[enum-widened] /definitions/NewPet/properties/kind: Value(s) \"bird\" were added to enum",
+            "non-breaking[enum-narrowed] /definitions/Pet/properties/status: Value(s) \"sold\" were removed from enum",
+            "breaking[enum-widened] /definitions/Pet/properties/status: Value(s) \"pending\" were added to enum",
+        ]
+    );
+}
+
+#[test]
+fn test_diff_resolved_specs() {
+    // Resolved specs should have the same changes as the raw ones.
+    let (old, new) = (common::raw_spec(OLD_SPEC), common::raw_spec(NEW_SPEC));
+    let expected = diff::diff(&old, &new)
+        .into_iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>();
+
+    let (old, new) = (old.resolve().unwrap(), new.resolve().unwrap());
+    let changes = diff::diff(&old, &new)
+        .into_iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(changes, expected);
+}
+
+#[test]
+fn test_diff_pet_spec() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/pet-v2.yaml");
+    let api: ResolvableApi<DefaultSchema> =
+        v2::from_reader(File::open(path).unwrap()).expect("deserializing spec");
+    assert!(diff::diff(&api, &api).is_empty());
+
+    // `Category` is used in both requests and responses, but the change is reported once.
+    let contents = fs::read_to_string(path)
+        .unwrap()
+        .replace("format: int64", "format: int32");
+    let new = common::raw_spec(contents.as_bytes());
+    let changes = diff::diff(&api, &new)
+        .into_iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        changes,
+        &[
+            "breaking[type-changed] /definitions/Category/properties/id: \
+           Type changed from Integer (Int64) to Integer (Int32)"
+        ]
+    );
+}
@@ -1,8 +1,10 @@
 use paperclip::v2::{
     self,
     codegen::{DefaultEmitter, Emitter, EmitterState},
-    models::{DefaultSchema, ResolvableApi},
+    loader::FileLoader,
+    models::{DefaultSchema, ResolvableApi, SpecFormat},
 };
+use paperclip::{PaperClipError, Report};
 
 use std::io::Cursor;
 
@@ -82,3 +84,311 @@ paths:
         "Path similar to \"/store/{storeId}/pets/{petId}\" already exists.",
     );
 }
+
+#[test]
+fn test_spec_format_detection() {
+    let spec = b"\xEF\xBB\xBF\n  \n{\"swagger\": \"2.0\", \"info\": {\"title\": \"Petstore\", \"version\": \"1.0.0\"}, \"paths\": {}}\n";
+    let raw: ResolvableApi<DefaultSchema> =
+        v2::from_reader(Cursor::new(&spec[..])).expect("deserializing spec");
+    assert_eq!(raw.spec_format, SpecFormat::Json);
+    assert_eq!(raw.info.title, "Petstore");
+
+    // JSON is a subset of YAML, so it can be parsed as YAML when asked.
+    let raw: ResolvableApi<DefaultSchema> =
+        v2::from_reader_with_format(Cursor::new(&spec[3..]), SpecFormat::Yaml)
+            .expect("deserializing spec");
+    assert_eq!(raw.spec_format, SpecFormat::Yaml);
+    assert_eq!(raw.info.title, "Petstore");
+
+    let err = v2::from_reader_with_format::<_, DefaultSchema>(
+        Cursor::new(b"swagger: \"2.0\"" as &[_]),
+        SpecFormat::Json,
+    )
+    .unwrap_err();
+    match err {
+        PaperClipError::InvalidSpec(e) => assert_eq!(e.format, SpecFormat::Json),
+        e => panic!("unexpected error: {}", e),
+    }
+}
+
+#[test]
+fn test_spec_error_location() {
+    let spec = b"
+{
+  \"swagger\": \"2.0\",
+  \"info\": {
+    \"title\": 42,
+    \"version\": \"1.0.0\"
+  },
+  \"paths\": {}
+}";
+    let err = match v2::from_reader::<_, DefaultSchema>(Cursor::new(&spec[..])) {
+        Err(PaperClipError::InvalidSpec(e)) => e,
+        r => panic!("unexpected result: {:?}", r.map(|_| ())),
+    };
+    assert_eq!((err.line, err.column), (Some(5), Some(15)));
+    assert_eq!(err.pointer, "/info/title");
+    // The message from the deserializer is kept as it is.
+    assert!(err
+        .to_string()
+        .starts_with("Cannot parse JSON spec (line 5, column 15) at \"/info/title\": "));
+    assert!(err
+        .message
+        .starts_with("invalid type: integer `42`, expected a string"));
+
+    let spec = b"
+swagger: \"2.0\"
+info:
+  title: Petstore
+  version: \"1.0.0\"
+tags:
+  - name: pets
+  - name: [foo]
+paths: {}
+";
+    let err = match v2::from_reader::<_, DefaultSchema>(Cursor::new(&spec[..])) {
+        Err(PaperClipError::InvalidSpec(e)) => e,
+        r => panic!("unexpected result: {:?}", r.map(|_| ())),
+    };
+    assert_eq!((err.line, err.column), (Some(8), Some(11)));
+    assert_eq!(err.pointer, "/tags/1/name");
+    assert!(err
+        .to_string()
+        .starts_with("Cannot parse YAML spec (line 8, column 11) at \"/tags/1/name\": "));
+    assert!(err
+        .message
+        .contains("invalid type: sequence, expected a string"));
+
+    // Syntax errors don't have a pointer.
+    let err = v2::from_reader::<_, DefaultSchema>(Cursor::new(&b"{\"swagger\": \"2.0\",\n}"[..]))
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("Cannot parse JSON spec (line 2, column 1): trailing comma"));
+
+    // Same for the characters after the document.
+    let spec = b"{\"swagger\": \"2.0\", \"info\": {\"title\": \"Foo\", \"version\": \"1\"}, \"paths\": {}}\n}";
+    let err = match v2::from_reader::<_, DefaultSchema>(Cursor::new(&spec[..])) {
+        Err(PaperClipError::InvalidSpec(e)) => e,
+        r => panic!("unexpected result: {:?}", r.map(|_| ())),
+    };
+    assert_eq!((err.line, err.column), (Some(2), Some(1)));
+    assert!(err.pointer.is_empty());
+    assert!(err.message.starts_with("trailing characters"));
+}
+
+#[test]
+fn test_resolver_reports_all_errors() {
+    let spec = Cursor::new(
+        b"
+swagger: \"2.0\"
+info:
+  title:  \"Petstore\"
+  version: \"1.0.0\"
+definitions:
+  Pet:
+    type: object
+    properties:
+      owner:
+        $ref: \"#/definitions/Owner\"
+      tags:
+        type: array
+        items:
+          $ref: \"#/definitions/Tag\"
+paths:
+  /pets:
+    get:
+      parameters:
+        - $ref: \"#/parameters/limit\"
+      responses:
+        \"200\":
+          schema:
+            $ref: \"#/definitions/Pet\"
+        \"404\":
+          $ref: \"#/responses/NotFound\"
+    post:
+      parameters:
+        - name: body
+          in: body
+          schema:
+            type: object
+            properties:
+              owner:
+                $ref: \"#/definitions/Owner\"
+      responses:
+        \"204\":
+          description: Created.
+" as &[_],
+    );
+
+    let raw: ResolvableApi<DefaultSchema> = v2::from_reader(spec).expect("deserializing spec");
+    let report = raw.resolve_with_report().unwrap_err();
+    let errors = report
+        .diagnostics
+        .iter()
+        .map(|d| (d.pointer.as_str(), d.error.to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        &[
+            (
+                "/paths/~1pets/get/parameters/0",
+                "Reference missing in spec: limit".to_owned()
+            ),
+            (
+                "/definitions/Pet/properties/owner",
+                "Reference missing in spec: Owner".to_owned()
+            ),
+            (
+                "/definitions/Pet/properties/tags/items",
+                "Reference missing in spec: Tag".to_owned()
+            ),
+            (
+                "/paths/~1pets/get/responses/404",
+                "Reference missing in spec: NotFound".to_owned()
+            ),
+            (
+                "/paths/~1pets/post/parameters/0/schema/properties/owner",
+                "Reference missing in spec: Owner".to_owned()
+            ),
+        ][..]
+    );
+    assert!(report
+        .to_string()
+        .starts_with("Found 5 error(s) in spec:\n  at /paths/~1pets/get/parameters/0: "));
+}
+
+#[test]
+fn test_emitter_reports_all_errors() {
+    let spec = Cursor::new(
+        b"
+swagger: \"2.0\"
+definitions: {}
+info:
+  title:  \"Petstore\"
+  version: \"1.0.0\"
+paths:
+  /pets/{petId}:
+    get:
+      responses:
+        \"200\":
+          schema:
+            type: string
+  /pets/{id}:
+    delete:
+      parameters:
+        - name: id
+          in: path
+          type: string
+          required: true
+        - name: X-Request-Id
+          in: header
+          type: object
+      responses:
+        \"204\":
+          description: Deleted.
+" as &[_],
+    );
+
+    let raw: ResolvableApi<DefaultSchema> = v2::from_reader(spec).expect("deserializing spec");
+    let resolved = raw.resolve().expect("resolution");
+
+    let mut state = EmitterState::default();
+    state.diagnostics = true;
+    let emitter = DefaultEmitter::from(state);
+    let report = match emitter.generate(&resolved).unwrap_err().downcast() {
+        Ok(PaperClipError::Diagnostics(r)) => r,
+        r => panic!("unexpected error: {:?}", r),
+    };
+    let errors = report
+        .diagnostics
+        .iter()
+        .map(|d| (d.pointer.as_str(), d.error.to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        &[
+            (
+                "/paths/~1pets~1{id}/delete/parameters/1",
+                "Parameter \"X-Request-Id\" in path \"/pets/{id}\" has specified Some(Object) \
+                 type, but it's invalid for Header parameters"
+                    .to_owned()
+            ),
+            (
+                "/paths/~1pets~1{petId}",
+                "Path similar to \"/pets/{petId}\" already exists.".to_owned()
+            ),
+            (
+                "/paths/~1pets~1{petId}",
+                "Parameter(s) {\"petId\"} aren't defined for templated path \"/pets/{petId}\""
+                    .to_owned()
+            ),
+        ][..]
+    );
+}
+
+#[test]
+fn test_emitter_reports_resolver_errors() {
+    let spec = Cursor::new(
+        b"
+swagger: \"2.0\"
+info:
+  title:  \"Petstore\"
+  version: \"1.0.0\"
+definitions:
+  Pet:
+    type: object
+    properties:
+      owner:
+        $ref: \"#/definitions/Owner\"
+paths:
+  /pets:
+    get:
+      parameters:
+        - name: X-Request-Id
+          in: header
+          type: object
+      responses:
+        \"200\":
+          schema:
+            $ref: \"#/definitions/Pet\"
+" as &[_],
+    );
+
+    let raw: ResolvableApi<DefaultSchema> = v2::from_reader(spec).expect("deserializing spec");
+    let base = "file:///spec.yaml".parse().expect("base URL");
+    let mut resolver_report = Report::default();
+    let resolved = raw
+        .resolve_with_loader_into_report(&base, &FileLoader, &mut resolver_report)
+        .expect("resolution");
+    assert_eq!(resolver_report.len(), 1);
+
+    let mut state = EmitterState::default();
+    state.diagnostics = true;
+    state.set_resolver_report(resolver_report);
+    let emitter = DefaultEmitter::from(state);
+    let report = match emitter.generate(&resolved).unwrap_err().downcast() {
+        Ok(PaperClipError::Diagnostics(r)) => r,
+        r => panic!("unexpected error: {:?}", r),
+    };
+    let errors = report
+        .diagnostics
+        .iter()
+        .map(|d| (d.pointer.as_str(), d.error.to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        errors,
+        &[
+            (
+                "/definitions/Pet/properties/owner",
+                "Reference missing in spec: Owner".to_owned()
+            ),
+            (
+                "/paths/~1pets/get/parameters/0",
+                "Parameter \"X-Request-Id\" in path \"/pets\" has specified Some(Object) \
+                 type, but it's invalid for Header parameters"
+                    .to_owned()
+            ),
+        ][..]
+    );
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use paperclip::v2::{
+    codegen::{DefaultEmitter, Emitter, EmitterState},
+    filter::Filter,
+    models::{DefaultSchema, HttpMethod, ResolvableApi},
+};
+
+use std::fs;
+
+const SPEC: &[u8] = include_bytes!("filter-v2.yaml");
+
+/// Returns the operation IDs, definitions and parameters in the given spec.
+fn summary(api: &ResolvableApi<DefaultSchema>) -> (Vec<&str>, Vec<&str>, Vec<&str>) {
+    let ops = api
+        .paths
+        .values()
+        .flat_map(|map| map.methods.values())
+        .filter_map(|op| op.operation_id.as_deref())
+        .collect();
+    let defs = api.definitions.keys().map(String::as_str).collect();
+    let params = api.parameters.keys().map(String::as_str).collect();
+    (ops, defs, params)
+}
+
+#[test]
+fn test_filter_operations() {
+    let api = common::raw_spec(SPEC);
+    assert!(Filter::default().is_empty());
+    assert_eq!(summary(&Filter::default().apply(&api)), summary(&api));
+
+    let mut filter = Filter::default();
+    filter.include_tags.push("pets".into());
+    filter.exclude_tags.push("admin".into());
+    assert_eq!(
+        summary(&filter.apply(&api)),
+        (vec!["listPets"], vec!["Dog", "Pet", "User"], vec!["limit"])
+    );
+
+    let mut filter = Filter::default();
+    filter.include_paths.push("/store/**".into());
+    filter.exclude_operations.push("getInventory".into());
+    assert_eq!(
+        summary(&filter.apply(&api)),
+        (vec!["placeOrder"], vec!["Item", "Order"], vec!["order"])
+    );
+
+    let mut filter = Filter::default();
+    filter.include_paths.push("/*".into());
+    filter.include_operations.push("getInventory".into());
+    assert_eq!(
+        summary(&filter.apply(&api)),
+        (
+            vec!["listPets", "getInventory"],
+            vec!["Dog", "Pet", "User"],
+            vec!["limit"]
+        )
+    );
+
+    let mut filter = Filter::default();
+    filter.exclude_paths.push("/pets*".into());
+    let filtered = filter.apply(&api);
+    assert_eq!(
+        filtered.paths.keys().collect::<Vec<_>>(),
+        &["/pets/{id}", "/store/inventory", "/store/orders"]
+    );
+    assert!(filtered.paths["/pets/{id}"]
+        .methods
+        .contains_key(&HttpMethod::Delete));
+}
+
+#[test]
+fn test_filter_resolved_spec() {
+    let api = common::raw_spec(SPEC).resolve().expect("resolution");
+    let mut filter = Filter::default();
+    filter.include_operations.push("placeOrder".into());
+    let filtered = filter.apply(&api);
+    assert_eq!(
+        filtered.definitions.keys().collect::<Vec<_>>(),
+        &["Item", "Order"]
+    );
+
+    // Codegen leaves out the unused definitions.
+    let dir = std::env::temp_dir().join(format!("paperclip-filter-{}", std::process::id()));
+    let mut state = EmitterState::default();
+    state.working_dir = dir.clone();
+    state.filter = filter;
+    DefaultEmitter::from(state).generate(&api).expect("codegen");
+
+    let mut files = fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    files.sort();
+    assert_eq!(
+        files,
+        &["generics.rs", "item.rs", "mod.rs", "order.rs", "util.rs"]
+    );
+    let _ = fs::remove_dir_all(&dir);
+}
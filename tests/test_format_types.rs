@@ -0,0 +1,242 @@
+mod common;
+
+use paperclip::v2::codegen::{EmitMode, FormatTypes};
+
+use std::fs;
+use std::path::PathBuf;
+
+const SPEC: &[u8] = b"
+swagger: \"2.0\"
+info:
+  title: Store
+  version: \"1.0.0\"
+definitions:
+  Order:
+    type: object
+    properties:
+      id:
+        type: string
+        format: uuid
+      placed:
+        type: string
+        format: date-time
+      delivery:
+        type: string
+        format: date
+      signature:
+        type: string
+        format: byte
+      attachments:
+        type: array
+        items:
+          type: string
+          format: byte
+      receipt:
+        type: string
+        format: binary
+      history:
+        type: array
+        items:
+          type: string
+          format: date-time
+    required: [id]
+paths:
+  /orders:
+    post:
+      operationId: placeOrder
+      parameters:
+        - name: body
+          in: body
+          schema:
+            $ref: \"#/definitions/Order\"
+        - name: X-Request-Id
+          in: header
+          type: string
+          format: uuid
+      responses:
+        \"200\":
+          description: Placed.
+  /orders/{id}/receipt:
+    get:
+      operationId: getReceipt
+      produces: [application/octet-stream]
+      parameters:
+        - name: id
+          in: path
+          type: string
+          format: uuid
+          required: true
+        - name: since
+          in: query
+          type: string
+          format: date-time
+        - name: X-Digest
+          in: header
+          type: string
+          format: byte
+      responses:
+        \"200\":
+          description: Receipt.
+          schema:
+            type: string
+            format: binary
+  /orders/{id}/attachments:
+    post:
+      operationId: addAttachment
+      consumes: [multipart/form-data]
+      parameters:
+        - name: id
+          in: path
+          type: string
+          format: uuid
+          required: true
+        - name: file
+          in: formData
+          type: string
+          format: binary
+      responses:
+        \"200\":
+          description: Added.
+          schema:
+            $ref: \"#/definitions/Order\"
+";
+
+fn generate(name: &str, types: FormatTypes) -> PathBuf {
+    common::generate(SPEC, common::temp_dir(name), |state| {
+        state.format_types = types;
+        state.set_meta(common::store_meta(EmitMode::Crate));
+    })
+}
+
+#[test]
+fn test_format_types() {
+    let dir = generate("format-types", FormatTypes::all());
+    let order = fs::read_to_string(dir.join("order.rs")).unwrap();
+    assert!(order.contains(
+        "
+pub struct Order {
+    #[serde(default, with = \"crate::util::base64\")]
+    pub attachments: Option<Vec<crate::util::Bytes>>,
+    pub delivery: Option<crate::util::Date>,
+    pub history: Option<Vec<crate::util::DateTime>>,
+    pub id: crate::util::Uuid,
+    pub placed: Option<crate::util::DateTime>,
+    pub receipt: Option<String>,
+    #[serde(default, with = \"crate::util::base64\")]
+    pub signature: Option<crate::util::Bytes>,
+}
+"
+    ));
+    assert!(order
+        .contains("pub fn placed(mut self, value: impl Into<crate::util::DateTime>) -> Self {"));
+    assert!(order
+        .contains("pub fn signature(mut self, value: impl Into<crate::util::Bytes>) -> Self {"));
+    assert!(order.contains("param_x_request_id: Option<crate::util::Uuid>,"));
+    assert!(!order.contains("value.validate_at"));
+
+    // Binary values are streamed in responses and uploaded from files in form data.
+    assert!(order.contains(
+        "
+    #[inline]
+    pub fn file(mut self, value: impl AsRef<std::path::Path>) -> Self {
+        self.inner.param_file = Some(value.as_ref().into());
+        self
+    }
+"
+    ));
+    let misc = fs::read_to_string(dir.join("miscellaneous.rs")).unwrap();
+    assert!(misc.contains("type Output = crate::util::ResponseStream<"));
+    assert!(misc.contains("param_id: Option<crate::util::Uuid>,"));
+    // Date-time parameters are written as RFC 3339 strings and bytes are sent as they are.
+    assert!(misc.contains("param_since: Option<crate::util::DateTimeParam>,"));
+    assert!(misc.contains("param_x_digest: Option<String>,"));
+
+    let util = fs::read_to_string(dir.join("util.rs")).unwrap();
+    assert!(util.contains(
+        "
+#[cfg(feature = \"chrono\")]
+pub type Date = chrono::NaiveDate;
+#[cfg(not(feature = \"chrono\"))]
+pub type Date = String;
+"
+    ));
+    assert!(util.contains(
+        "
+#[cfg(feature = \"uuid\")]
+pub type Uuid = uuid::Uuid;
+#[cfg(not(feature = \"uuid\"))]
+pub type Uuid = String;
+"
+    ));
+    assert!(util.contains(
+        "
+#[cfg(feature = \"base64\")]
+pub type Bytes = Vec<u8>;
+#[cfg(not(feature = \"base64\"))]
+pub type Bytes = String;
+"
+    ));
+    assert!(util.contains("pub struct DateTimeParam(pub super::DateTime);"));
+    assert!(util.contains("pub mod base64 {"));
+
+    let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains(
+        "
+chrono = { version = \"0.4\", features = [\"serde\"], optional = true }
+uuid = { version = \"0.8\", features = [\"serde\"], optional = true }
+base64 = { version = \"0.13\", optional = true }
+
+[workspace]"
+    ));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_no_format_types_by_default() {
+    let dir = generate("no-format-types", FormatTypes::default());
+    let order = fs::read_to_string(dir.join("order.rs")).unwrap();
+    assert!(order.contains(
+        "
+pub struct Order {
+    pub attachments: Option<Vec<String>>,
+    pub delivery: Option<String>,
+    pub history: Option<Vec<String>>,
+    pub id: String,
+    pub placed: Option<String>,
+    pub receipt: Option<String>,
+    pub signature: Option<String>,
+}
+"
+    ));
+    assert!(order.contains("param_file: Option<String>,"));
+
+    let util = fs::read_to_string(dir.join("util.rs")).unwrap();
+    assert!(!util.contains("Bytes"));
+    assert!(!util.contains("DateTime"));
+    assert!(!util.contains("pub mod base64"));
+    let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+    assert!(!manifest.contains("chrono"));
+    assert!(!manifest.contains("uuid"));
+    assert!(!manifest.contains("base64"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_date_time_only() {
+    let dir = generate(
+        "date-time-only",
+        FormatTypes {
+            date_time: true,
+            ..FormatTypes::default()
+        },
+    );
+    let order = fs::read_to_string(dir.join("order.rs")).unwrap();
+    assert!(order.contains("pub delivery: Option<String>,"));
+    assert!(order.contains("pub placed: Option<crate::util::DateTime>,"));
+    let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("chrono = "));
+    assert!(!manifest.contains("uuid"));
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,194 @@
+mod common;
+
+use paperclip::v2::{
+    self,
+    lint::{self, Finding, LintConfig, Rule, RuleLevel, Severity},
+    models::{DefaultSchema, ResolvableApi},
+};
+
+use std::fs::File;
+
+const SPEC: &[u8] = include_bytes!("lint-v2.yaml");
+
+fn summary(findings: &[Finding]) -> Vec<(Rule, Severity, &str)> {
+    findings
+        .iter()
+        .map(|f| (f.rule, f.severity, f.pointer.as_str()))
+        .collect()
+}
+
+#[test]
+fn test_lint_reports_all_findings() {
+    let api = common::raw_spec(SPEC);
+    let findings = lint::lint(&api, &LintConfig::default());
+    assert_eq!(
+        summary(&findings),
+        &[
+            (
+                Rule::MissingReference,
+                Severity::Error,
+                "/paths/~1pets/get/parameters/1"
+            ),
+            (
+                Rule::CollectionFormat,
+                Severity::Warning,
+                "/paths/~1pets/get/parameters/2"
+            ),
+            (
+                Rule::DuplicateOperationId,
+                Severity::Error,
+                "/paths/~1pets/post/operationId"
+            ),
+            (
+                Rule::MissingDescription,
+                Severity::Warning,
+                "/paths/~1pets/post"
+            ),
+            (
+                Rule::InvalidParameter,
+                Severity::Error,
+                "/paths/~1pets/post/parameters/0"
+            ),
+            (
+                Rule::UndocumentedResponses,
+                Severity::Warning,
+                "/paths/~1pets/post/responses"
+            ),
+            (
+                Rule::MissingOperationId,
+                Severity::Warning,
+                "/paths/~1pets~1{id}/get"
+            ),
+            (
+                Rule::PathParameters,
+                Severity::Error,
+                "/paths/~1pets~1{id}/get"
+            ),
+            (
+                Rule::PathParameters,
+                Severity::Error,
+                "/paths/~1pets~1{id}/get"
+            ),
+            (
+                Rule::UndocumentedResponses,
+                Severity::Warning,
+                "/paths/~1pets~1{id}/get/responses/200"
+            ),
+            (
+                Rule::DuplicatePath,
+                Severity::Error,
+                "/paths/~1pets~1{petId}"
+            ),
+            (
+                Rule::MissingDescription,
+                Severity::Warning,
+                "/definitions/Orphan"
+            ),
+            (
+                Rule::PropertyCase,
+                Severity::Warning,
+                "/definitions/Pet/properties/owner_name"
+            ),
+            (
+                Rule::MissingReference,
+                Severity::Error,
+                "/definitions/Orphan/properties/friend"
+            ),
+            (
+                Rule::UnusedDefinition,
+                Severity::Warning,
+                "/definitions/Orphan"
+            ),
+        ][..]
+    );
+
+    let messages = findings
+        .iter()
+        .map(|f| f.to_string())
+        .filter(|m| m.starts_with("error"))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        &[
+            "error[missing-reference] /paths/~1pets/get/parameters/1: Reference missing in spec: #/parameters/offset",
+            "error[duplicate-operation-id] /paths/~1pets/post/operationId: Operation ID \"listPets\" is already used by \"/paths/~1pets/get\"",
+            "error[invalid-parameter] /paths/~1pets/post/parameters/0: Parameter \"pet\" in path \"/pets\" is a body but the schema is missing",
+            "error[path-parameters] /paths/~1pets~1{id}/get: Parameter(s) {\"id\"} aren't defined for templated path \"/pets/{id}\"",
+            "error[path-parameters] /paths/~1pets~1{id}/get: Path parameter \"petId\" doesn't exist in templated path \"/pets/{id}\"",
+            "error[duplicate-path] /paths/~1pets~1{petId}: Path \"/pets/{petId}\" is the same as \"/pets/{id}\" (regardless of templating)",
+            "error[missing-reference] /definitions/Orphan/properties/friend: Reference missing in spec: #/definitions/Friend",
+        ]
+    );
+}
+
+#[test]
+fn test_lint_config() {
+    let config: LintConfig = serde_yaml::from_str(
+        "
+rules:
+  missing-description: off
+  missing-operation-id: off
+  property-case: error
+  missing-reference: warning
+",
+    )
+    .expect("deserializing config");
+    assert_eq!(config.rules[&Rule::MissingDescription], RuleLevel::Off);
+
+    let api = common::raw_spec(SPEC);
+    let findings = lint::lint(&api, &config);
+    assert!(findings
+        .iter()
+        .all(|f| f.rule != Rule::MissingDescription && f.rule != Rule::MissingOperationId));
+    let case = findings
+        .iter()
+        .find(|f| f.rule == Rule::PropertyCase)
+        .unwrap();
+    assert_eq!(case.severity, Severity::Error);
+    assert!(findings
+        .iter()
+        .filter(|f| f.rule == Rule::MissingReference)
+        .all(|f| f.severity == Severity::Warning));
+
+    assert!(serde_yaml::from_str::<LintConfig>("rules:\n  no-such-rule: off\n").is_err());
+
+    let json = serde_json::to_value(&findings[0]).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "rule": "missing-reference",
+            "severity": "warning",
+            "pointer": "/paths/~1pets/get/parameters/1",
+            "message": "Reference missing in spec: #/parameters/offset",
+        })
+    );
+}
+
+#[test]
+fn test_lint_global_parameters() {
+    let api = common::raw_spec(include_bytes!("lint-parameters-v2.yaml"));
+    let messages = lint::lint(&api, &LintConfig::default())
+        .into_iter()
+        .filter(|f| f.rule == Rule::InvalidParameter)
+        .map(|f| f.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        &[
+            "error[invalid-parameter] /parameters/contentType: Header parameter \"Content-Type\" is not allowed",
+            "error[invalid-parameter] /parameters/pet: Parameter \"pet\" is a body but the schema is missing",
+        ]
+    );
+}
+
+#[test]
+fn test_lint_pet_spec_has_no_errors() {
+    let fd = File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/pet-v2.yaml")).unwrap();
+    let api: ResolvableApi<DefaultSchema> = v2::from_reader(fd).expect("deserializing spec");
+    let errors = lint::lint(&api, &LintConfig::default())
+        .into_iter()
+        .filter(|f| f.severity == Severity::Error)
+        .map(|f| f.to_string())
+        .collect::<Vec<_>>();
+    assert!(errors.is_empty(), "{:#?}", errors);
+}
@@ -0,0 +1,149 @@
+use paperclip::v2::{
+    self,
+    loader::{FileLoader, LoaderError, Url},
+    models::{DefaultSchema, ResolvableApi},
+    Schema,
+};
+use parking_lot::Mutex;
+
+use std::fs::{self, File};
+use std::path::PathBuf;
+
+lazy_static::lazy_static! {
+    static ref ROOT: PathBuf = fs::canonicalize(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/refs")).unwrap();
+}
+
+fn raw_spec() -> ResolvableApi<DefaultSchema> {
+    let fd = File::open(ROOT.join("api.yaml")).expect("file?");
+    v2::from_reader(fd).expect("deserializing spec")
+}
+
+fn assert_resolved(api: &ResolvableApi<DefaultSchema>) {
+    let names = api.definitions.keys().cloned().collect::<Vec<_>>();
+    assert_eq!(names, &["Error", "Item", "ModelsError", "Money", "Order"]);
+
+    let order = api.definitions["Order"].read();
+    let item = order.properties().unwrap()["item"].read();
+    assert_eq!(item.name(), Some("Item"));
+    let props = item.properties().unwrap();
+    assert_eq!(
+        props.keys().map(String::as_str).collect::<Vec<_>>(),
+        &["name", "price", "related"]
+    );
+    assert_eq!(props["price"].read().name(), Some("Money"));
+    assert_eq!(
+        props["related"].read().items().unwrap().read().name(),
+        Some("Item")
+    );
+
+    let op = &api.paths["/orders"].methods[&v2::models::HttpMethod::Post];
+    let resp = op.responses["default"].read();
+    let error = resp.schema.as_ref().unwrap().read();
+    assert_eq!(error.name(), Some("ModelsError"));
+    assert!(error.required_properties().unwrap().contains("code"));
+}
+
+#[test]
+fn test_relative_file_refs() {
+    let base = Url::from_file_path(ROOT.join("api.yaml")).unwrap();
+    let api = raw_spec()
+        .resolve_with_loader(&base, &FileLoader)
+        .expect("resolution");
+    assert_resolved(&api);
+}
+
+#[test]
+fn test_remote_refs_are_loaded_once() {
+    // Stand-in for some server hosting the specs.
+    let loaded = Mutex::new(vec![]);
+    let loader = |uri: &Url| -> Result<String, LoaderError> {
+        assert_eq!(uri.host_str(), Some("example.com"));
+        loaded.lock().push(uri.path().to_owned());
+        Ok(fs::read_to_string(ROOT.join(&uri.path()[1..]))?)
+    };
+
+    let base = Url::parse("http://example.com/api.yaml").unwrap();
+    let api = raw_spec()
+        .resolve_with_loader(&base, &loader)
+        .expect("resolution");
+    assert_resolved(&api);
+    assert_eq!(
+        loaded.into_inner(),
+        &["/common/models.yaml", "/common/money.yaml"]
+    );
+}
+
+#[test]
+fn test_missing_external_refs() {
+    let base = Url::parse("http://example.com/api.yaml").unwrap();
+    let loader = |_: &Url| -> Result<String, LoaderError> { Err("connection refused".into()) };
+    let err = raw_spec().resolve_with_loader(&base, &loader).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Failed to load \"http://example.com/common/models.yaml\": connection refused"
+    );
+
+    // Plain resolution doesn't support external references.
+    let err = raw_spec().resolve().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Invalid $ref URI \"common/models.yaml#/definitions/Item\". Only relative URIs are supported."
+    );
+}
+
+#[test]
+fn test_refs_back_into_spec() {
+    const ROOT_SPEC: &str = "
+swagger: \"2.0\"
+info:
+  title: Pets
+  version: \"1.0.0\"
+definitions:
+  Owner:
+    type: object
+    properties:
+      pets:
+        type: array
+        items:
+          $ref: \"other.yaml#/definitions/Pet\"
+paths: {}
+";
+    const OTHER_SPEC: &str = "
+definitions:
+  Pet:
+    type: object
+    properties:
+      owner:
+        $ref: \"root.yaml#/definitions/Owner\"
+";
+
+    let loaded = Mutex::new(vec![]);
+    let loader = |uri: &Url| -> Result<String, LoaderError> {
+        loaded.lock().push(uri.path().to_owned());
+        match uri.path() {
+            "/other.yaml" => Ok(OTHER_SPEC.into()),
+            _ => Ok(ROOT_SPEC.into()),
+        }
+    };
+
+    let base = Url::parse("http://example.com/root.yaml").unwrap();
+    let api: ResolvableApi<DefaultSchema> = v2::from_reader(ROOT_SPEC.as_bytes()).unwrap();
+    let api = api.resolve_with_loader(&base, &loader).expect("resolution");
+    // The spec itself isn't loaded again.
+    assert_eq!(loaded.into_inner(), &["/other.yaml"]);
+
+    let names = api.definitions.keys().cloned().collect::<Vec<_>>();
+    assert_eq!(names, &["Owner", "Pet"]);
+    let owner = api.definitions["Owner"].read();
+    let pet = owner.properties().unwrap()["pets"]
+        .read()
+        .items()
+        .unwrap()
+        .read()
+        .clone();
+    assert_eq!(pet.name(), Some("Pet"));
+    assert_eq!(
+        pet.properties().unwrap()["owner"].read().name(),
+        Some("Owner")
+    );
+}
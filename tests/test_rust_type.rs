@@ -0,0 +1,177 @@
+mod common;
+
+use paperclip::v2::{
+    self,
+    models::{DefaultSchema, ResolvableApi},
+};
+
+use std::fs;
+use std::io::Cursor;
+
+const SPEC: &[u8] = b"
+swagger: \"2.0\"
+info:
+  title: Store
+  version: \"1.0.0\"
+definitions:
+  Money:
+    type: object
+    x-rust-type: common::Money
+    properties:
+      amount:
+        type: integer
+      currency:
+        type: string
+  Timestamp:
+    type: string
+  Order:
+    type: object
+    properties:
+      total:
+        $ref: \"#/definitions/Money\"
+      placed:
+        $ref: \"#/definitions/Timestamp\"
+      history:
+        type: array
+        items:
+          $ref: \"#/definitions/Timestamp\"
+      note:
+        type: string
+        x-rust-type: std::borrow::Cow<'static, str>
+    required: [total]
+paths:
+  /orders:
+    post:
+      operationId: placeOrder
+      parameters:
+        - name: body
+          in: body
+          schema:
+            $ref: \"#/definitions/Order\"
+      responses:
+        \"200\":
+          description: Placed.
+  /charge:
+    post:
+      operationId: charge
+      parameters:
+        - name: amount
+          in: body
+          required: true
+          schema:
+            $ref: \"#/definitions/Money\"
+      responses:
+        \"200\":
+          description: Charged.
+          schema:
+            type: string
+    delete:
+      operationId: refund
+      parameters:
+        - name: amount
+          in: body
+          schema:
+            $ref: \"#/definitions/Money\"
+      responses:
+        \"200\":
+          description: Refunded.
+          schema:
+            type: string
+  /orders/total:
+    get:
+      operationId: getTotal
+      responses:
+        \"200\":
+          description: Total.
+          schema:
+            $ref: \"#/definitions/Money\"
+";
+
+#[test]
+fn test_existing_rust_types() {
+    let dir = common::generate(SPEC, common::temp_dir("rust-type"), |state| {
+        state
+            .rust_types
+            .insert("Timestamp".into(), "chrono::DateTime<chrono::Utc>".into());
+    });
+
+    // No modules for definitions with existing Rust types.
+    let mut files = fs::read_dir(&dir)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    files.sort();
+    assert_eq!(
+        files,
+        &[
+            "generics.rs",
+            "miscellaneous.rs",
+            "mod.rs",
+            "order.rs",
+            "util.rs"
+        ]
+    );
+
+    let order = fs::read_to_string(dir.join("order.rs")).unwrap();
+    assert!(order.contains(
+        "
+pub struct Order {
+    pub history: Option<Vec<chrono::DateTime<chrono::Utc>>>,
+    pub note: Option<std::borrow::Cow<'static, str>>,
+    pub placed: Option<chrono::DateTime<chrono::Utc>>,
+    pub total: common::Money,
+}
+"
+    ));
+    assert!(order.contains(
+        "pub fn history(mut self, value: impl Iterator<Item = impl Into<chrono::DateTime<chrono::Utc>>>) -> Self {
+        self.body.history = Some(value.map(|value| value.into()).collect::<Vec<_>>().into());"
+    ));
+    assert!(order.contains(
+        "pub fn placed(mut self, value: impl Into<chrono::DateTime<chrono::Utc>>) -> Self {"
+    ));
+    assert!(order.contains(
+        "pub fn total(mut self, value: common::Money) -> OrderBuilder<crate::generics::TotalExists> {"
+    ));
+    // Existing types are left alone during validation.
+    assert!(!order.contains("value.validate_at"));
+
+    // Operations returning existing types go to the miscellaneous module.
+    let misc = fs::read_to_string(dir.join("miscellaneous.rs")).unwrap();
+    assert!(misc.contains("type Output = common::Money;"));
+    // Bodies with existing types are set through builders.
+    assert!(misc.contains(
+        "pub fn amount(mut self, value: common::Money) -> MiscellaneousPostBuilder<crate::generics::AmountExists> {"
+    ));
+    assert!(misc.contains(
+        "serde_yaml::to_writer(&mut vec, self.inner.param_amount.as_ref().expect(\"missing parameter amount?\"))?;"
+    ));
+    assert!(misc.contains(
+        "let req = match self.param_amount.as_ref() {
+            Some(v) => req"
+    ));
+    let root = fs::read_to_string(dir.join("mod.rs")).unwrap();
+    assert!(!root.contains("pub mod money"));
+    assert!(!root.contains("pub mod timestamp"));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_rust_type_extension_roundtrip() {
+    let api: ResolvableApi<DefaultSchema> =
+        v2::from_reader(Cursor::new(SPEC)).expect("deserializing spec");
+    assert_eq!(
+        api.definitions["Money"].read().rust_type.as_deref(),
+        Some("common::Money")
+    );
+
+    let value = serde_json::to_value(&api).unwrap();
+    assert_eq!(
+        value["definitions"]["Money"]["x-rust-type"],
+        "common::Money"
+    );
+    assert!(value["definitions"]["Timestamp"]
+        .get("x-rust-type")
+        .is_none());
+}
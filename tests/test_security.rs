@@ -0,0 +1,246 @@
+mod common;
+
+use paperclip::v2::{
+    self,
+    codegen::EmitMode,
+    models::{DefaultSchema, HttpMethod, ResolvableApi},
+};
+use serde_json::json;
+
+use std::fs;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+const SPEC: &[u8] = b"
+swagger: \"2.0\"
+info:
+  title: Store
+  version: \"1.0.0\"
+securityDefinitions:
+  api_key:
+    type: apiKey
+    name: X-API-Key
+    in: header
+  token:
+    type: apiKey
+    name: token
+    in: query
+  basic:
+    type: basic
+  oauth:
+    type: oauth2
+    flow: implicit
+    authorizationUrl: https://example.com/oauth
+    scopes:
+      read: Read access.
+security:
+  - api_key: []
+definitions:
+  Order:
+    type: object
+    properties:
+      id:
+        type: integer
+        format: int64
+    required: [id]
+paths:
+  /orders:
+    get:
+      operationId: listOrders
+      responses:
+        \"200\":
+          description: Orders.
+          schema:
+            type: array
+            items:
+              $ref: \"#/definitions/Order\"
+    post:
+      operationId: placeOrder
+      security:
+        - basic: []
+          token: []
+        - oauth: [read]
+      parameters:
+        - name: body
+          in: body
+          schema:
+            $ref: \"#/definitions/Order\"
+      responses:
+        \"200\":
+          description: Placed.
+  /health:
+    get:
+      operationId: health
+      security: []
+      responses:
+        \"200\":
+          description: Healthy.
+          schema:
+            type: string
+";
+
+fn generate(name: &str, spec: &[u8], blocking: bool) -> PathBuf {
+    common::generate(spec, common::temp_dir(name), |state| {
+        let mut meta = common::store_meta(EmitMode::Crate);
+        meta.blocking = blocking;
+        state.set_meta(meta);
+    })
+}
+
+#[test]
+fn test_security_schemes() {
+    let dir = generate("security", SPEC, false);
+    let lib = fs::read_to_string(dir.join("lib.rs")).unwrap();
+    assert!(lib.contains("fn authorize(self, requirements: &[&[&str]]) -> Self"));
+    assert!(lib.contains("inner: credentials.apply(inner, requirements),"));
+    assert!(lib.contains("pub struct ApiKey(pub String);"));
+    assert!(lib.contains("req.header(\"X-API-Key\", &self.0)"));
+    assert!(lib.contains("pub struct Token(pub String);"));
+    assert!(lib.contains("req.query(&[(\"token\", &self.0)])"));
+    assert!(lib.contains(
+        "
+        pub struct Basic {
+            pub username: String,
+            pub password: Option<String>,
+        }"
+    ));
+    assert!(lib.contains("pub struct Oauth(pub String);"));
+    assert!(lib.contains(
+        "
+        pub struct Credentials {
+            pub api_key: Option<ApiKey>,
+            pub basic: Option<Basic>,
+            pub oauth: Option<Oauth>,
+            pub token: Option<Token>,
+        }"
+    ));
+    assert!(lib.contains("impl<C: ApiClient + Sync> ApiClient for Authenticated<C> {"));
+
+    let order = fs::read_to_string(dir.join("order.rs")).unwrap();
+    assert!(order.contains("let req = crate::client::security::authorize(req, &[&[\"api_key\"]]);"));
+    assert!(order.contains(
+        "let req = crate::client::security::authorize(req, &[&[\"basic\", \"token\"], &[\"oauth\"]]);"
+    ));
+
+    let misc = fs::read_to_string(dir.join("miscellaneous.rs")).unwrap();
+    // Empty requirements override the global requirements.
+    assert!(!misc.contains("authorize"));
+
+    let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("base64 = "));
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_empty_security_roundtrip() {
+    let api: ResolvableApi<DefaultSchema> =
+        v2::from_reader(Cursor::new(SPEC)).expect("deserializing spec");
+    let health = &api.paths["/health"].methods[&HttpMethod::Get];
+    assert_eq!(health.security, Some(vec![]));
+    let orders = &api.paths["/orders"].methods[&HttpMethod::Get];
+    assert_eq!(orders.security, None);
+
+    let value = serde_json::to_value(&api).unwrap();
+    assert_eq!(value["paths"]["/health"]["get"]["security"], json!([]));
+    assert!(value["paths"]["/orders"]["get"].get("security").is_none());
+}
+
+#[test]
+fn test_blocking_security() {
+    let dir = generate("blocking-security", SPEC, true);
+    let lib = fs::read_to_string(dir.join("lib.rs")).unwrap();
+    assert!(lib.contains("impl<C: ApiClient> ApiClient for Authenticated<C> {"));
+    assert!(!lib.contains("async"));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_no_security() {
+    let spec = b"
+swagger: \"2.0\"
+info:
+  title: Store
+  version: \"1.0.0\"
+definitions:
+  Order:
+    type: object
+    properties:
+      id:
+        type: integer
+        format: int64
+paths:
+  /health:
+    get:
+      operationId: health
+      responses:
+        \"200\":
+          description: Healthy.
+";
+    let dir = generate("no-security", spec, false);
+    let lib = fs::read_to_string(dir.join("lib.rs")).unwrap();
+    assert!(!lib.contains("security"));
+    assert!(!lib.contains("credentials"));
+    assert!(!lib.contains("authorize"));
+    let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+    assert!(!manifest.contains("base64"));
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_security_scheme_name_clashes() {
+    let spec = b"
+swagger: \"2.0\"
+info:
+  title: Store
+  version: \"1.0.0\"
+securityDefinitions:
+  apiKey:
+    type: apiKey
+    name: X-API-Key
+    in: header
+  api_key:
+    type: apiKey
+    name: api_key
+    in: query
+  credentials:
+    type: basic
+security:
+  - api_key: []
+  - credentials: []
+definitions:
+  Order:
+    type: object
+    properties:
+      id:
+        type: integer
+        format: int64
+paths:
+  /health:
+    get:
+      operationId: health
+      responses:
+        \"200\":
+          description: Healthy.
+          schema:
+            type: string
+";
+    let dir = generate("security-clashes", spec, false);
+    let lib = fs::read_to_string(dir.join("lib.rs")).unwrap();
+    assert!(lib.contains("pub struct ApiKey(pub String);"));
+    assert!(lib.contains("pub struct ApiKey1(pub String);"));
+    assert!(lib.contains("pub struct Credentials1 {"));
+    assert!(lib.contains(
+        "
+        pub struct Credentials {
+            pub api_key: Option<ApiKey>,
+            pub api_key_1: Option<ApiKey1>,
+            pub credentials_1: Option<Credentials1>,
+        }"
+    ));
+    assert!(lib.contains("\"api_key\" => self.api_key_1.is_some(),"));
+
+    let misc = fs::read_to_string(dir.join("miscellaneous.rs")).unwrap();
+    assert!(misc.contains("authorize(req, &[&[\"api_key\"], &[\"credentials\"]]);"));
+    let _ = fs::remove_dir_all(&dir);
+}
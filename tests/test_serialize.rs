@@ -0,0 +1,96 @@
+use paperclip::v2::{
+    self,
+    models::{DefaultSchema, HttpMethod, Resolvable, ResolvableApi},
+};
+use parking_lot::RwLock;
+use serde_json::json;
+
+use std::fs::File;
+use std::sync::Arc;
+
+fn resolved_spec() -> ResolvableApi<DefaultSchema> {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/pet-v2.yaml");
+    let api: ResolvableApi<DefaultSchema> =
+        v2::from_reader(File::open(path).unwrap()).expect("deserializing spec");
+    api.resolve().expect("resolution")
+}
+
+#[test]
+fn test_resolved_spec_keeps_references() {
+    let value = serde_json::to_value(resolved_spec()).unwrap();
+    let defs = &value["definitions"];
+    assert_eq!(
+        defs["RecursiveObject"]["properties"]["next"],
+        json!({ "$ref": "#/definitions/RecursiveObject" })
+    );
+    assert_eq!(
+        defs["RecursiveObject"]["properties"]["children"],
+        json!({
+            "type": "array",
+            "items": { "$ref": "#/definitions/RecursiveObject" },
+        })
+    );
+    assert_eq!(
+        defs["PaymentCreated"]["allOf"][0],
+        json!({ "$ref": "#/definitions/Event" })
+    );
+
+    // Anonymous schemas in operations are moved to definitions by the resolver.
+    let op = &value["paths"]["/pets"]["get"];
+    assert_eq!(
+        op["responses"]["200"]["schema"],
+        json!({ "$ref": "#/definitions/GetPetsResponse" })
+    );
+    assert_eq!(
+        defs["GetPetsResponse"],
+        json!({
+            "type": "array",
+            "items": { "$ref": "#/definitions/Pet" },
+        })
+    );
+
+    // Serializing the spec again after resolving it shouldn't change anything.
+    let api: ResolvableApi<DefaultSchema> = serde_json::from_value(value.clone()).unwrap();
+    let api = api.resolve().expect("resolution");
+    assert_eq!(serde_json::to_value(&api).unwrap(), value);
+}
+
+#[test]
+fn test_resolved_spec_modifications() {
+    let mut api = resolved_spec();
+    // Add a property to some definition referred from other definitions.
+    {
+        let mut tag = api.definitions["Tag"].write();
+        let mut prop = DefaultSchema::default();
+        prop.data_type = Some(v2::models::DataType::String);
+        tag.properties.insert("color".into(), prop.into());
+    }
+
+    // Refer to some definition from an operation (using the name of the
+    // definition instead of `$ref`).
+    let op = api
+        .paths
+        .get_mut("/pets")
+        .unwrap()
+        .methods
+        .get_mut(&HttpMethod::Get)
+        .unwrap();
+    op.responses["200"].write().schema = Some(Resolvable::Resolved {
+        new: (*api.definitions["Category"]).clone(),
+        old: Arc::new(RwLock::new(DefaultSchema::default())),
+    });
+
+    let value = serde_json::to_value(&api).unwrap();
+    assert_eq!(
+        value["definitions"]["Tag"]["properties"]["color"],
+        json!({ "type": "string" })
+    );
+    assert_eq!(
+        value["definitions"]["Pet"]["properties"]["tags"]["items"],
+        json!({ "$ref": "#/definitions/Tag" })
+    );
+    assert_eq!(
+        value["paths"]["/pets"]["get"]["responses"]["200"]["schema"],
+        json!({ "$ref": "#/definitions/Category" })
+    );
+}
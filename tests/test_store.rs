@@ -0,0 +1,71 @@
+//! Generates crates for the store spec with the codegen options which aren't
+//! used for the pet crate (tags, format types, backends, blocking client and
+//! security), so that the generated code can also be compiled.
+
+mod common;
+
+use paperclip::v2::codegen::{EmitMode, FormatTypes, HttpBackends};
+
+use std::fs;
+use std::path::PathBuf;
+
+const SPEC: &[u8] = include_bytes!("store-v2.yaml");
+
+fn store_dir(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/test_store")
+        .join(name)
+}
+
+#[test]
+fn test_async_store() {
+    let dir = common::generate(SPEC, store_dir("client"), |state| {
+        state.format_types = FormatTypes::all();
+        state.http_backends = HttpBackends {
+            hyper: true,
+            awc: true,
+        };
+        state.group_by_tags = true;
+        state.set_meta(common::store_meta(EmitMode::Crate));
+    });
+
+    let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("default = [\"reqwest\"]"));
+    // None of the definitions have patterns.
+    assert!(!manifest.contains("regex"));
+    let lib = fs::read_to_string(dir.join("lib.rs")).unwrap();
+    assert!(lib.contains("pub mod security {"));
+    assert!(lib.contains("pub mod tags {"));
+}
+
+#[test]
+fn test_blocking_store() {
+    let dir = common::generate(SPEC, store_dir("blocking"), |state| {
+        state.format_types = FormatTypes::all();
+        state.group_by_tags = true;
+        let mut meta = common::store_meta(EmitMode::Crate);
+        meta.blocking = true;
+        state.set_meta(meta);
+    });
+
+    let lib = fs::read_to_string(dir.join("lib.rs")).unwrap();
+    assert!(lib.contains("impl ApiClient for reqwest::blocking::Client {"));
+    assert!(lib.contains("pub mod security {"));
+
+    // Date-time parameters are written as RFC 3339 strings (see `wire` crate).
+    let order = fs::read_to_string(dir.join("order.rs")).unwrap();
+    assert!(order.contains("param_since: Option<crate::util::DateTimeParam>,"));
+    assert!(order.contains("pub placed: Option<crate::util::DateTime>,"));
+    let util = fs::read_to_string(dir.join("util.rs")).unwrap();
+    assert!(util.contains("f.write_str(&self.0.to_rfc3339())"));
+}
+
+#[test]
+fn test_store_cli() {
+    let dir = common::generate(SPEC, store_dir("cli"), |state| {
+        state.format_types = FormatTypes::all();
+        state.set_meta(common::store_meta(EmitMode::App));
+    });
+
+    assert!(dir.join("main.rs").exists());
+}
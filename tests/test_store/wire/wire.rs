@@ -0,0 +1,81 @@
+//! Checks the requests sent by the generated (blocking) client.
+
+use store::client::{ApiClient, ApiError, Sendable};
+use store::order::Order;
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+
+/// Client for sending the requests to a local server.
+struct LocalClient {
+    inner: reqwest::blocking::Client,
+    base_url: String,
+}
+
+impl ApiClient for LocalClient {
+    type Request = reqwest::blocking::RequestBuilder;
+    type Response = reqwest::blocking::Response;
+
+    fn request_builder(&self, method: reqwest::Method, rel_path: &str) -> Self::Request {
+        self.inner
+            .request(method, &format!("{}{}", self.base_url, rel_path))
+    }
+
+    fn make_request(&self, req: Self::Request) -> Result<Self::Response, ApiError<Self::Response>> {
+        self.inner.make_request(req)
+    }
+}
+
+/// Sends a request to a local server using the given function and returns
+/// the request received by the server.
+fn received_request<F>(send: F) -> String
+where
+    F: FnOnce(&LocalClient),
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buf = vec![0; 8192];
+        let n = stream.read(&mut buf).unwrap();
+        tx.send(String::from_utf8_lossy(&buf[..n]).into_owned())
+            .unwrap();
+        stream
+            .write_all(
+                b"HTTP/1.1 200 OK\r\ncontent-type: application/json\r\n\
+                  content-length: 2\r\nconnection: close\r\n\r\n[]",
+            )
+            .unwrap();
+    });
+
+    send(&LocalClient {
+        inner: reqwest::blocking::Client::new(),
+        base_url,
+    });
+    rx.recv().unwrap()
+}
+
+#[test]
+fn test_date_time_parameters() {
+    let time: chrono::DateTime<chrono::Utc> = "2020-01-01T00:00:00Z".parse().unwrap();
+    let req = received_request(|client| {
+        Order::list_orders()
+            .since(time)
+            .x_request_time(time)
+            .send(client)
+            .unwrap();
+    });
+
+    assert!(req.starts_with("GET /orders?since=2020-01-01T00%3A00%3A00%2B00%3A00 HTTP/1.1\r\n"));
+    assert!(req.contains("\r\nx-request-time: 2020-01-01T00:00:00+00:00\r\n"));
+}
+
+#[test]
+fn test_required_fields() {
+    let errors = Order::builder().validate().unwrap_err();
+    assert_eq!(errors.to_string(), "/id: missing required field");
+    assert!(Order::builder().id(uuid::Uuid::nil()).validate().is_ok());
+}
@@ -0,0 +1,160 @@
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+
+const SPEC: &[u8] = b"
+swagger: \"2.0\"
+info:
+  title: Store
+  version: \"1.0.0\"
+tags:
+  - name: pets
+    description: Everything about pets.
+definitions:
+  Pet:
+    type: object
+    properties:
+      id:
+        type: integer
+      name:
+        type: string
+    required: [id]
+  Order:
+    type: object
+    properties:
+      quantity:
+        type: integer
+paths:
+  /pets:
+    get:
+      tags: [pets]
+      operationId: listPets
+      responses:
+        \"200\":
+          description: Pets.
+          schema:
+            type: array
+            items:
+              $ref: \"#/definitions/Pet\"
+    post:
+      tags: [pets]
+      operationId: addPet
+      parameters:
+        - name: body
+          in: body
+          schema:
+            $ref: \"#/definitions/Pet\"
+      responses:
+        \"200\":
+          description: Added.
+  /store/orders:
+    post:
+      tags: [store, pets]
+      parameters:
+        - name: body
+          in: body
+          schema:
+            $ref: \"#/definitions/Order\"
+      responses:
+        \"200\":
+          description: Placed.
+  /store/inventory:
+    get:
+      operationId: getInventory
+      responses:
+        \"200\":
+          description: Inventory.
+          schema:
+            $ref: \"#/definitions/Order\"
+";
+
+fn generate(name: &str, group_by_tags: bool) -> PathBuf {
+    common::generate(SPEC, common::temp_dir(name), |state| {
+        state.group_by_tags = group_by_tags;
+    })
+}
+
+#[test]
+fn test_tag_modules() {
+    let dir = generate("tags", true);
+    let root = fs::read_to_string(dir.join("mod.rs")).unwrap();
+    assert!(root.contains("pub mod tags {\n    include!(\"./tags/mod.rs\");\n}"));
+    assert!(root.contains("pub struct Bound<'a, Client, B>"));
+
+    let tags = fs::read_to_string(dir.join("tags/mod.rs")).unwrap();
+    assert!(tags.starts_with(
+        "
+pub mod pets {
+    include!(\"./pets.rs\");
+}
+
+pub mod store {
+    include!(\"./store.rs\");
+}
+"
+    ));
+    assert!(tags.contains(
+        "
+    /// Operations tagged with `store`.
+    #[inline]
+    fn store(&self) -> store::Store<'_, Self> {
+        store::Store::new(self)
+    }
+"
+    ));
+    assert!(tags.contains(
+        "impl<Client: crate::client::ApiClient + Sync + 'static> ApiClientExt for Client {}"
+    ));
+
+    // Operations can be in multiple tags, and the ones without tags are left out.
+    let pets = fs::read_to_string(dir.join("tags/pets.rs")).unwrap();
+    assert!(pets.contains("/// Everything about pets.\npub struct Pets<'a, Client> {"));
+    assert!(pets.contains(
+        "
+    #[inline]
+    pub fn add_pet(&self) -> crate::client::Bound<'a, Client, crate::pet::PetPostBuilder<crate::generics::MissingId>> {
+        crate::client::Bound::new(self.client, crate::pet::Pet::add_pet())
+    }
+"
+    ));
+    assert!(pets.contains("pub fn list_pets(&self)"));
+    assert!(pets.contains("pub fn post_1(&self)"));
+    assert!(!pets.contains("get_inventory"));
+
+    let store = fs::read_to_string(dir.join("tags/store.rs")).unwrap();
+    assert_eq!(
+        store,
+        "
+/// Operations tagged with `store`.
+pub struct Store<'a, Client> {
+    client: &'a Client,
+}
+
+impl<'a, Client: crate::client::ApiClient + Sync + 'static> Store<'a, Client> {
+    /// Creates the facade for the given client.
+    #[inline]
+    pub fn new(client: &'a Client) -> Self {
+        Store { client }
+    }
+
+    #[inline]
+    pub fn post_1(&self) -> crate::client::Bound<'a, Client, crate::order::OrderPostBuilder1> {
+        crate::client::Bound::new(self.client, crate::order::Order::post_1())
+    }
+}
+"
+    );
+
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn test_no_tag_modules_by_default() {
+    let dir = generate("no-tags", false);
+    let root = fs::read_to_string(dir.join("mod.rs")).unwrap();
+    assert!(!root.contains("pub mod tags"));
+    assert!(!root.contains("Bound"));
+    assert!(!dir.join("tags").exists());
+    let _ = fs::remove_dir_all(&dir);
+}
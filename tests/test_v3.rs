@@ -0,0 +1,448 @@
+use paperclip::v2::{
+    self,
+    models::{DataType, DataTypeFormat, DefaultSchema, HttpMethod, ResolvableApi},
+    Schema as _,
+};
+use paperclip::v3::{
+    self,
+    models::{ParameterIn, ParameterStyle, Version},
+};
+
+use std::fs::File;
+use std::io::Cursor;
+
+lazy_static::lazy_static! {
+    static ref ROOT: String = String::from(env!("CARGO_MANIFEST_DIR"));
+}
+
+#[test]
+fn test_v3_parse() {
+    let fd = File::open(ROOT.clone() + "/tests/pet-v3.yaml").expect("file?");
+    let api = v3::from_reader(fd).expect("deserializing spec");
+    assert_eq!(api.openapi, Version::V3_0_3);
+    assert_eq!(api.servers[0].url, "https://pets.com:8888/api");
+
+    let op = &api.paths["/pets/{petId}"].methods[&HttpMethod::Get];
+    assert_eq!(op.operation_id.as_ref().unwrap(), "getPet");
+    let param = op.parameters[0].right().unwrap().read();
+    assert_eq!(param.in_, ParameterIn::Path);
+    let schema = param.schema.as_ref().unwrap().read();
+    assert_eq!(schema.data_type, Some(DataType::String));
+
+    let op = &api.paths["/pets"].methods[&HttpMethod::Post];
+    assert_eq!(
+        op.request_body.as_ref().unwrap().left().unwrap().reference,
+        "#/components/requestBodies/NewPet"
+    );
+
+    let pet = api.components.schemas["Pet"].read();
+    assert!(pet.properties["tag"].read().nullable);
+    assert_eq!(
+        pet.properties["friends"]
+            .read()
+            .items
+            .as_ref()
+            .unwrap()
+            .read()
+            .reference
+            .as_ref()
+            .unwrap(),
+        "#/components/schemas/Pet"
+    );
+    assert_eq!(api.components.security_schemes.len(), 1);
+}
+
+#[test]
+fn test_v3_parse_json() {
+    let spec = Cursor::new(
+        br#"{
+  "openapi": "3.0.0",
+  "info": { "title": "Foo", "version": "0.1.0" },
+  "paths": {
+    "/foo": {
+      "put": {
+        "requestBody": {
+          "content": {
+            "application/json": {
+              "schema": { "type": "object", "additionalProperties": { "type": "string" } }
+            }
+          }
+        },
+        "responses": { "204": { "description": "OK" } }
+      }
+    }
+  }
+}"#
+        .to_vec(),
+    );
+
+    let api = v3::from_reader(spec).expect("deserializing spec");
+    assert_eq!(api.openapi, Version::V3_0_0);
+    let op = &api.paths["/foo"].methods[&HttpMethod::Put];
+    let body = op.request_body.as_ref().unwrap().right().unwrap().read();
+    let media = body.content.values().next().unwrap();
+    let schema = media.schema.as_ref().unwrap().read();
+    assert_eq!(schema.data_type, Some(DataType::Object));
+    assert!(schema.extra_props.as_ref().unwrap().right().is_some());
+}
+
+#[test]
+fn test_v3_unsupported_version() {
+    let spec = Cursor::new(
+        b"
+openapi: 3.1.0
+info:
+  title: Foo
+  version: 0.1.0
+paths: {}
+"
+        .to_vec(),
+    );
+
+    assert!(v3::from_reader(spec).is_err());
+}
+
+#[test]
+fn test_v3_resolve() {
+    let fd = File::open(ROOT.clone() + "/tests/pet-v3.yaml").expect("file?");
+    let api = v3::from_reader(fd)
+        .expect("deserializing spec")
+        .resolve()
+        .expect("resolution");
+
+    let schemas = &api.components.schemas;
+    assert_eq!(
+        schemas.keys().map(String::as_str).collect::<Vec<_>>(),
+        vec![
+            "Error",
+            "GetPets200ResponseJson",
+            "NewPetBodyJson",
+            "Pet",
+            "PostPets201ResponseJson"
+        ]
+    );
+
+    let pet = schemas["Pet"].read();
+    assert!(pet.is_cyclic());
+    assert_eq!(pet.name(), Some("Pet"));
+
+    let op = &api.paths["/pets"].methods[&HttpMethod::Post];
+    let body = op.request_body.as_ref().unwrap().right().unwrap().read();
+    let schema = body
+        .content
+        .values()
+        .next()
+        .unwrap()
+        .schema
+        .as_ref()
+        .unwrap();
+    assert_eq!(schema.read().name(), Some("NewPetBodyJson"));
+    assert_eq!(
+        schema.read().properties["name"].read().data_type,
+        Some(DataType::String)
+    );
+
+    let op = &api.paths["/pets"].methods[&HttpMethod::Get];
+    let param = op.parameters[0].right().unwrap().read();
+    assert_eq!(param.name, "limit");
+    let resp = op.responses["200"].right().unwrap().read();
+    let header = resp.headers["x-next"].right().unwrap().read();
+    assert_eq!(
+        header.schema.as_ref().unwrap().read().data_type,
+        Some(DataType::String)
+    );
+    let schema = resp
+        .content
+        .values()
+        .next()
+        .unwrap()
+        .schema
+        .as_ref()
+        .unwrap();
+    let items = schema
+        .read()
+        .items
+        .as_ref()
+        .unwrap()
+        .read()
+        .name()
+        .map(String::from);
+    assert_eq!(items.as_deref(), Some("Pet"));
+    let error = op.responses["default"].right().unwrap().read();
+    assert_eq!(error.description, "unexpected error");
+}
+
+#[test]
+fn test_v3_resolve_anonymous_responses() {
+    let spec = Cursor::new(
+        b"
+openapi: 3.0.1
+info:
+  title: Foo
+  version: 0.1.0
+components:
+  schemas:
+    Foo:
+      $ref: '#/components/schemas/Bar'
+    Bar:
+      type: string
+paths:
+  /foo:
+    get:
+      responses:
+        '200':
+          description: Foo.
+          content:
+            application/json:
+              schema:
+                type: object
+                properties:
+                  foo:
+                    $ref: '#/components/schemas/Foo'
+        '201':
+          description: Created.
+          content:
+            application/json:
+              schema:
+                type: integer
+        '404':
+          description: Not found.
+          content:
+            application/json:
+              schema:
+                type: string
+            application/problem+json:
+              schema:
+                type: object
+        default:
+          description: Error.
+          content:
+            application/json:
+              schema:
+                type: boolean
+"
+        .to_vec(),
+    );
+
+    let api = v3::from_reader(spec)
+        .expect("deserializing spec")
+        .resolve()
+        .expect("resolution");
+    let schemas = &api.components.schemas;
+    assert_eq!(
+        schemas.keys().map(String::as_str).collect::<Vec<_>>(),
+        vec![
+            "Bar",
+            "Foo",
+            "GetFoo200ResponseJson",
+            "GetFoo201ResponseJson",
+            "GetFoo404ResponseJson",
+            "GetFoo404ResponseProblemJson",
+            "GetFooDefaultResponseJson"
+        ]
+    );
+    let types = [
+        "GetFoo200ResponseJson",
+        "GetFoo201ResponseJson",
+        "GetFoo404ResponseJson",
+        "GetFoo404ResponseProblemJson",
+        "GetFooDefaultResponseJson",
+    ]
+    .iter()
+    .map(|n| schemas[*n].read().data_type)
+    .collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        &[
+            Some(DataType::Object),
+            Some(DataType::Integer),
+            Some(DataType::String),
+            Some(DataType::Object),
+            Some(DataType::Boolean)
+        ]
+    );
+
+    let resp = schemas["GetFoo200ResponseJson"].read();
+    let foo = resp.properties["foo"].read();
+    assert_eq!(foo.data_type, Some(DataType::String));
+}
+
+#[test]
+fn test_v3_missing_reference() {
+    let spec = Cursor::new(
+        b"
+openapi: 3.0.1
+info:
+  title: Foo
+  version: 0.1.0
+paths:
+  /foo:
+    get:
+      responses:
+        '200':
+          $ref: '#/components/responses/Foo'
+"
+        .to_vec(),
+    );
+
+    let api = v3::from_reader(spec).expect("deserializing spec");
+    assert_eq!(
+        api.resolve().unwrap_err().to_string(),
+        "Reference missing in spec: Foo"
+    );
+}
+
+#[test]
+fn test_v2_to_v3_conversion() {
+    let spec = Cursor::new(
+        b"
+swagger: \"2.0\"
+info:
+  title: Petstore
+  version: 1.0.0
+host: pets.com
+basePath: /api
+schemes: [http, https]
+consumes: [application/json, application/yaml]
+securityDefinitions:
+  basic:
+    type: basic
+  token:
+    type: apiKey
+    name: X-Token
+    in: header
+  oauth:
+    type: oauth2
+    flow: application
+    tokenUrl: https://pets.com/token
+    scopes:
+      read: Read access
+definitions:
+  Pet:
+    type: object
+    properties:
+      friend:
+        $ref: '#/definitions/Pet'
+paths:
+  /pets:
+    post:
+      parameters:
+      - name: body
+        in: body
+        required: true
+        schema:
+          $ref: '#/definitions/Pet'
+      - name: tags
+        in: query
+        type: array
+        collectionFormat: pipes
+        items:
+          type: string
+        x-example-tags: [cat, dog]
+      responses:
+        '201':
+          description: Created
+          headers:
+            X-Id:
+              type: integer
+              format: int64
+  /pets/{id}/photo:
+    put:
+      consumes: [multipart/form-data]
+      parameters:
+      - name: id
+        in: path
+        required: true
+        type: string
+      - name: photo
+        in: formData
+        type: file
+        required: true
+      responses:
+        '204':
+          description: Updated
+",
+    );
+
+    let api: ResolvableApi<DefaultSchema> = v2::from_reader(spec).expect("deserializing spec");
+    let api = v3::Api::from_v2(&api).expect("converting spec");
+    assert_eq!(
+        api.servers.iter().map(|s| &*s.url).collect::<Vec<_>>(),
+        vec!["http://pets.com/api", "https://pets.com/api"]
+    );
+
+    let schemes = &api.components.security_schemes;
+    let basic = schemes["basic"].right().unwrap();
+    assert_eq!(
+        (&*basic.type_, basic.scheme.as_deref()),
+        ("http", Some("basic"))
+    );
+    let token = schemes["token"].right().unwrap();
+    assert_eq!(token.in_.as_deref(), Some("header"));
+    let oauth = schemes["oauth"].right().unwrap();
+    let flow = oauth.flows.as_ref().unwrap().client_credentials.as_ref();
+    assert_eq!(
+        flow.unwrap().token_url.as_deref(),
+        Some("https://pets.com/token")
+    );
+
+    let pet = api.components.schemas["Pet"].read();
+    assert_eq!(
+        pet.properties["friend"].read().reference.as_deref(),
+        Some("#/components/schemas/Pet")
+    );
+
+    let op = &api.paths["/pets"].methods[&HttpMethod::Post];
+    let body = op.request_body.as_ref().unwrap().right().unwrap().read();
+    assert!(body.required);
+    assert_eq!(
+        body.content
+            .keys()
+            .map(|k| k.0.as_ref())
+            .collect::<Vec<_>>(),
+        vec!["application/json", "application/yaml"]
+    );
+    let param = op.parameters[0].right().unwrap().read();
+    assert_eq!(param.style, Some(ParameterStyle::PipeDelimited));
+    assert_eq!(
+        param.extensions["x-example-tags"],
+        serde_json::json!(["cat", "dog"])
+    );
+    let schema = param.schema.as_ref().unwrap().read();
+    assert_eq!(schema.data_type, Some(DataType::Array));
+    let resp = op.responses["201"].right().unwrap().read();
+    assert!(resp.content.is_empty());
+    let header = resp.headers["X-Id"].right().unwrap().read();
+    assert_eq!(
+        header.schema.as_ref().unwrap().read().format,
+        Some(DataTypeFormat::Int64)
+    );
+
+    let op = &api.paths["/pets/{id}/photo"].methods[&HttpMethod::Put];
+    assert_eq!(op.parameters.len(), 1);
+    let body = op.request_body.as_ref().unwrap().right().unwrap().read();
+    let form = body.content.values().next().unwrap();
+    let schema = form.schema.as_ref().unwrap().read();
+    assert!(schema.required.contains("photo"));
+    let photo = schema.properties["photo"].read();
+    assert_eq!(photo.data_type, Some(DataType::String));
+    assert_eq!(photo.format, Some(DataTypeFormat::Binary));
+}
+
+#[test]
+fn test_v2_to_v3_conversion_round_trip() {
+    let fd = File::open(ROOT.clone() + "/tests/pet-v2.yaml").expect("file?");
+    let api: ResolvableApi<DefaultSchema> = v2::from_reader(fd).expect("deserializing spec");
+    let api = v3::Api::from_v2(&api).expect("converting spec");
+    assert_eq!(api.servers[0].url, "//pets.com:8888/api");
+
+    let op = &api.paths["/pets/{petId}"];
+    assert_eq!(
+        op.parameters[0].left().unwrap().reference,
+        "#/components/parameters/petID"
+    );
+
+    let json = serde_json::to_vec(&api).expect("serializing spec");
+    let api = v3::from_reader(Cursor::new(json)).expect("deserializing converted spec");
+    api.resolve().expect("resolving converted spec");
+}